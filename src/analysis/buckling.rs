@@ -0,0 +1,234 @@
+use crate::analysis::linear_static::LinearStaticResults;
+use crate::analysis::*;
+use crate::elements::frame::{
+    frame_element_geometric_stiffness_matrix, transform_frame_stiffness_local_to_world,
+};
+use crate::models::frame::FrameElement;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::{DMatrix, DVector, Vector6};
+use std::collections::HashMap;
+
+pub struct BucklingMode {
+    /// Factor on the loads of the static case at which this mode becomes critical
+    pub load_factor: f64,
+    /// World mode shape `[Ux, Uy, Uz, Rx, Ry, Rz]` of every node, scaled so the largest
+    /// component is one
+    pub shape: HashMap<String, Vector6<f64>>,
+}
+
+pub struct BucklingResults {
+    /// Buckling modes in order of increasing load factor
+    pub modes: Vec<BucklingMode>,
+}
+
+/**
+ * Runs a linear (eigenvalue) buckling analysis for the loads of a preceding static analysis.
+ *
+ * The axial forces of the static case form the world geometric stiffness `kg`, and the
+ * generalized eigenproblem `k φ = -λ kg φ` is solved for the critical load factors `λ` and mode
+ * shapes `φ`. Only positive load factors are reported.
+ *
+ * # Arguments
+ *
+ * `static_results` - results of `linear_static::run_linear_static_analysis` for the current model
+ * `mode_count` - the number of lowest buckling modes to return
+ */
+pub fn run_linear_buckling_analysis(
+    static_results: &LinearStaticResults,
+    mode_count: usize,
+) -> Result<BucklingResults, String> {
+    let world = ANALYSIS_DATA.read().unwrap().world_stiffness.clone();
+    if world.is_empty() {
+        return Err("Run a linear static analysis before a buckling analysis".to_string());
+    }
+    let geometric = assemble_world_geometric_stiffness_matrix(static_results)?;
+
+    let partition = partition_degrees_of_freedom();
+    let stiffness = reduce_to_free_dofs_dense(&world, &partition);
+    let geometric = reduce_to_free_dofs_dense(&geometric, &partition);
+
+    let modes = critical_load_factors(&stiffness, &geometric, mode_count)?
+        .into_iter()
+        .map(|(load_factor, shape)| BucklingMode {
+            load_factor,
+            shape: nodal_shape(&shape, &partition),
+        })
+        .collect();
+
+    Ok(BucklingResults { modes })
+}
+
+/**
+ * Returns the non-zero entries of the world geometric stiffness matrix built from the frame
+ * axial forces of a static analysis.
+ *
+ * # Arguments
+ *
+ * `static_results` - results providing the local end forces of every frame
+ */
+pub fn assemble_world_geometric_stiffness_matrix(
+    static_results: &LinearStaticResults,
+) -> Result<HashMap<(usize, usize), f64>, String> {
+    let frames: Vec<FrameElement> = ELEMENT_DATA
+        .read()
+        .unwrap()
+        .frames
+        .values()
+        .cloned()
+        .collect();
+
+    frames
+        .iter()
+        .try_fold(HashMap::<(usize, usize), f64>::new(), |acc, frame| {
+            let end_forces = match static_results.frame_end_forces.get(&frame.id) {
+                Some(x) => x,
+                None => {
+                    return Err(format!(
+                        "Failed to locate forces of frame id = {}",
+                        frame.id
+                    ))
+                }
+            };
+            // The force on the end node along local x is the axial force, tension positive
+            let local = frame_element_geometric_stiffness_matrix(frame, end_forces[6]);
+            let world = transform_frame_stiffness_local_to_world(frame, &local);
+            merge_matrix_at_frame_dofs(acc, frame, &world)
+        })
+}
+
+/**
+ * Returns the lowest positive eigenvalues `λ` and eigenvectors `φ` of `k φ = -λ kg φ`, in
+ * order of increasing `λ`, with each eigenvector scaled so its largest component is one.
+ *
+ * With the Cholesky factor `k = L Lᵀ`, the problem becomes the standard symmetric problem
+ * `C ψ = μ ψ` where `C = -L⁻¹ kg L⁻ᵀ`, `μ = 1 / λ` and `φ = L⁻ᵀ ψ`.
+ *
+ * # Arguments
+ *
+ * `stiffness` - the elastic stiffness `k` of the free degrees of freedom
+ * `geometric` - the geometric stiffness `kg` of the free degrees of freedom
+ * `mode_count` - the number of eigenpairs to return
+ */
+pub fn critical_load_factors(
+    stiffness: &DMatrix<f64>,
+    geometric: &DMatrix<f64>,
+    mode_count: usize,
+) -> Result<Vec<(f64, DVector<f64>)>, String> {
+    let cholesky = match stiffness.clone().cholesky() {
+        Some(x) => x,
+        None => return Err("The world stiffness matrix is not positive definite".to_string()),
+    };
+    let l = cholesky.l();
+    let lt = l.transpose();
+
+    let half = match l.solve_lower_triangular(&(-geometric)) {
+        Some(x) => x,
+        None => return Err("Failed to reduce the geometric stiffness matrix".to_string()),
+    };
+    let reduced = match l.solve_lower_triangular(&half.transpose()) {
+        Some(x) => x,
+        None => return Err("Failed to reduce the geometric stiffness matrix".to_string()),
+    };
+    let symmetric = (&reduced + reduced.transpose()) * 0.5;
+    let eigen = symmetric.symmetric_eigen();
+
+    let largest = eigen.eigenvalues.amax();
+    let mut pairs: Vec<(f64, usize)> = eigen
+        .eigenvalues
+        .iter()
+        .enumerate()
+        .filter(|(_, mu)| **mu > 1e-12 * largest)
+        .map(|(i, mu)| (1. / mu, i))
+        .collect();
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    pairs.truncate(mode_count);
+
+    pairs
+        .into_iter()
+        .map(|(load_factor, i)| {
+            let psi = eigen.eigenvectors.column(i).into_owned();
+            match lt.solve_upper_triangular(&psi) {
+                Some(phi) => Ok((load_factor, normalize_to_largest(phi))),
+                None => Err("Failed to recover the buckling mode shape".to_string()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::frame::frame_element_stiffness_matrix;
+    use crate::models::frame::*;
+    use crate::models::material::IsotropicMaterial;
+    use crate::models::node::Node;
+    use crate::*;
+    use na::{Matrix3, Point3, Vector6};
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn cantilever_column_matches_euler_load() {
+        // A 4 m cantilever along x in four elements, fixed at node 0, bending about z only
+        let (length, segments) = (4., 4);
+        let (E, Iz) = (200e6, 8e-6);
+        for i in 0..=segments {
+            add_node(Node {
+                id: format!("buckling_{}", i),
+                degrees_of_freedom: Vector6::from_iterator(6 * i..6 * i + 6),
+                coordinate: Point3::new(length * i as f64 / segments as f64, 0., 0.),
+                local_axes: None,
+            });
+        }
+
+        // Keep the in-plane deflection and rotation (Uy, Rz) of the free nodes
+        let kept: Vec<usize> = (1..=segments)
+            .flat_map(|i| vec![6 * i + 1, 6 * i + 5])
+            .collect();
+        let mut k = DMatrix::<f64>::zeros(kept.len(), kept.len());
+        let mut kg = DMatrix::<f64>::zeros(kept.len(), kept.len());
+
+        for i in 0..segments {
+            let frame = FrameElement {
+                id: format!("buckling_{}", i),
+                start_node_id: format!("buckling_{}", i),
+                end_node_id: format!("buckling_{}", i + 1),
+                start_releases: FrameEndReleases::fully_fixed(),
+                end_releases: FrameEndReleases::fully_fixed(),
+                geometry: FrameGeometry {
+                    cross_section: CrossSection {
+                        A: 1e-3,
+                        Avy: 0.,
+                        Avz: 0.,
+                        J: 1e-6,
+                        Iy: 8e-6,
+                        Iz,
+                    },
+                    local_axes: Matrix3::identity(),
+                    taper: None,
+                },
+                material: IsotropicMaterial::new(E, 0.3),
+                end_offsets: FrameEndOffsets::none(),
+                beam_theory: BeamTheory::EulerBernoulli,
+                foundation: None,
+            };
+            let element_k = frame_element_stiffness_matrix(&frame);
+            let element_kg = frame_element_geometric_stiffness_matrix(&frame, -1.);
+            for a in 0..12 {
+                for b in 0..12 {
+                    let (dof_a, dof_b) = (6 * i + a, 6 * i + b);
+                    if let (Some(p), Some(q)) = (
+                        kept.iter().position(|x| *x == dof_a),
+                        kept.iter().position(|x| *x == dof_b),
+                    ) {
+                        k[(p, q)] += element_k[(a, b)];
+                        kg[(p, q)] += element_kg[(a, b)];
+                    }
+                }
+            }
+        }
+
+        let modes = critical_load_factors(&k, &kg, 1).unwrap();
+        let euler = std::f64::consts::PI.powi(2) * E * Iz / (4. * length * length);
+        assert_relative_eq!(modes[0].0, euler, max_relative = 1e-3);
+    }
+}
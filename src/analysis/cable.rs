@@ -0,0 +1,275 @@
+use crate::analysis::linear_static::*;
+use crate::analysis::*;
+use crate::elements::cable::{cable_element_state, cable_profile, CableState};
+use crate::models::cable::CableElement;
+use crate::models::node::Node;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::{Point3, Vector3, Vector6};
+use std::collections::HashMap;
+
+/// Controls for the Newton-Raphson iteration of a cable analysis.
+#[derive(Clone, Debug)]
+pub struct CableAnalysisSettings {
+    /// Convergence is reached once `‖δΔ‖ / ‖Δ‖` of an iteration falls to or below this value
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    /// Number of points along each cable's profile including both ends, at least 2
+    pub profile_stations: usize,
+}
+
+impl Default for CableAnalysisSettings {
+    fn default() -> Self {
+        CableAnalysisSettings {
+            tolerance: 1e-8,
+            max_iterations: 50,
+            profile_stations: 11,
+        }
+    }
+}
+
+pub struct CableResult {
+    pub unstressed_length: f64,
+    /// Tension at the start of the cable
+    pub start_tension: f64,
+    /// Tension at the end of the cable, which differs from the start by the weight between them
+    pub end_tension: f64,
+    /// World force exerted by the cable on its start node
+    pub start_force: Vector3<f64>,
+    /// World force exerted by the cable on its end node
+    pub end_force: Vector3<f64>,
+    /// Deformed world positions at equal intervals of unstressed length from start to end
+    pub profile: Vec<Point3<f64>>,
+    /// Largest distance of the profile from the chord between the displaced nodes
+    pub sag: f64,
+    /// True if the cable carries no tension
+    pub slack: bool,
+}
+
+pub struct CableAnalysisResults {
+    /// Displacements, reactions and frame end forces of the deformed structure. The applied
+    /// resultant of its equilibrium check includes the forces of the cables on their nodes, and
+    /// so the weight of the cables.
+    pub results: LinearStaticResults,
+    pub cables: HashMap<String, CableResult>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// The forces the nodes exert on the cables at every world degree of freedom, with the tangent
+/// stiffness of the structure and the state of each cable
+struct CableContributions {
+    forces: Vec<f64>,
+    tangent: HashMap<(usize, usize), f64>,
+    states: Vec<CableState>,
+}
+
+/// A cable along with its unstressed length
+struct Cable {
+    element: CableElement,
+    unstressed_length: f64,
+}
+
+/**
+ * Runs a static analysis of a model with cables, whose stiffness depends on their tension and
+ * the displaced positions of their nodes.
+ *
+ * The frames and supports respond linearly while each cable follows its elastic catenary
+ * between the displaced nodes. Newton-Raphson iteration solves `kₜ δΔ = F − k Δ − q(Δ)`, where
+ * `q` are the forces the nodes exert on the cables and `kₜ` adds the tangent stiffness of the
+ * cables to the linear stiffness `k`, until the displacement increments settle. The cable
+ * weights are carried without being applied as loads.
+ *
+ * # Arguments
+ *
+ * `settings` - convergence tolerance, iteration limit and profile stations
+ */
+pub fn run_cable_analysis(
+    settings: &CableAnalysisSettings,
+) -> Result<CableAnalysisResults, String> {
+    let (world, warnings) = assemble_validated_world_stiffness()?;
+
+    let partition = partition_degrees_of_freedom();
+    let mut forces = ANALYSIS_DATA.read().unwrap().applied_forces.clone();
+    forces.resize(partition.dof_count, 0.);
+
+    let (nodes, cables) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let cables: Vec<Cable> = data
+            .cables
+            .values()
+            .map(|x| {
+                let chord = na::distance(
+                    &data.nodes[&x.start_node_id].coordinate,
+                    &data.nodes[&x.end_node_id].coordinate,
+                );
+                Cable {
+                    element: x.clone(),
+                    unstressed_length: x.unstressed_length(chord),
+                }
+            })
+            .collect();
+        (data.nodes.clone(), cables)
+    };
+
+    let mut deflections = partition.prescribed_deflections();
+    let mut contributions = cable_contributions(&world, &cables, &nodes, &deflections)?;
+    check_world_stiffness(&contributions.tangent)?;
+
+    let mut iterations = 0;
+    let mut converged = false;
+    for iteration in 1..=settings.max_iterations.max(1) {
+        let linear = multiply_stiffness(&world, &deflections);
+        let residual: Vec<f64> = (0..partition.dof_count)
+            .map(|i| forces[i] - linear[i] - contributions.forces[i])
+            .collect();
+        let increment =
+            solve_free_degrees_of_freedom(&contributions.tangent, &residual, &partition).map_err(
+                |e| {
+                    format!(
+                        "Stiffness is singular at iteration {}, as when slack cables leave nodes \
+                     unsupported: {}",
+                        iteration, e
+                    )
+                },
+            )?;
+        for (x, dx) in deflections.iter_mut().zip(increment.iter()) {
+            *x += dx;
+        }
+        contributions = cable_contributions(&world, &cables, &nodes, &deflections)?;
+        iterations = iteration;
+
+        let norm = deflections.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let change = increment.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if cables.is_empty() || change <= settings.tolerance * norm {
+            converged = true;
+            break;
+        }
+    }
+
+    // The cables act on the nodes like applied forces in the reactions and statics check
+    let effective_forces: Vec<f64> = forces
+        .iter()
+        .zip(contributions.forces.iter())
+        .map(|(f, q)| f - q)
+        .collect();
+    let mut results = collect_results(&world, &effective_forces, &deflections);
+    results.warnings = warnings;
+
+    let mut cable_results = HashMap::<String, CableResult>::new();
+    for (cable, state) in cables.iter().zip(contributions.states) {
+        let (start, end) = displaced_ends(cable, &nodes, &deflections);
+        let profile = cable_profile(
+            &cable.element,
+            cable.unstressed_length,
+            &start,
+            &end,
+            settings.profile_stations,
+        )?;
+        let chord = (end - start).normalize();
+        let sag = profile
+            .iter()
+            .map(|x| {
+                let offset = x - start;
+                (offset - chord * offset.dot(&chord)).norm()
+            })
+            .fold(0., f64::max);
+        cable_results.insert(
+            cable.element.id.clone(),
+            CableResult {
+                unstressed_length: cable.unstressed_length,
+                start_tension: state.start_tension,
+                end_tension: state.end_tension,
+                start_force: state.start_force,
+                end_force: state.end_force,
+                profile,
+                sag,
+                slack: state.slack,
+            },
+        );
+    }
+
+    Ok(CableAnalysisResults {
+        results,
+        cables: cable_results,
+        iterations,
+        converged,
+    })
+}
+
+/// Returns the world positions of the start and end node of a cable once displaced
+fn displaced_ends(
+    cable: &Cable,
+    nodes: &HashMap<String, Node>,
+    deflections: &[f64],
+) -> (Point3<f64>, Point3<f64>) {
+    let displaced = |node: &Node| {
+        let nodal = node.degrees_of_freedom.map(|dof| deflections[dof]);
+        let world = node.to_world(&nodal);
+        node.coordinate + Vector3::new(world[0], world[1], world[2])
+    };
+    (
+        displaced(&nodes[&cable.element.start_node_id]),
+        displaced(&nodes[&cable.element.end_node_id]),
+    )
+}
+
+/**
+ * Returns the contributions of the cables at the given deflections.
+ *
+ * # Arguments
+ *
+ * `world` - non-zero entries of the linear world stiffness matrix
+ * `cables` - the cables with their unstressed lengths
+ * `nodes` - all nodes of the model
+ * `deflections` - deflections at every world degree of freedom
+ */
+fn cable_contributions(
+    world: &HashMap<(usize, usize), f64>,
+    cables: &[Cable],
+    nodes: &HashMap<String, Node>,
+    deflections: &[f64],
+) -> Result<CableContributions, String> {
+    let mut cable_forces = vec![0.; deflections.len()];
+    let mut tangent = world.clone();
+    let mut states = Vec::<CableState>::with_capacity(cables.len());
+
+    for cable in cables.iter() {
+        let (start_position, end_position) = displaced_ends(cable, nodes, deflections);
+        let state = cable_element_state(
+            &cable.element,
+            cable.unstressed_length,
+            &start_position,
+            &end_position,
+        )?;
+        let ends = [
+            (&nodes[&cable.element.start_node_id], state.start_force, 1.),
+            (&nodes[&cable.element.end_node_id], state.end_force, -1.),
+        ];
+
+        for (node, force, _) in ends.iter() {
+            let nodal = node.from_world(&Vector6::new(force.x, force.y, force.z, 0., 0., 0.));
+            for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+                cable_forces[*dof] -= nodal[i];
+            }
+        }
+        for (a, _, sign_a) in ends.iter() {
+            for (b, _, sign_b) in ends.iter() {
+                let block =
+                    translational_block_at_nodes(a, b, &(state.stiffness * (sign_a * sign_b)));
+                for i in 0..3 {
+                    for j in 0..3 {
+                        let (row, column) = (a.degrees_of_freedom[i], b.degrees_of_freedom[j]);
+                        *tangent.entry((row, column)).or_insert(0.) += block[(i, j)];
+                    }
+                }
+            }
+        }
+        states.push(state);
+    }
+
+    Ok(CableContributions {
+        forces: cable_forces,
+        tangent,
+        states,
+    })
+}
@@ -0,0 +1,382 @@
+use crate::analysis::partition_degrees_of_freedom;
+use crate::models::node::Direction;
+use crate::ELEMENT_DATA;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Diagonal stiffness at or below this fraction of the largest diagonal is treated as zero.
+const ZERO_STIFFNESS_TOLERANCE: f64 = 1e-12;
+
+/// An elimination pivot at or below this fraction of its original diagonal indicates a mechanism.
+const MECHANISM_TOLERANCE: f64 = 1e-10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A free degree of freedom to which no element contributes stiffness
+    ZeroStiffness,
+    /// A free degree of freedom that becomes unstable once the others are restrained
+    Mechanism,
+    /// A group of connected nodes with no support anywhere in the group
+    DisconnectedSubstructure,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DofLocation {
+    pub node_id: String,
+    pub direction: Direction,
+}
+
+#[derive(Clone, Debug)]
+pub struct StiffnessDiagnostic {
+    pub kind: DiagnosticKind,
+    pub node_ids: Vec<String>,
+    /// The offending degrees of freedom, which for a disconnected sub-structure are all of its
+    /// free degrees of freedom
+    pub locations: Vec<DofLocation>,
+    pub message: String,
+}
+
+/**
+ * Returns the reasons the world stiffness matrix is singular, or an empty list if the
+ * structure is stable.
+ *
+ * Three checks are run in turn, each skipping degrees of freedom already reported:
+ * * groups of nodes connected by elements but not to any support
+ * * free degrees of freedom with no stiffness at all, e.g. unrestrained rotations at truss
+ *   joints or torsion at a node where every frame releases torsion
+ * * free degrees of freedom which can displace without resistance once all of the preceding
+ *   degrees of freedom are held, i.e. mechanisms
+ *
+ * The mechanism check eliminates the sparse free stiffness in the solution order of
+ * `partition_degrees_of_freedom`, so its cost follows the fill-in of a direct factorization
+ * rather than growing with the cube of the number of degrees of freedom.
+ *
+ * # Arguments
+ *
+ * `world` - non-zero entries of the assembled world stiffness matrix
+ */
+pub fn diagnose_world_stiffness(world: &HashMap<(usize, usize), f64>) -> Vec<StiffnessDiagnostic> {
+    let partition = partition_degrees_of_freedom();
+    let world = &partition.constrain(world);
+    let (owners, components) = degree_of_freedom_owners_and_components();
+
+    let mut diagnostics = Vec::<StiffnessDiagnostic>::new();
+    let mut excluded = HashSet::<usize>::new();
+
+    let spring_node_ids: HashSet<String> = ELEMENT_DATA
+        .read()
+        .unwrap()
+        .supports
+        .values()
+        .filter(|x| x.has_springs())
+        .map(|x| x.node_id.clone())
+        .collect();
+
+    // Sub-structures that are not connected to any support
+    for (node_ids, node_dofs) in components.iter() {
+        let restrained = node_dofs
+            .iter()
+            .any(|dof| partition.restrained.binary_search(dof).is_ok())
+            || node_ids.iter().any(|id| spring_node_ids.contains(id));
+        if restrained {
+            continue;
+        }
+        excluded.extend(node_dofs.iter());
+        diagnostics.push(StiffnessDiagnostic {
+            kind: DiagnosticKind::DisconnectedSubstructure,
+            node_ids: node_ids.clone(),
+            locations: node_dofs
+                .iter()
+                .filter(|dof| partition.free_index.contains_key(dof))
+                .filter_map(|dof| owners.get(dof).cloned())
+                .collect(),
+            message: format!(
+                "Nodes [{}] form a sub-structure that is not connected to any support",
+                node_ids.join(", ")
+            ),
+        });
+    }
+
+    // Free degrees of freedom without any stiffness
+    let diagonal = |dof: &usize| world.get(&(*dof, *dof)).cloned().unwrap_or(0.);
+    let largest_diagonal = partition
+        .free
+        .iter()
+        .map(|dof| diagonal(dof).abs())
+        .fold(0., f64::max);
+    let zero_dofs: Vec<usize> = partition
+        .free
+        .iter()
+        .cloned()
+        .filter(|dof| !excluded.contains(dof))
+        .filter(|dof| diagonal(dof).abs() <= ZERO_STIFFNESS_TOLERANCE * largest_diagonal)
+        .collect();
+    excluded.extend(zero_dofs.iter());
+    diagnostics.extend(group_by_node(&zero_dofs, &owners).into_iter().map(
+        |(node_id, locations)| StiffnessDiagnostic {
+            kind: DiagnosticKind::ZeroStiffness,
+            message: format!(
+                "Node {} has no stiffness in {}",
+                node_id,
+                directions_list(&locations)
+            ),
+            node_ids: vec![node_id],
+            locations,
+        },
+    ));
+
+    // Mechanisms among the remaining free degrees of freedom
+    let remaining: Vec<usize> = partition
+        .free
+        .iter()
+        .cloned()
+        .filter(|dof| !excluded.contains(dof))
+        .collect();
+    let position: HashMap<usize, usize> = remaining
+        .iter()
+        .enumerate()
+        .map(|(i, dof)| (*dof, i))
+        .collect();
+    let mut remaining_entries = HashMap::<(usize, usize), f64>::new();
+    for ((i, j), value) in world.iter() {
+        if let (Some(row), Some(column)) = (position.get(i), position.get(j)) {
+            *remaining_entries.entry((*row, *column)).or_insert(0.) += value;
+        }
+    }
+    let mechanism_dofs: Vec<usize> = unstable_pivots(&remaining_entries, remaining.len())
+        .into_iter()
+        .map(|i| remaining[i])
+        .collect();
+    diagnostics.extend(group_by_node(&mechanism_dofs, &owners).into_iter().map(
+        |(node_id, locations)| StiffnessDiagnostic {
+            kind: DiagnosticKind::Mechanism,
+            message: format!(
+                "Node {} forms part of a mechanism and is unstable in {}",
+                node_id,
+                directions_list(&locations)
+            ),
+            node_ids: vec![node_id],
+            locations,
+        },
+    ));
+
+    diagnostics
+}
+
+/**
+ * Returns the indices of the pivots which vanish during symmetric Gaussian elimination
+ * of the stiffness matrix.
+ *
+ * A vanishing pivot means the degree of freedom can displace freely once all earlier degrees
+ * of freedom are held. It is then treated as restrained so the elimination can continue, so
+ * each returned index corresponds to one independent mechanism. Only the upper triangle is
+ * stored and eliminated, row by row, so the work follows the fill-in of the ordering.
+ *
+ * # Arguments
+ *
+ * `stiffness` - non-zero entries of the symmetric stiffness matrix of the free degrees of
+ *               freedom
+ * `n` - the number of free degrees of freedom
+ */
+pub fn unstable_pivots(stiffness: &HashMap<(usize, usize), f64>, n: usize) -> Vec<usize> {
+    let mut rows = vec![BTreeMap::<usize, f64>::new(); n];
+    for ((i, j), value) in stiffness.iter() {
+        if i <= j && *j < n {
+            *rows[*i].entry(*j).or_insert(0.) += value;
+        }
+    }
+    let original: Vec<f64> = rows
+        .iter()
+        .enumerate()
+        .map(|(k, row)| row.get(&k).cloned().unwrap_or(0.).abs())
+        .collect();
+    let mut unstable = Vec::<usize>::new();
+
+    for k in 0..n {
+        let row = std::mem::take(&mut rows[k]);
+        let pivot = row.get(&k).cloned().unwrap_or(0.);
+        if pivot <= MECHANISM_TOLERANCE * original[k] {
+            unstable.push(k);
+            continue;
+        }
+        let coupled: Vec<(usize, f64)> = row.range(k + 1..).map(|(j, x)| (*j, *x)).collect();
+        for (a, (i, a_ki)) in coupled.iter().enumerate() {
+            let factor = a_ki / pivot;
+            for (j, a_kj) in coupled[a..].iter() {
+                *rows[*i].entry(*j).or_insert(0.) -= factor * a_kj;
+            }
+        }
+    }
+
+    unstable
+}
+
+type Component = (Vec<String>, Vec<usize>);
+
+/// Maps every world degree of freedom to its node and direction, and groups the nodes into
+/// sub-structures connected by frame elements or constraints.
+fn degree_of_freedom_owners_and_components() -> (HashMap<usize, DofLocation>, Vec<Component>) {
+    let data = ELEMENT_DATA.read().unwrap();
+
+    let mut owners = HashMap::<usize, DofLocation>::new();
+    for node in data.nodes.values() {
+        for direction in Direction::ALL.iter() {
+            owners.insert(
+                node.degrees_of_freedom[direction.index()],
+                DofLocation {
+                    node_id: node.id.clone(),
+                    direction: *direction,
+                },
+            );
+        }
+    }
+
+    let mut node_ids: Vec<&String> = data.nodes.keys().collect();
+    node_ids.sort();
+    let index: HashMap<&String, usize> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect();
+
+    let mut parent: Vec<usize> = (0..node_ids.len()).collect();
+    for frame in data.frames.values() {
+        if let (Some(a), Some(b)) = (
+            index.get(&frame.start_node_id),
+            index.get(&frame.end_node_id),
+        ) {
+            let (root_a, root_b) = (find_root(&mut parent, *a), find_root(&mut parent, *b));
+            parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+    for cable in data.cables.values() {
+        if let (Some(a), Some(b)) = (
+            index.get(&cable.start_node_id),
+            index.get(&cable.end_node_id),
+        ) {
+            let (root_a, root_b) = (find_root(&mut parent, *a), find_root(&mut parent, *b));
+            parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+    let element_node_ids = data
+        .membranes
+        .values()
+        .map(|x| &x.node_ids)
+        .chain(data.shells.values().map(|x| &x.node_ids));
+    for node_ids in element_node_ids {
+        let connected: Vec<usize> = node_ids
+            .iter()
+            .filter_map(|x| index.get(x).cloned())
+            .collect();
+        for pair in connected.windows(2) {
+            let (root_a, root_b) = (
+                find_root(&mut parent, pair[0]),
+                find_root(&mut parent, pair[1]),
+            );
+            parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+    for constraint in data.constraints.values() {
+        let connected: Vec<usize> = constraint
+            .node_ids()
+            .iter()
+            .filter_map(|x| index.get(&x.to_string()).cloned())
+            .collect();
+        for pair in connected.windows(2) {
+            let (root_a, root_b) = (
+                find_root(&mut parent, pair[0]),
+                find_root(&mut parent, pair[1]),
+            );
+            parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+
+    let mut grouped = HashMap::<usize, Component>::new();
+    for (i, id) in node_ids.iter().enumerate() {
+        let root = find_root(&mut parent, i);
+        let entry = grouped.entry(root).or_insert((Vec::new(), Vec::new()));
+        entry.0.push((*id).clone());
+        entry.1.extend(data.nodes[*id].degrees_of_freedom.iter());
+    }
+    let mut components: Vec<Component> = grouped.into_values().collect();
+    components.sort_by(|a, b| a.0.cmp(&b.0));
+
+    (owners, components)
+}
+
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Groups degrees of freedom by owning node, in order of first appearance
+fn group_by_node(
+    dofs: &[usize],
+    owners: &HashMap<usize, DofLocation>,
+) -> Vec<(String, Vec<DofLocation>)> {
+    let mut grouped = Vec::<(String, Vec<DofLocation>)>::new();
+    for dof in dofs.iter() {
+        let location = match owners.get(dof) {
+            Some(x) => x.clone(),
+            None => continue,
+        };
+        match grouped.iter_mut().find(|(id, _)| *id == location.node_id) {
+            Some((_, locations)) => locations.push(location),
+            None => grouped.push((location.node_id.clone(), vec![location])),
+        }
+    }
+    grouped
+}
+
+fn directions_list(locations: &[DofLocation]) -> String {
+    locations
+        .iter()
+        .map(|x| x.direction.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::DMatrix;
+
+    fn unstable_dense_pivots(stiffness: DMatrix<f64>) -> Vec<usize> {
+        let mut entries = HashMap::<(usize, usize), f64>::new();
+        for i in 0..stiffness.nrows() {
+            for j in 0..stiffness.ncols() {
+                if stiffness[(i, j)] != 0. {
+                    entries.insert((i, j), stiffness[(i, j)]);
+                }
+            }
+        }
+        unstable_pivots(&entries, stiffness.nrows())
+    }
+
+    #[test]
+    fn unstable_pivots_finds_each_independent_mechanism() {
+        // Two springs k = 1 in series between three free points: one rigid body mode
+        #[rustfmt::skip]
+        let free_chain = DMatrix::from_row_slice(3, 3, &[ 1., -1.,  0.,
+                                                         -1.,  2., -1.,
+                                                          0., -1.,  1.]);
+        assert_eq!(unstable_dense_pivots(free_chain).len(), 1);
+
+        // Grounding the first point leaves a stable system
+        #[rustfmt::skip]
+        let grounded_chain = DMatrix::from_row_slice(3, 3, &[ 2., -1.,  0.,
+                                                             -1.,  2., -1.,
+                                                              0., -1.,  1.]);
+        assert!(unstable_dense_pivots(grounded_chain).is_empty());
+
+        // Two disconnected free springs each contribute a rigid body mode
+        #[rustfmt::skip]
+        let split = DMatrix::from_row_slice(4, 4, &[ 1., -1.,  0.,  0.,
+                                                    -1.,  1.,  0.,  0.,
+                                                     0.,  0.,  1., -1.,
+                                                     0.,  0., -1.,  1.]);
+        assert_eq!(unstable_dense_pivots(split), vec![1, 3]);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::elements::frame::{
+    frame_element_local_stiffness_matrix, frame_end_forces_at_flexible_ends,
+    frame_end_offset_transform, integrate_along_frame,
+};
+use crate::models::frame::{BeamTheory, CrossSection, FrameElement, FrameEndRelease};
+use crate::utils::transform::world_to_local_transform;
+use na::{Vector2, Vector6, VectorN, U12};
+use std::collections::HashMap;
+
+pub struct FrameDiagram {
+    /// Distances of the stations from the start of the flexible length
+    pub stations: Vec<f64>,
+    /// Local internal forces `[N, Vy, Vz, T, My, Mz]` at each station, exerted on the member up
+    /// to the station by the rest of it. Tension is positive and the forces at the last station
+    /// equal the end forces.
+    pub forces: Vec<Vector6<f64>>,
+    /// Local displacements `[ux, uy, uz, θx, θy, θz]` of the member axis at each station
+    pub displacements: Vec<Vector6<f64>>,
+    /// Local forces per unit length `[qy, qz]` which any foundation exerts on the member at each
+    /// station, zero for a frame without a foundation
+    pub soil_pressures: Vec<Vector2<f64>>,
+}
+
+/**
+ * Returns the internal forces and displacements at equally spaced stations along the flexible
+ * length of a frame carrying no loads between its nodes other than its foundation.
+ *
+ * The displacements integrate the curvature `M / EI`, and for the Timoshenko beam theory the
+ * shear strain `V / GAv`, of the section at each point along the frame, so they follow the
+ * varying stiffness of a tapered frame. Where an end release breaks the continuity of the member
+ * with the start node, the start displacement follows from the end instead.
+ *
+ * On an elastic foundation the transverse displacements instead follow the cubic displacement
+ * functions behind the foundation stiffness, and the soil pressure `-k v` they give is carried
+ * into the shears and moments. Such frames may not have bending end releases.
+ *
+ * # Arguments
+ *
+ * `frame` - the frame, whose nodes must exist
+ * `displacements` - world displacements `[Ux, Uy, Uz, Rx, Ry, Rz]` of the nodes, such as those
+ * of a linear static analysis
+ * `station_count` - the number of stations including both ends, at least 2
+ */
+#[allow(non_snake_case)]
+pub fn frame_diagram(
+    frame: &FrameElement,
+    displacements: &HashMap<String, Vector6<f64>>,
+    station_count: usize,
+) -> Result<FrameDiagram, String> {
+    if station_count < 2 {
+        return Err("A frame diagram needs at least two stations".to_string());
+    }
+    let (start, end) = match (
+        displacements.get(&frame.start_node_id),
+        displacements.get(&frame.end_node_id),
+    ) {
+        (Some(x), Some(y)) => (x, y),
+        _ => {
+            return Err(format!(
+                "Failed to find the displacements of frame {}",
+                frame.id
+            ))
+        }
+    };
+    let length = frame.flexible_length_or_inf();
+    if !length.is_finite() {
+        return Err(format!("Failed to find the nodes of frame {}", frame.id));
+    }
+
+    let world = VectorN::<f64, U12>::from_iterator(start.iter().chain(end.iter()).cloned());
+    let nodal = world_to_local_transform(&frame.geometry.local_axes) * world;
+    let node_forces = frame_element_local_stiffness_matrix(frame) * nodal;
+    let u = frame_end_offset_transform(frame) * nodal;
+    let f = frame_end_forces_at_flexible_ends(frame, &node_forces);
+
+    let (ky, kz) = match &frame.foundation {
+        Some(x) => (x.ky, x.kz),
+        None => (0., 0.),
+    };
+    let on_foundation = frame.foundation.is_some();
+    let start_releases = &frame.start_releases;
+    let end_releases = &frame.end_releases;
+    let is_free = |x: &FrameEndRelease| *x == FrameEndRelease::Free;
+    if on_foundation
+        && [start_releases, end_releases]
+            .iter()
+            .any(|x| is_free(&x.Vy) || is_free(&x.Vz) || is_free(&x.My) || is_free(&x.Mz))
+    {
+        return Err(format!(
+            "Frame {} on an elastic foundation has bending end releases, which its diagram does \
+             not support",
+            frame.id
+        ));
+    }
+
+    // Transverse displacements and rotations [v, w, θy, θz] from the cubic displacement functions
+    let cubic = |x: f64| {
+        let xi = x / length;
+        let (xi2, xi3) = (xi * xi, xi * xi * xi);
+        let n = [
+            1. - 3. * xi2 + 2. * xi3,
+            length * (xi - 2. * xi2 + xi3),
+            3. * xi2 - 2. * xi3,
+            length * (xi3 - xi2),
+        ];
+        let slope = [
+            6. * (xi2 - xi) / length,
+            1. - 4. * xi + 3. * xi2,
+            6. * (xi - xi2) / length,
+            3. * xi2 - 2. * xi,
+        ];
+        let v = n[0] * u[1] + n[1] * u[5] + n[2] * u[7] + n[3] * u[11];
+        let w = n[0] * u[2] - n[1] * u[4] + n[2] * u[8] - n[3] * u[10];
+        let rz = slope[0] * u[1] + slope[1] * u[5] + slope[2] * u[7] + slope[3] * u[11];
+        let ry = -(slope[0] * u[2] - slope[1] * u[4] + slope[2] * u[8] - slope[3] * u[10]);
+        [v, w, ry, rz]
+    };
+    let soil = |x: f64| {
+        if !on_foundation {
+            return Vector2::zeros();
+        }
+        let displaced = cubic(x);
+        Vector2::new(-ky * displaced[0], -kz * displaced[1])
+    };
+
+    let internal = |x: f64| {
+        let mut forces = Vector6::new(
+            -f[0],
+            -f[1],
+            -f[2],
+            -f[3],
+            -(f[4] + x * f[2]),
+            -(f[5] - x * f[1]),
+        );
+        if on_foundation {
+            let load = |power: i32, i: usize| {
+                integrate_along_frame(x, |s| (x - s).powi(power) * soil(s)[i])
+            };
+            forces[1] -= load(0, 0);
+            forces[2] -= load(0, 1);
+            forces[4] -= load(1, 1);
+            forces[5] += load(1, 0);
+        }
+        forces
+    };
+
+    let (E, G) = (frame.material.E, frame.material.G);
+    let shear = frame.beam_theory == BeamTheory::Timoshenko;
+    let axial = |x: &CrossSection| E * x.A;
+    let torsion = |x: &CrossSection| G * x.J;
+    let bending_y = |x: &CrossSection| E * x.Iy;
+    let bending_z = |x: &CrossSection| E * x.Iz;
+    let shear_y = |x: &CrossSection| if shear { G * x.Avy } else { 0. };
+    let shear_z = |x: &CrossSection| if shear { G * x.Avz } else { 0. };
+
+    // Integral from 0 to x of (x - s)ⁿ p(s) / k(s), where p is a component of the internal forces
+    // and k the matching stiffness of the section, taking zero stiffness as no flexibility
+    let integrate =
+        |x: f64, power: i32, component: usize, stiffness: &dyn Fn(&CrossSection) -> f64| {
+            integrate_along_frame(x, |s| {
+                let k = stiffness(&frame.geometry.cross_section_at(s / length));
+                if k == 0. || !k.is_finite() {
+                    0.
+                } else if component == 0 || component == 3 {
+                    // The foundation adds no axial force or torque, which stay constant
+                    (x - s).powi(power) * -f[component] / k
+                } else {
+                    (x - s).powi(power) * internal(s)[component] / k
+                }
+            })
+        };
+
+    let axial_start = if is_free(&start_releases.A) && !is_free(&end_releases.A) {
+        u[6] - integrate(length, 0, 0, &axial)
+    } else {
+        u[0]
+    };
+    let twist_start = if is_free(&start_releases.T) && !is_free(&end_releases.T) {
+        u[9] - integrate(length, 0, 3, &torsion)
+    } else {
+        u[3]
+    };
+    // On a foundation the cubic displacement functions give the bending displacements instead
+    let (v_start, rz_start, w_start, ry_start) = if on_foundation {
+        (u[1], u[5], u[2], u[4])
+    } else {
+        let (v_start, rz_start) = bending_start(
+            (u[1], u[5]),
+            (u[7], u[11]),
+            (is_free(&start_releases.Vy), is_free(&start_releases.Mz)),
+            length,
+            integrate(length, 0, 5, &bending_z),
+            integrate(length, 1, 5, &bending_z) + integrate(length, 0, 1, &shear_y),
+        );
+        // About y the rotation is the negative slope, so the same relations hold for -θy
+        let (w_start, negative_ry_start) = bending_start(
+            (u[2], -u[4]),
+            (u[8], -u[10]),
+            (is_free(&start_releases.Vz), is_free(&start_releases.My)),
+            length,
+            -integrate(length, 0, 4, &bending_y),
+            -integrate(length, 1, 4, &bending_y) + integrate(length, 0, 2, &shear_z),
+        );
+        (v_start, rz_start, w_start, -negative_ry_start)
+    };
+
+    let stations: Vec<f64> = (0..station_count)
+        .map(|i| length * i as f64 / (station_count - 1) as f64)
+        .collect();
+    let forces = stations.iter().map(|x| internal(*x)).collect();
+    let displacements = stations
+        .iter()
+        .map(|x| {
+            let x = *x;
+            let axial = axial_start + integrate(x, 0, 0, &axial);
+            let twist = twist_start + integrate(x, 0, 3, &torsion);
+            if on_foundation {
+                let [v, w, ry, rz] = cubic(x);
+                return Vector6::new(axial, v, w, twist, ry, rz);
+            }
+            Vector6::new(
+                axial,
+                v_start
+                    + x * rz_start
+                    + integrate(x, 1, 5, &bending_z)
+                    + integrate(x, 0, 1, &shear_y),
+                w_start - x * ry_start - integrate(x, 1, 4, &bending_y)
+                    + integrate(x, 0, 2, &shear_z),
+                twist,
+                ry_start + integrate(x, 0, 4, &bending_y),
+                rz_start + integrate(x, 0, 5, &bending_z),
+            )
+        })
+        .collect();
+    let soil_pressures = stations.iter().map(|x| soil(*x)).collect();
+
+    Ok(FrameDiagram {
+        stations,
+        forces,
+        displacements,
+        soil_pressures,
+    })
+}
+
+/// Returns the transverse displacement and rotation at the start of the flexible member in one
+/// plane, where `v₂ = v₁ + L θ₁ + deflection` and `θ₂ = θ₁ + rotation`. A released start takes
+/// its values from the end rather than the start node.
+fn bending_start(
+    start: (f64, f64),
+    end: (f64, f64),
+    (shear_released, moment_released): (bool, bool),
+    length: f64,
+    rotation: f64,
+    deflection: f64,
+) -> (f64, f64) {
+    match (shear_released, moment_released) {
+        (false, false) => start,
+        (false, true) => (start.0, (end.0 - start.0 - deflection) / length),
+        (true, false) => (end.0 - length * start.1 - deflection, start.1),
+        (true, true) => {
+            let rotation_start = end.1 - rotation;
+            (end.0 - length * rotation_start - deflection, rotation_start)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::add_node;
+    use crate::elements::frame::frame_element_tapered_stiffness_matrix;
+    use crate::models::frame::*;
+    use crate::models::material::IsotropicMaterial;
+    use crate::models::node::Node;
+    use na::{Matrix3, Matrix6, Point3};
+
+    #[test]
+    fn tapered_cantilever_diagram_reaches_tip_displacement() {
+        add_node(Node {
+            id: "diagram_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "diagram_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(4., 0., 0.),
+            local_axes: None,
+        });
+        let section = |inertia: f64| CrossSection {
+            A: 2.,
+            Avy: 1.,
+            Avz: 1.,
+            J: 1.,
+            Iy: inertia,
+            Iz: inertia,
+        };
+        let frame = FrameElement {
+            id: "diagram".to_string(),
+            start_node_id: "diagram_a".to_string(),
+            end_node_id: "diagram_b".to_string(),
+            start_releases: FrameEndReleases::fully_fixed(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry {
+                cross_section: section(2.),
+                local_axes: Matrix3::identity(),
+                taper: Some(FrameTaper {
+                    end_cross_section: section(1.),
+                    variation: TaperVariation::Linear,
+                }),
+            },
+            material: IsotropicMaterial::new(200., 0.25),
+            end_offsets: FrameEndOffsets::none(),
+            beam_theory: BeamTheory::Timoshenko,
+            foundation: None,
+        };
+
+        // Tip loads in both planes on a cantilever fixed at the start
+        let k = frame_element_tapered_stiffness_matrix(&frame);
+        let tip = Matrix6::<f64>::from_fn(|i, j| k[(i + 6, j + 6)]);
+        let tip_displacement = tip.try_inverse().unwrap() * Vector6::new(0., 1., 2., 0., 0., 0.);
+        let displacements: HashMap<String, Vector6<f64>> = vec![
+            ("diagram_a".to_string(), Vector6::zeros()),
+            ("diagram_b".to_string(), tip_displacement),
+        ]
+        .into_iter()
+        .collect();
+
+        let diagram = frame_diagram(&frame, &displacements, 5).unwrap();
+        let last = diagram.displacements[4];
+        for i in 0..6 {
+            assert_abs_diff_eq!(last[i], tip_displacement[i], epsilon = 1e-9);
+        }
+        assert_abs_diff_eq!(diagram.displacements[0].amax(), 0., epsilon = 1e-12);
+
+        // Moments fall linearly from the fixed start to zero at the tip
+        assert_relative_eq!(diagram.forces[0][5], 4., max_relative = 1e-9);
+        assert_relative_eq!(diagram.forces[0][4], -8., max_relative = 1e-9);
+        assert_relative_eq!(diagram.forces[2][5], 2., max_relative = 1e-9);
+        assert_abs_diff_eq!(diagram.forces[4][5], 0., epsilon = 1e-9);
+        assert_relative_eq!(diagram.forces[2][1], 1., max_relative = 1e-9);
+    }
+
+    #[test]
+    fn translated_frame_on_foundation_carries_uniform_soil_pressure() {
+        add_node(Node {
+            id: "winkler_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "winkler_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(3., 0., 0.),
+            local_axes: None,
+        });
+        let frame = FrameElement {
+            id: "winkler".to_string(),
+            start_node_id: "winkler_a".to_string(),
+            end_node_id: "winkler_b".to_string(),
+            start_releases: FrameEndReleases::fully_fixed(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry::new(
+                Matrix3::identity(),
+                CrossSection {
+                    A: 1.,
+                    Avy: 1.,
+                    Avz: 1.,
+                    J: 1.,
+                    Iy: 1.,
+                    Iz: 1.,
+                },
+            ),
+            material: IsotropicMaterial::new(200., 0.25),
+            end_offsets: FrameEndOffsets::none(),
+            beam_theory: BeamTheory::EulerBernoulli,
+            foundation: Some(ElasticFoundation { ky: 5., kz: 2. }),
+        };
+
+        // A rigid translation only compresses the foundation
+        let translation = Vector6::new(0., 1., -2., 0., 0., 0.);
+        let displacements: HashMap<String, Vector6<f64>> = vec![
+            ("winkler_a".to_string(), translation),
+            ("winkler_b".to_string(), translation),
+        ]
+        .into_iter()
+        .collect();
+        let k = frame_element_local_stiffness_matrix(&frame);
+        let u = VectorN::<f64, U12>::from_iterator(
+            translation.iter().chain(translation.iter()).cloned(),
+        );
+        let node_forces = k * u;
+        assert_relative_eq!(node_forces[1] + node_forces[7], 15., max_relative = 1e-12);
+        assert_relative_eq!(node_forces[2] + node_forces[8], -12., max_relative = 1e-12);
+
+        let diagram = frame_diagram(&frame, &displacements, 7).unwrap();
+        for i in 0..7 {
+            assert_relative_eq!(diagram.soil_pressures[i][0], -5., max_relative = 1e-12);
+            assert_relative_eq!(diagram.soil_pressures[i][1], 4., max_relative = 1e-12);
+            assert_relative_eq!(diagram.displacements[i][1], 1., max_relative = 1e-12);
+            assert_abs_diff_eq!(diagram.displacements[i][5], 0., epsilon = 1e-12);
+        }
+
+        // The soil pressure brings the internal forces from the start to the end forces
+        for i in 0..6 {
+            assert_abs_diff_eq!(diagram.forces[6][i], node_forces[i + 6], epsilon = 1e-9);
+        }
+        // At midspan the end forces kL/2 and moments kL²/12 leave a moment of kL²/24
+        assert_relative_eq!(diagram.forces[3][5], 5. * 9. / 24., max_relative = 1e-9);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::analysis::linear_static::assemble_checked_world_stiffness;
+use crate::analysis::modal::*;
+use crate::analysis::time_history::RayleighDamping;
+use crate::analysis::*;
+use crate::ELEMENT_DATA;
+use na::{DMatrix, DVector, Vector6};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Damping used for the steady-state response.
+#[derive(Clone, Debug)]
+pub enum HarmonicDamping {
+    /// The same damping ratio in every mode, solved by superposition of the lowest modes
+    Modal {
+        damping_ratio: f64,
+        mode_count: usize,
+    },
+    /// Damping matrix `C = a0 M + a1 k`, solved directly on the assembled matrices
+    Rayleigh(RayleighDamping),
+}
+
+/// World force and moment amplitudes `[Fx, Fy, Fz, Mx, My, Mz]` at a node, all in phase.
+#[derive(Clone, Debug)]
+pub struct HarmonicLoad {
+    pub node_id: String,
+    pub amplitude: Vector6<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HarmonicSettings {
+    /// Cyclic frequencies of the load to evaluate
+    pub frequencies: Vec<f64>,
+    pub loads: Vec<HarmonicLoad>,
+    pub damping: HarmonicDamping,
+    pub mass_formulation: MassFormulation,
+    /// Nodes whose response is reported
+    pub output_node_ids: Vec<String>,
+}
+
+/// Steady-state motion `A cos(Ωt + θ)` of each world direction `[Ux, Uy, Uz, Rx, Ry, Rz]`
+/// under loads varying as `cos(Ωt)`.
+pub struct NodeResponse {
+    pub amplitude: Vector6<f64>,
+    /// Phase angle `θ` in radians, negative where the response lags the load
+    pub phase: Vector6<f64>,
+}
+
+pub struct FrequencyResponse {
+    /// Cyclic frequency of the load
+    pub frequency: f64,
+    pub nodes: HashMap<String, NodeResponse>,
+}
+
+/// Real and imaginary parts of the steady-state displacement of the free degrees of freedom.
+pub type ComplexDisplacements = (DVector<f64>, DVector<f64>);
+
+/**
+ * Returns the steady-state response at the requested nodes to harmonic loads at each frequency.
+ *
+ * The model is validated and the world stiffness is checked as for a linear static analysis.
+ * With Rayleigh damping the complex equations `(k − Ω² M + iΩ C) U = F` are solved directly at
+ * every frequency; with modal damping the lowest modes are found once and superposed.
+ *
+ * # Arguments
+ *
+ * `settings` - frequencies, loads, damping and requested output
+ */
+pub fn run_harmonic_analysis(
+    settings: &HarmonicSettings,
+) -> Result<Vec<FrequencyResponse>, String> {
+    let (world, _) = assemble_checked_world_stiffness()?;
+    let partition = partition_degrees_of_freedom();
+    let stiffness = reduce_to_free_dofs_dense(&world, &partition);
+    let mass = reduce_to_free_dofs_dense(
+        &assemble_world_mass_matrix(settings.mass_formulation)?,
+        &partition,
+    );
+
+    let nodes = ELEMENT_DATA.read().unwrap().nodes.clone();
+    let mut forces = DVector::<f64>::zeros(partition.free.len());
+    for load in settings.loads.iter() {
+        let node = match nodes.get(&load.node_id) {
+            Some(x) => x,
+            None => return Err(format!("Failed to locate node id = {}", load.node_id)),
+        };
+        let amplitude = node.from_world(&load.amplitude);
+        for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+            for (row, c) in partition.free_components(*dof).iter() {
+                forces[*row] += c * amplitude[i];
+            }
+        }
+    }
+    for id in settings.output_node_ids.iter() {
+        if !nodes.contains_key(id) {
+            return Err(format!("Failed to locate node id = {}", id));
+        }
+    }
+
+    let method = match &settings.damping {
+        HarmonicDamping::Modal {
+            damping_ratio,
+            mode_count,
+        } => {
+            let modal = ModalSettings::default();
+            let modes = subspace_iteration(
+                &stiffness,
+                &mass,
+                *mode_count,
+                modal.tolerance,
+                modal.max_iterations,
+            )?;
+            if !modes.converged {
+                return Err("The modes for modal damping did not converge".to_string());
+            }
+            SolutionMethod::Modal(modes, *damping_ratio)
+        }
+        HarmonicDamping::Rayleigh(rayleigh) => SolutionMethod::Direct(
+            &mass * rayleigh.mass_coefficient + &stiffness * rayleigh.stiffness_coefficient,
+        ),
+    };
+
+    settings
+        .frequencies
+        .iter()
+        .map(|frequency| {
+            let omega = 2. * PI * frequency;
+            let (real, imaginary) = match &method {
+                SolutionMethod::Modal(modes, damping_ratio) => modal_frequency_response(
+                    &modes.eigenvalues,
+                    &modes.eigenvectors,
+                    &forces,
+                    omega,
+                    *damping_ratio,
+                ),
+                SolutionMethod::Direct(damping) => {
+                    direct_frequency_response(&stiffness, &mass, damping, &forces, omega)?
+                }
+            };
+
+            let (real, imaginary) = (
+                partition.expand(real.as_slice()),
+                partition.expand(imaginary.as_slice()),
+            );
+            let response = settings
+                .output_node_ids
+                .iter()
+                .map(|id| {
+                    let node = &nodes[id];
+                    let (real, imaginary) = (
+                        node.to_world(&node.degrees_of_freedom.map(|dof| real[dof])),
+                        node.to_world(&node.degrees_of_freedom.map(|dof| imaginary[dof])),
+                    );
+                    let amplitude = real.zip_map(&imaginary, |x, y| x.hypot(y));
+                    let phase = real.zip_map(&imaginary, |x, y| y.atan2(x));
+                    (id.clone(), NodeResponse { amplitude, phase })
+                })
+                .collect();
+
+            Ok(FrequencyResponse {
+                frequency: *frequency,
+                nodes: response,
+            })
+        })
+        .collect()
+}
+
+enum SolutionMethod {
+    Modal(SubspaceSolution, f64),
+    Direct(DMatrix<f64>),
+}
+
+/**
+ * Solves `(k − Ω² M + iΩ C) U = F` for a real load amplitude `F`.
+ *
+ * The complex equations are solved as the real system
+ * `[k − Ω² M, −Ω C; Ω C, k − Ω² M] [Ur; Ui] = [F; 0]` of twice the size.
+ *
+ * # Arguments
+ *
+ * `stiffness` - stiffness `k` of the free degrees of freedom
+ * `mass` - mass `M` of the free degrees of freedom
+ * `damping` - damping `C` of the free degrees of freedom
+ * `forces` - load amplitudes at the free degrees of freedom
+ * `omega` - circular frequency `Ω` of the load
+ */
+pub fn direct_frequency_response(
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+    damping: &DMatrix<f64>,
+    forces: &DVector<f64>,
+    omega: f64,
+) -> Result<ComplexDisplacements, String> {
+    let n = stiffness.nrows();
+    let dynamic = stiffness - mass * (omega * omega);
+    let viscous = damping * omega;
+
+    let mut system = DMatrix::<f64>::zeros(2 * n, 2 * n);
+    system.slice_mut((0, 0), (n, n)).copy_from(&dynamic);
+    system.slice_mut((n, n), (n, n)).copy_from(&dynamic);
+    system.slice_mut((0, n), (n, n)).copy_from(&(-&viscous));
+    system.slice_mut((n, 0), (n, n)).copy_from(&viscous);
+
+    let mut rhs = DVector::<f64>::zeros(2 * n);
+    rhs.rows_mut(0, n).copy_from(forces);
+
+    match system.lu().solve(&rhs) {
+        Some(x) => Ok((x.rows(0, n).into_owned(), x.rows(n, n).into_owned())),
+        None => Err(format!(
+            "The undamped structure is in resonance at {} Hz",
+            omega / (2. * PI)
+        )),
+    }
+}
+
+/**
+ * Superposes the steady-state responses `qn = φnᵀ F / (ωn² − Ω² + 2iζ ωn Ω)` of the modes.
+ *
+ * # Arguments
+ *
+ * `eigenvalues` - the squared circular frequencies `ωn²` of the modes
+ * `shapes` - the mode shapes in columns, normalized so `φᵀ M φ = 1`
+ * `forces` - load amplitudes at the free degrees of freedom
+ * `omega` - circular frequency `Ω` of the load
+ * `damping_ratio` - damping ratio `ζ` of every mode
+ */
+pub fn modal_frequency_response(
+    eigenvalues: &[f64],
+    shapes: &DMatrix<f64>,
+    forces: &DVector<f64>,
+    omega: f64,
+    damping_ratio: f64,
+) -> ComplexDisplacements {
+    let mut real = DVector::<f64>::zeros(shapes.nrows());
+    let mut imaginary = DVector::<f64>::zeros(shapes.nrows());
+
+    for (i, eigenvalue) in eigenvalues.iter().enumerate() {
+        let shape = shapes.column(i);
+        let modal_force = shape.dot(forces);
+        let (a, b) = (
+            eigenvalue - omega * omega,
+            2. * damping_ratio * eigenvalue.sqrt() * omega,
+        );
+        let denominator = a * a + b * b;
+        if denominator == 0. {
+            continue;
+        }
+        real += shape * (modal_force * a / denominator);
+        imaginary -= shape * (modal_force * b / denominator);
+    }
+    (real, imaginary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Single degree of freedom with `k = 4`, `m = 1`, so `ω = 2`
+    #[test]
+    fn direct_and_modal_match_single_degree_of_freedom() {
+        let (zeta, omega) = (0.05, 1.5);
+        let stiffness = DMatrix::<f64>::from_element(1, 1, 4.);
+        let mass = DMatrix::<f64>::identity(1, 1);
+        let damping = DMatrix::<f64>::from_element(1, 1, 2. * zeta * 2.);
+        let forces = DVector::<f64>::from_element(1, 1.);
+
+        let r: f64 = omega / 2.;
+        let amplitude = 0.25 / ((1. - r * r).powi(2) + (2. * zeta * r).powi(2)).sqrt();
+        let phase = -(2. * zeta * r).atan2(1. - r * r);
+
+        let (real, imaginary) =
+            direct_frequency_response(&stiffness, &mass, &damping, &forces, omega).unwrap();
+        assert_relative_eq!(real[0].hypot(imaginary[0]), amplitude, max_relative = 1e-12);
+        assert_relative_eq!(imaginary[0].atan2(real[0]), phase, max_relative = 1e-12);
+
+        let (real, imaginary) = modal_frequency_response(&[4.], &mass, &forces, omega, zeta);
+        assert_relative_eq!(real[0].hypot(imaginary[0]), amplitude, max_relative = 1e-12);
+        assert_relative_eq!(imaginary[0].atan2(real[0]), phase, max_relative = 1e-12);
+    }
+}
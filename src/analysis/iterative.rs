@@ -0,0 +1,372 @@
+use crate::utils::ZERO_EPSILON;
+use std::collections::HashMap;
+
+/// Preconditioners available to the conjugate gradient solver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preconditioner {
+    /// Scales the residual by the inverse of the stiffness diagonal
+    Jacobi,
+    /// Zero fill-in incomplete Cholesky factor of the stiffness, IC(0)
+    IncompleteCholesky,
+}
+
+/// Controls for the preconditioned conjugate gradient solver.
+#[derive(Clone, Debug)]
+pub struct ConjugateGradientSettings {
+    pub preconditioner: Preconditioner,
+    /// Convergence is reached once `‖r‖ / ‖F‖` falls to or below this value
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for ConjugateGradientSettings {
+    fn default() -> Self {
+        ConjugateGradientSettings {
+            preconditioner: Preconditioner::Jacobi,
+            tolerance: 1e-10,
+            max_iterations: 10_000,
+        }
+    }
+}
+
+/// Iterations and residuals of a conjugate gradient solution, reported with analysis results.
+#[derive(Clone, Debug)]
+pub struct ConjugateGradientConvergence {
+    pub iterations: usize,
+    pub converged: bool,
+    /// Relative residual `‖r‖ / ‖F‖` at the start and after every iteration
+    pub residual_history: Vec<f64>,
+}
+
+pub struct ConjugateGradientSolution {
+    pub deflections: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+    /// Relative residual `‖r‖ / ‖F‖` at the start and after every iteration
+    pub residual_history: Vec<f64>,
+}
+
+/**
+ * Returns the world displacement vectors for all free degrees of freedom using the
+ * preconditioned conjugate gradient method.
+ *
+ * Solves equations of the form `F = k Δ` without factoring `k`, so the memory required is
+ * proportional to the number of non-zero stiffness entries. This is an alternative to
+ * `solve_for_deflections` for models too large to factor directly. The stiffness matrix must
+ * be symmetric positive definite, i.e. the structure must be stable.
+ *
+ * A solution is returned even when the iteration limit is reached before convergence; check
+ * `converged` and `residual_history` on the result.
+ *
+ * # Arguments
+ *
+ * `stiffness` - non-zero entries of the world stiffness matrix for the free degrees of freedom
+ * `forces` - forces acting at the free degrees of freedom
+ * `settings` - preconditioner, tolerance and iteration limit
+ */
+pub fn solve_for_deflections_iteratively(
+    stiffness: &HashMap<(usize, usize), f64>,
+    forces: Vec<f64>,
+    settings: &ConjugateGradientSettings,
+) -> Result<ConjugateGradientSolution, String> {
+    let n = forces.len();
+    let rows = compressed_rows(stiffness, n)?;
+    let preconditioner = match settings.preconditioner {
+        Preconditioner::Jacobi => PreconditionerFactor::Jacobi(inverse_diagonal(&rows)?),
+        Preconditioner::IncompleteCholesky => {
+            PreconditionerFactor::IncompleteCholesky(incomplete_cholesky(&rows)?)
+        }
+    };
+
+    let mut deflections = vec![0.; n];
+    let mut residual = forces;
+    let force_norm = norm(&residual);
+    if force_norm == 0. {
+        return Ok(ConjugateGradientSolution {
+            deflections,
+            iterations: 0,
+            converged: true,
+            residual_history: vec![0.],
+        });
+    }
+
+    let mut residual_history = vec![1.];
+    let mut z = preconditioner.apply(&residual);
+    let mut direction = z.clone();
+    let mut rz = dot(&residual, &z);
+
+    for iteration in 1..=settings.max_iterations {
+        let q = multiply(&rows, &direction);
+        let curvature = dot(&direction, &q);
+        if curvature <= 0. {
+            return Err(format!(
+                "Stiffness matrix is not positive definite (encountered at iteration {})",
+                iteration
+            ));
+        }
+
+        let alpha = rz / curvature;
+        for i in 0..n {
+            deflections[i] += alpha * direction[i];
+            residual[i] -= alpha * q[i];
+        }
+
+        let relative_residual = norm(&residual) / force_norm;
+        residual_history.push(relative_residual);
+        if relative_residual <= settings.tolerance {
+            return Ok(ConjugateGradientSolution {
+                deflections,
+                iterations: iteration,
+                converged: true,
+                residual_history,
+            });
+        }
+
+        z = preconditioner.apply(&residual);
+        let rz_next = dot(&residual, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        for i in 0..n {
+            direction[i] = z[i] + beta * direction[i];
+        }
+    }
+
+    Ok(ConjugateGradientSolution {
+        deflections,
+        iterations: settings.max_iterations,
+        converged: false,
+        residual_history,
+    })
+}
+
+impl ConjugateGradientSolution {
+    /// Returns the iterations and residuals of the solution without the deflections
+    pub fn convergence(&self) -> ConjugateGradientConvergence {
+        ConjugateGradientConvergence {
+            iterations: self.iterations,
+            converged: self.converged,
+            residual_history: self.residual_history.clone(),
+        }
+    }
+}
+
+enum PreconditionerFactor {
+    Jacobi(Vec<f64>),
+    IncompleteCholesky(LowerTriangular),
+}
+
+impl PreconditionerFactor {
+    fn apply(&self, residual: &[f64]) -> Vec<f64> {
+        match self {
+            PreconditionerFactor::Jacobi(inverse) => residual
+                .iter()
+                .zip(inverse.iter())
+                .map(|(r, d)| r * d)
+                .collect(),
+            PreconditionerFactor::IncompleteCholesky(factor) => factor.solve(residual),
+        }
+    }
+}
+
+/// Row `i` holds the strictly lower entries `(j, Lij)` sorted by column, plus the diagonal `Lii`
+struct LowerTriangular {
+    rows: Vec<Vec<(usize, f64)>>,
+    diagonal: Vec<f64>,
+}
+
+impl LowerTriangular {
+    /// Solves `L Lᵀ z = r`
+    fn solve(&self, residual: &[f64]) -> Vec<f64> {
+        let n = residual.len();
+        let mut y = residual.to_vec();
+        for i in 0..n {
+            let sum: f64 = self.rows[i].iter().map(|(j, l)| l * y[*j]).sum();
+            y[i] = (y[i] - sum) / self.diagonal[i];
+        }
+        for i in (0..n).rev() {
+            y[i] /= self.diagonal[i];
+            for (k, l) in self.rows[i].iter() {
+                y[*k] -= l * y[i];
+            }
+        }
+        y
+    }
+}
+
+/// Groups the stiffness entries by row, with each row sorted by column
+fn compressed_rows(
+    stiffness: &HashMap<(usize, usize), f64>,
+    n: usize,
+) -> Result<Vec<Vec<(usize, f64)>>, String> {
+    let mut rows = vec![Vec::<(usize, f64)>::new(); n];
+    for ((i, j), value) in stiffness.iter() {
+        if *i >= n || *j >= n {
+            return Err(format!(
+                "Stiffness entry ({}, {}) lies outside the {} free degrees of freedom",
+                i, j, n
+            ));
+        }
+        rows[*i].push((*j, *value));
+    }
+    for row in rows.iter_mut() {
+        row.sort_by_key(|(j, _)| *j);
+    }
+    Ok(rows)
+}
+
+fn diagonal_entry(row: &[(usize, f64)], i: usize) -> f64 {
+    match row.binary_search_by_key(&i, |(j, _)| *j) {
+        Ok(position) => row[position].1,
+        Err(_) => 0.,
+    }
+}
+
+fn inverse_diagonal(rows: &[Vec<(usize, f64)>]) -> Result<Vec<f64>, String> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let d = diagonal_entry(row, i);
+            if d <= ZERO_EPSILON {
+                Err(format!(
+                    "Non-positive stiffness on the diagonal at dof {}",
+                    i
+                ))
+            } else {
+                Ok(1. / d)
+            }
+        })
+        .collect()
+}
+
+/// Computes the IC(0) factor, which keeps the sparsity pattern of the lower triangle of `k`
+fn incomplete_cholesky(rows: &[Vec<(usize, f64)>]) -> Result<LowerTriangular, String> {
+    let n = rows.len();
+    let mut lower = vec![Vec::<(usize, f64)>::new(); n];
+    let mut diagonal = vec![0.; n];
+
+    for i in 0..n {
+        let mut row_i = Vec::<(usize, f64)>::new();
+        for (j, a_ij) in rows[i].iter().filter(|(j, _)| *j < i) {
+            let sum = sparse_dot_below(&row_i, &lower[*j], *j);
+            row_i.push((*j, (a_ij - sum) / diagonal[*j]));
+        }
+
+        let pivot = diagonal_entry(&rows[i], i) - row_i.iter().map(|(_, l)| l * l).sum::<f64>();
+        if pivot <= ZERO_EPSILON {
+            return Err(format!(
+                "Incomplete Cholesky factorization broke down at dof {}; use the Jacobi preconditioner",
+                i
+            ));
+        }
+        diagonal[i] = pivot.sqrt();
+        lower[i] = row_i;
+    }
+
+    Ok(LowerTriangular {
+        rows: lower,
+        diagonal,
+    })
+}
+
+/// Sum of `a_k b_k` over the columns `k < limit` shared by two sorted sparse rows
+fn sparse_dot_below(a: &[(usize, f64)], b: &[(usize, f64)], limit: usize) -> f64 {
+    let (mut p, mut q, mut sum) = (0, 0, 0.);
+    while p < a.len() && q < b.len() && a[p].0 < limit && b[q].0 < limit {
+        if a[p].0 == b[q].0 {
+            sum += a[p].1 * b[q].1;
+            p += 1;
+            q += 1;
+        } else if a[p].0 < b[q].0 {
+            p += 1;
+        } else {
+            q += 1;
+        }
+    }
+    sum
+}
+
+fn multiply(rows: &[Vec<(usize, f64)>], x: &[f64]) -> Vec<f64> {
+    rows.iter()
+        .map(|row| row.iter().map(|(j, value)| value * x[*j]).sum())
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three springs in series, fixed at one end, with stiffnesses 3, 2 and 1
+    fn spring_chain() -> HashMap<(usize, usize), f64> {
+        let mut k = HashMap::<(usize, usize), f64>::new();
+        k.insert((0, 0), 5.);
+        k.insert((0, 1), -2.);
+        k.insert((1, 0), -2.);
+        k.insert((1, 1), 3.);
+        k.insert((1, 2), -1.);
+        k.insert((2, 1), -1.);
+        k.insert((2, 2), 1.);
+        k
+    }
+
+    fn assert_spring_chain_deflections(solution: &ConjugateGradientSolution) {
+        assert!(solution.converged);
+        assert!(solution.iterations <= 3);
+        assert_eq!(solution.residual_history.len(), solution.iterations + 1);
+        // A unit load at the free end stretches every spring by 1 / k
+        assert_relative_eq!(solution.deflections[0], 1. / 3., max_relative = 1e-9);
+        assert_relative_eq!(
+            solution.deflections[1],
+            1. / 3. + 1. / 2.,
+            max_relative = 1e-9
+        );
+        assert_relative_eq!(
+            solution.deflections[2],
+            1. / 3. + 1. / 2. + 1.,
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn jacobi_preconditioned_spring_chain() {
+        let settings = ConjugateGradientSettings::default();
+        let solution =
+            solve_for_deflections_iteratively(&spring_chain(), vec![0., 0., 1.], &settings)
+                .unwrap();
+        assert_spring_chain_deflections(&solution);
+    }
+
+    #[test]
+    fn incomplete_cholesky_preconditioned_spring_chain() {
+        let settings = ConjugateGradientSettings {
+            preconditioner: Preconditioner::IncompleteCholesky,
+            ..ConjugateGradientSettings::default()
+        };
+        let solution =
+            solve_for_deflections_iteratively(&spring_chain(), vec![0., 0., 1.], &settings)
+                .unwrap();
+        // The pattern of a tridiagonal matrix produces no fill-in, so IC(0) is the exact factor
+        assert_eq!(solution.iterations, 1);
+        assert_spring_chain_deflections(&solution);
+    }
+
+    #[test]
+    fn iteration_limit_reports_not_converged() {
+        let settings = ConjugateGradientSettings {
+            max_iterations: 1,
+            ..ConjugateGradientSettings::default()
+        };
+        let solution =
+            solve_for_deflections_iteratively(&spring_chain(), vec![0., 0., 1.], &settings)
+                .unwrap();
+        assert!(!solution.converged);
+        assert_eq!(solution.residual_history.len(), 2);
+    }
+}
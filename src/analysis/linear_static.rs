@@ -0,0 +1,854 @@
+use crate::analysis::diagnostics::diagnose_world_stiffness;
+use crate::analysis::iterative::{
+    solve_for_deflections_iteratively, ConjugateGradientConvergence, ConjugateGradientSettings,
+};
+use crate::analysis::*;
+use crate::elements::frame::*;
+use crate::elements::membrane::{membrane_stress, MembraneStress};
+use crate::elements::shell::{shell_resultants, ShellResultants};
+use crate::models::constraint::{Constraint, MultiPointConstraint};
+use crate::models::frame::FrameElement;
+use crate::models::node::{Direction, Node};
+use crate::models::support::Support;
+use crate::models::validation::{validate, ValidationIssue};
+use crate::utils::transform::world_to_local_transform;
+use crate::{update_frame_element_stiffness, ANALYSIS_DATA, ELEMENT_DATA};
+use na::{DMatrix, Vector3, Vector6, VectorN, U12};
+use std::collections::HashMap;
+
+/// Non-zero entries of the world stiffness matrix, keyed by row and column
+pub(crate) type WorldStiffness = HashMap<(usize, usize), f64>;
+
+pub struct LinearStaticResults {
+    /// World displacements `[Ux, Uy, Uz, Rx, Ry, Rz]` of every node
+    pub displacements: HashMap<String, Vector6<f64>>,
+    /// World reactions `[Fx, Fy, Fz, Mx, My, Mz]` at every supported node
+    pub reactions: HashMap<String, Vector6<f64>>,
+    /// World forces `[Fx, Fy, Fz, Mx, My, Mz]` exerted by the springs at every node with
+    /// spring supports
+    pub spring_forces: HashMap<String, Vector6<f64>>,
+    /// World forces `[Fx, Fy, Fz, Mx, My, Mz]` exerted by each constraint on every node it
+    /// connects, keyed by constraint id then node id
+    pub constraint_forces: HashMap<String, HashMap<String, Vector6<f64>>>,
+    /// In-plane world forces `[Fx, Fy, Mz]` exerted by each diaphragm on every node it ties and
+    /// on its master node, keyed by diaphragm id then node id
+    pub diaphragm_forces: HashMap<String, HashMap<String, Vector3<f64>>>,
+    /// Displacements along the local axes of every node with its own coordinate system
+    pub local_displacements: HashMap<String, Vector6<f64>>,
+    /// Reactions along the local axes of every supported node with its own coordinate system
+    pub local_reactions: HashMap<String, Vector6<f64>>,
+    /// Local end forces of every frame, acting on the start then the end of the member at the
+    /// faces of any rigid end zones
+    pub frame_end_forces: HashMap<String, VectorN<f64, U12>>,
+    /// In-plane strains and stresses of every membrane
+    pub membrane_stresses: HashMap<String, MembraneStress>,
+    /// Force and moment resultants per unit length at the centroid of every shell
+    pub shell_resultants: HashMap<String, ShellResultants>,
+    /// Statics check of the applied forces against the reactions
+    pub equilibrium: EquilibriumCheck,
+    /// Iterations and residuals of the conjugate gradient solver, if it solved the model. The
+    /// results are only approximate unless it converged.
+    pub conjugate_gradient: Option<ConjugateGradientConvergence>,
+    /// Model validation warnings, which did not prevent the analysis
+    pub warnings: Vec<ValidationIssue>,
+}
+
+/// Statics check of a solution. The reactions are recovered as `k Δ − F − C` at the supported
+/// degrees of freedom, so the residual there is zero by construction and only the global
+/// resultants and the residuals at the free degrees of freedom test the solution.
+pub struct EquilibriumCheck {
+    /// Resultant `[Fx, Fy, Fz, Mx, My, Mz]` of the forces applied at the nodes about the world
+    /// origin, which include the nodal equivalents of membrane edge loads
+    pub applied: Vector6<f64>,
+    /// Resultant `[Fx, Fy, Fz, Mx, My, Mz]` of the reactions, spring forces and constraint forces
+    /// about the world origin. Rigid links contribute nothing as their forces balance.
+    pub reactions: Vector6<f64>,
+    /// Sum of the applied and reaction resultants, which vanishes for a structure in equilibrium
+    pub imbalance: Vector6<f64>,
+    /// Norms of the residual `k Δ − F − R − C` at every node, where `C` are the constraint
+    /// forces. Only the components at free degrees of freedom can differ from zero.
+    pub nodal_residuals: HashMap<String, NodalResidual>,
+}
+
+pub struct NodalResidual {
+    /// Norm of the translational components `[Fx, Fy, Fz]`
+    pub force: f64,
+    /// Norm of the rotational components `[Mx, My, Mz]`
+    pub moment: f64,
+}
+
+impl EquilibriumCheck {
+    /// Returns true if every component of the imbalance is within `tolerance` times the
+    /// largest component of the applied or reaction resultant.
+    pub fn is_satisfied(&self, tolerance: f64) -> bool {
+        let scale = self.applied.amax().max(self.reactions.amax());
+        self.imbalance.amax() <= tolerance * scale
+    }
+}
+
+/// The method used to solve `F = k Δ` for the free degrees of freedom.
+#[derive(Clone, Debug)]
+pub enum LinearSolver {
+    /// Sparse LU factorization of the stiffness
+    Direct,
+    /// Preconditioned conjugate gradient iteration, for models too large to factor
+    ConjugateGradient(ConjugateGradientSettings),
+}
+
+/**
+ * Runs a linear elastic static analysis under the applied forces.
+ *
+ * The model is validated, the frame stiffnesses are computed and assembled into the world
+ * stiffness matrix, the stiffness is checked for singularities, and the equations `F = k Δ` are
+ * solved for the free degrees of freedom.
+ *
+ * If the model is invalid or the structure is unstable, the returned error lists every element,
+ * node and direction at fault. Call `validation::validate` and
+ * `diagnostics::diagnose_world_stiffness` directly to inspect them programmatically.
+ */
+pub fn run_linear_static_analysis() -> Result<LinearStaticResults, String> {
+    run_linear_static_analysis_with_solver(&LinearSolver::Direct)
+}
+
+/**
+ * Runs a linear elastic static analysis under the applied forces as in
+ * `run_linear_static_analysis`, solving with the given method.
+ *
+ * The conjugate gradient solver returns results even if it reaches its iteration limit before
+ * converging. Its iterations and residuals are reported in `conjugate_gradient` on the results.
+ *
+ * # Arguments
+ *
+ * `solver` - the direct or iterative solver to use
+ */
+pub fn run_linear_static_analysis_with_solver(
+    solver: &LinearSolver,
+) -> Result<LinearStaticResults, String> {
+    if !ELEMENT_DATA.read().unwrap().cables.is_empty() {
+        return Err(
+            "The model has cables, whose stiffness depends on their tension; run a cable \
+             analysis instead"
+                .to_string(),
+        );
+    }
+    let (world, warnings) = assemble_checked_world_stiffness()?;
+
+    let partition = partition_degrees_of_freedom();
+    let mut forces = ANALYSIS_DATA.read().unwrap().applied_forces.clone();
+    forces.resize(partition.dof_count, 0.);
+
+    let (deflections, convergence) = match solver {
+        LinearSolver::Direct => (
+            solve_with_prescribed_displacements(&world, &forces, &partition)?,
+            None,
+        ),
+        LinearSolver::ConjugateGradient(settings) => {
+            let prescribed = partition.prescribed_deflections();
+            let solution = solve_for_deflections_iteratively(
+                &reduce_to_free_dofs(&world, &partition),
+                reduce_forces_to_free_dofs(
+                    &forces_less_prescribed(&world, &forces, &prescribed),
+                    &partition,
+                ),
+                settings,
+            )?;
+            (
+                add_prescribed(partition.expand(&solution.deflections), &prescribed),
+                Some(solution.convergence()),
+            )
+        }
+    };
+
+    let mut results = collect_results(&world, &forces, &deflections);
+    results.conjugate_gradient = convergence;
+    results.warnings = warnings;
+    Ok(results)
+}
+
+/**
+ * Runs a linear elastic static analysis under each of several load cases, factoring the world
+ * stiffness matrix once for all of them.
+ *
+ * The model is validated and checked as in `run_linear_static_analysis`, but the forces added
+ * to the model are ignored in favour of the given load cases. The results are returned in the
+ * order of the load cases.
+ *
+ * # Arguments
+ *
+ * `load_cases` - the applied force at every world degree of freedom for each load case,
+ *                indexed by the degrees of freedom of the nodes
+ */
+pub fn run_linear_static_analysis_by_load_case(
+    load_cases: &[Vec<f64>],
+) -> Result<Vec<LinearStaticResults>, String> {
+    if !ELEMENT_DATA.read().unwrap().cables.is_empty() {
+        return Err(
+            "The model has cables, whose stiffness depends on their tension; run a cable \
+             analysis instead"
+                .to_string(),
+        );
+    }
+    let (world, warnings) = assemble_checked_world_stiffness()?;
+
+    let partition = partition_degrees_of_freedom();
+    let load_cases: Vec<Vec<f64>> = load_cases
+        .iter()
+        .map(|x| {
+            let mut forces = x.clone();
+            forces.resize(partition.dof_count, 0.);
+            forces
+        })
+        .collect();
+
+    let prescribed = partition.prescribed_deflections();
+    let effective_load_cases: Vec<Vec<f64>> = load_cases
+        .iter()
+        .map(|x| forces_less_prescribed(&world, x, &prescribed))
+        .collect();
+    let deflections =
+        solve_free_degrees_of_freedom_by_load_case(&world, &effective_load_cases, &partition)?;
+
+    Ok(load_cases
+        .iter()
+        .zip(deflections)
+        .map(|(forces, deflections)| {
+            let deflections = add_prescribed(deflections, &prescribed);
+            let mut results = collect_results(&world, forces, &deflections);
+            results.warnings = warnings.clone();
+            results
+        })
+        .collect())
+}
+
+/**
+ * Validates the model, assembles and stores the world stiffness matrix, and checks it for
+ * singularities, returning the stiffness along with any validation warnings.
+ */
+pub(crate) fn assemble_checked_world_stiffness(
+) -> Result<(WorldStiffness, Vec<ValidationIssue>), String> {
+    let (world, warnings) = assemble_validated_world_stiffness()?;
+    check_world_stiffness(&world)?;
+    Ok((world, warnings))
+}
+
+/**
+ * Validates the model and assembles and stores the world stiffness matrix without checking it
+ * for singularities, for analyses which add the stiffness of further elements first.
+ */
+pub(crate) fn assemble_validated_world_stiffness(
+) -> Result<(WorldStiffness, Vec<ValidationIssue>), String> {
+    let validation = validate();
+    if validation.has_errors() {
+        let messages: Vec<String> = validation.errors().map(|x| x.message.clone()).collect();
+        return Err(format!("The model is invalid:\n{}", messages.join("\n")));
+    }
+
+    update_frame_stiffnesses();
+
+    let world = assemble_world_stiffness_matrix()?;
+    ANALYSIS_DATA.write().unwrap().world_stiffness = world.clone();
+
+    Ok((world, validation.warnings().cloned().collect()))
+}
+
+/// Returns an error listing every reason the world stiffness matrix is singular, if it is
+pub(crate) fn check_world_stiffness(world: &HashMap<(usize, usize), f64>) -> Result<(), String> {
+    let diagnostics = diagnose_world_stiffness(world);
+    if !diagnostics.is_empty() {
+        let messages: Vec<String> = diagnostics.into_iter().map(|x| x.message).collect();
+        return Err(format!(
+            "The world stiffness matrix is singular:\n{}",
+            messages.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Computes the local and world stiffness matrix of every frame
+fn update_frame_stiffnesses() {
+    let frames: Vec<FrameElement> = ELEMENT_DATA
+        .read()
+        .unwrap()
+        .frames
+        .values()
+        .cloned()
+        .collect();
+
+    for frame in frames.iter() {
+        let local = frame_element_local_stiffness_matrix(frame);
+        let world = transform_frame_stiffness_local_to_world(frame, &local);
+        update_frame_element_stiffness(frame, local, world);
+    }
+}
+
+/// Solves `F = k Δ` for the free degrees of freedom, returning the deflection at every world
+/// degree of freedom with zero at the restrained ones.
+pub(crate) fn solve_free_degrees_of_freedom(
+    world: &HashMap<(usize, usize), f64>,
+    forces: &[f64],
+    partition: &DegreeOfFreedomPartition,
+) -> Result<Vec<f64>, String> {
+    if partition.free.is_empty() {
+        return Ok(vec![0.; partition.dof_count]);
+    }
+
+    let mut stiffness = sparse21::Matrix::new();
+    for ((i, j), value) in reduce_to_free_dofs(world, partition).iter() {
+        stiffness.add_element(*i, *j, *value);
+    }
+    let free_forces = reduce_forces_to_free_dofs(forces, partition);
+    let free_deflections = solve_for_deflections(&mut stiffness, free_forces)?;
+    Ok(partition.expand(&free_deflections))
+}
+
+/// Solves `F = k Δ` for the free degrees of freedom with the restrained ones held at their
+/// prescribed displacements, returning the deflection at every world degree of freedom.
+pub(crate) fn solve_with_prescribed_displacements(
+    world: &HashMap<(usize, usize), f64>,
+    forces: &[f64],
+    partition: &DegreeOfFreedomPartition,
+) -> Result<Vec<f64>, String> {
+    let prescribed = partition.prescribed_deflections();
+    let deflections = solve_free_degrees_of_freedom(
+        world,
+        &forces_less_prescribed(world, forces, &prescribed),
+        partition,
+    )?;
+    Ok(add_prescribed(deflections, &prescribed))
+}
+
+/// Returns the forces less `k Δr`, which move the prescribed displacements `Δr` to the right
+/// hand side so that the free degrees of freedom are solved for as if the supports were fixed
+fn forces_less_prescribed(
+    world: &HashMap<(usize, usize), f64>,
+    forces: &[f64],
+    prescribed: &[f64],
+) -> Vec<f64> {
+    if prescribed.iter().all(|x| *x == 0.) {
+        return forces.to_vec();
+    }
+    let internal = multiply_stiffness(world, prescribed);
+    forces
+        .iter()
+        .enumerate()
+        .map(|(i, f)| f - internal.get(i).unwrap_or(&0.))
+        .collect()
+}
+
+/// Adds the prescribed displacements to deflections solved with the supports fixed
+fn add_prescribed(mut deflections: Vec<f64>, prescribed: &[f64]) -> Vec<f64> {
+    for (x, dx) in deflections.iter_mut().zip(prescribed.iter()) {
+        *x += dx;
+    }
+    deflections
+}
+
+/// Solves `F = k Δ` for the free degrees of freedom under each load case with a single
+/// factorization, returning the deflection at every world degree of freedom for each case.
+pub(crate) fn solve_free_degrees_of_freedom_by_load_case(
+    world: &HashMap<(usize, usize), f64>,
+    load_cases: &[Vec<f64>],
+    partition: &DegreeOfFreedomPartition,
+) -> Result<Vec<Vec<f64>>, String> {
+    if partition.free.is_empty() {
+        return Ok(vec![vec![0.; partition.dof_count]; load_cases.len()]);
+    }
+
+    let mut stiffness = sparse21::Matrix::new();
+    for ((i, j), value) in reduce_to_free_dofs(world, partition).iter() {
+        stiffness.add_element(*i, *j, *value);
+    }
+    let mut free_forces = DMatrix::<f64>::zeros(partition.free.len(), load_cases.len());
+    for (case, forces) in load_cases.iter().enumerate() {
+        for (i, force) in reduce_forces_to_free_dofs(forces, partition)
+            .iter()
+            .enumerate()
+        {
+            free_forces[(i, case)] = *force;
+        }
+    }
+    let free_deflections = solve_for_deflections_by_load_case(&mut stiffness, &free_forces)?;
+    Ok((0..load_cases.len())
+        .map(|case| {
+            let column: Vec<f64> = free_deflections.column(case).iter().cloned().collect();
+            partition.expand(&column)
+        })
+        .collect())
+}
+
+/// Returns the forces at the free degrees of freedom, with the forces at constrained degrees of
+/// freedom transferred to their masters
+pub(crate) fn reduce_forces_to_free_dofs(
+    forces: &[f64],
+    partition: &DegreeOfFreedomPartition,
+) -> Vec<f64> {
+    let mut free_forces = vec![0.; partition.free.len()];
+    for (dof, force) in forces.iter().enumerate() {
+        if *force == 0. {
+            continue;
+        }
+        for (i, c) in partition.free_components(dof).iter() {
+            free_forces[*i] += c * force;
+        }
+    }
+    free_forces
+}
+
+/// Returns the local displacements of the start then end node of a frame
+pub(crate) fn frame_local_displacements(
+    frame: &FrameElement,
+    start: &Node,
+    end: &Node,
+    deflections: &[f64],
+) -> VectorN<f64, U12> {
+    let nodal_displacements = VectorN::<f64, U12>::from_iterator(
+        start
+            .degrees_of_freedom
+            .iter()
+            .chain(end.degrees_of_freedom.iter())
+            .map(|dof| deflections[*dof]),
+    );
+    let world_displacements = if start.local_axes.is_some() || end.local_axes.is_some() {
+        world_to_frame_nodal_transform(start, end).transpose() * nodal_displacements
+    } else {
+        nodal_displacements
+    };
+    world_to_local_transform(&frame.geometry.local_axes) * world_displacements
+}
+
+pub(crate) fn collect_results(
+    world: &HashMap<(usize, usize), f64>,
+    forces: &[f64],
+    deflections: &[f64],
+) -> LinearStaticResults {
+    let internal = multiply_stiffness(world, deflections);
+
+    let data = ELEMENT_DATA.read().unwrap();
+    let analysis = ANALYSIS_DATA.read().unwrap();
+
+    let mut displacements = HashMap::<String, Vector6<f64>>::new();
+    let mut reactions = HashMap::<String, Vector6<f64>>::new();
+    let mut spring_forces = HashMap::<String, Vector6<f64>>::new();
+    let mut local_displacements = HashMap::<String, Vector6<f64>>::new();
+    let mut local_reactions = HashMap::<String, Vector6<f64>>::new();
+    let residual: Vec<f64> = internal
+        .iter()
+        .zip(forces.iter())
+        .map(|(k, f)| k - f)
+        .collect();
+    let constraint_forces =
+        constraint_forces(&data.nodes, &data.supports, &data.constraints, &residual);
+    let nodal_constraint_forces = sum_by_node(&constraint_forces);
+    for node in data.nodes.values() {
+        let dofs = node.degrees_of_freedom;
+        let displacement = Vector6::from_iterator(dofs.iter().map(|dof| deflections[*dof]));
+        displacements.insert(node.id.clone(), node.to_world(&displacement));
+        if node.local_axes.is_some() {
+            local_displacements.insert(node.id.clone(), displacement);
+        }
+
+        if let Some(support) = data.supports.get(&node.id) {
+            // The support also holds the forces the constraints exert on the node
+            let constraint_force = match nodal_constraint_forces.get(&node.id) {
+                Some(x) => node.from_world(x),
+                None => Vector6::zeros(),
+            };
+            let mut reaction = Vector6::<f64>::zeros();
+            for direction in Direction::ALL.iter() {
+                if support.is_fixed(*direction) {
+                    let i = direction.index();
+                    reaction[i] = residual[dofs[i]] - constraint_force[i];
+                }
+            }
+            reactions.insert(node.id.clone(), node.to_world(&reaction));
+            if node.local_axes.is_some() {
+                local_reactions.insert(node.id.clone(), reaction);
+            }
+
+            if support.has_springs() {
+                let world_displacement = displacements[&node.id];
+                let spring_force = match support.local_axes {
+                    Some(_) => -(support.spring_stiffness() * world_displacement),
+                    None => node.to_world(&-(support.spring_stiffness() * displacement)),
+                };
+                spring_forces.insert(node.id.clone(), spring_force);
+            }
+        }
+    }
+
+    let mut diaphragm_forces = HashMap::<String, HashMap<String, Vector3<f64>>>::new();
+    for (id, constraint) in data.constraints.iter() {
+        if let (Constraint::Diaphragm(_), Some(forces)) = (constraint, constraint_forces.get(id)) {
+            let in_plane = forces
+                .iter()
+                .map(|(x, force)| (x.clone(), Vector3::new(force[0], force[1], force[5])))
+                .collect();
+            diaphragm_forces.insert(id.clone(), in_plane);
+        }
+    }
+
+    let mut frame_end_forces = HashMap::<String, VectorN<f64, U12>>::new();
+    for frame in data.frames.values() {
+        let (start, end) = match (
+            data.nodes.get(&frame.start_node_id),
+            data.nodes.get(&frame.end_node_id),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => continue,
+        };
+        let stiffness = match analysis.frame_stiffnesses.get(&frame.id) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let local_displacements = frame_local_displacements(frame, start, end, deflections);
+        frame_end_forces.insert(
+            frame.id.clone(),
+            frame_end_forces_at_faces(frame, &(stiffness.local * local_displacements)),
+        );
+    }
+
+    let mut membrane_stresses = HashMap::<String, MembraneStress>::new();
+    for membrane in data.membranes.values() {
+        let nodes: Option<Vec<&Node>> = membrane
+            .node_ids
+            .iter()
+            .map(|x| data.nodes.get(x))
+            .collect();
+        let nodes = match nodes {
+            Some(x) => x,
+            None => continue,
+        };
+        let coordinates: Vec<_> = nodes.iter().map(|x| x.coordinate).collect();
+        let translations: Vec<Vector3<f64>> = nodes
+            .iter()
+            .map(|x| {
+                let displacement = displacements[&x.id];
+                Vector3::new(displacement[0], displacement[1], displacement[2])
+            })
+            .collect();
+        membrane_stresses.insert(
+            membrane.id.clone(),
+            membrane_stress(membrane, &coordinates, &translations),
+        );
+    }
+
+    let mut shell_results = HashMap::<String, ShellResultants>::new();
+    for shell in data.shells.values() {
+        let nodes: Option<Vec<&Node>> = shell.node_ids.iter().map(|x| data.nodes.get(x)).collect();
+        let nodes = match nodes {
+            Some(x) => x,
+            None => continue,
+        };
+        let coordinates: Vec<_> = nodes.iter().map(|x| x.coordinate).collect();
+        let nodal_displacements: Vec<Vector6<f64>> =
+            nodes.iter().map(|x| displacements[&x.id]).collect();
+        shell_results.insert(
+            shell.id.clone(),
+            shell_resultants(shell, &coordinates, &nodal_displacements),
+        );
+    }
+
+    let equilibrium = check_equilibrium(
+        &data.nodes,
+        forces,
+        &internal,
+        &reactions,
+        &spring_forces,
+        &constraint_forces,
+    );
+
+    LinearStaticResults {
+        displacements,
+        reactions,
+        spring_forces,
+        constraint_forces,
+        diaphragm_forces,
+        local_displacements,
+        local_reactions,
+        frame_end_forces,
+        membrane_stresses,
+        shell_resultants: shell_results,
+        equilibrium,
+        conjugate_gradient: None,
+        warnings: Vec::new(),
+    }
+}
+
+/**
+ * Returns the world forces `[Fx, Fy, Fz, Mx, My, Mz]` each constraint exerts on the nodes it
+ * connects, keyed by constraint id then node id.
+ *
+ * The force on each slave degree of freedom is its Lagrange multiplier `λ`, which is the
+ * residual `k Δ − F` there plus the multipliers passed on by any slaves following it. The
+ * constraint passes `−Tᵀ λ` to its masters, i.e. `−c λ` through each coefficient `c` of the
+ * slave's equation, so the forces of every constraint balance and a node shared by several
+ * constraints has its unbalanced force split among them.
+ *
+ * # Arguments
+ *
+ * `nodes` - all nodes of the model
+ * `supports` - supports of the model, whose restrained degrees of freedom are not slaves
+ * `constraints` - constraints of the model
+ * `residual` - the residual `k Δ − F` at every world degree of freedom
+ */
+fn constraint_forces(
+    nodes: &HashMap<String, Node>,
+    supports: &HashMap<String, Support>,
+    constraints: &HashMap<String, Constraint>,
+    residual: &[f64],
+) -> HashMap<String, HashMap<String, Vector6<f64>>> {
+    let dof = |node_id: &str, direction: Direction| {
+        nodes
+            .get(node_id)
+            .map(|x| x.degrees_of_freedom[direction.index()])
+    };
+
+    // Every slave degree of freedom with its constraint, equation and master degrees of freedom
+    let mut slaves = HashMap::<usize, (&str, MultiPointConstraint, Vec<(usize, f64)>)>::new();
+    for (id, constraint) in constraints.iter() {
+        for equation in constraint.equations(nodes).unwrap_or_default() {
+            let fixed = matches!(
+                supports.get(&equation.slave_node_id),
+                Some(x) if x.is_fixed(equation.slave_direction)
+            );
+            let slave = match dof(&equation.slave_node_id, equation.slave_direction) {
+                Some(x) if !fixed => x,
+                _ => continue,
+            };
+            let terms: Option<Vec<(usize, f64)>> = equation
+                .masters
+                .iter()
+                .map(|x| dof(&x.node_id, x.direction).map(|y| (y, x.coefficient)))
+                .collect();
+            if let Some(terms) = terms {
+                slaves.insert(slave, (id.as_str(), equation, terms));
+            }
+        }
+    }
+
+    // Masters which are slaves of other constraints pass multipliers along a chain, which is
+    // resolved a link at a time
+    let own_residuals: HashMap<usize, f64> = slaves
+        .keys()
+        .map(|x| (*x, residual.get(*x).cloned().unwrap_or(0.)))
+        .collect();
+    let mut multipliers = own_residuals.clone();
+    for _ in 0..slaves.len() {
+        let mut updated = own_residuals.clone();
+        for (slave, (_, _, terms)) in slaves.iter() {
+            for (master, c) in terms.iter() {
+                if let Some(x) = updated.get_mut(master) {
+                    *x += c * multipliers[slave];
+                }
+            }
+        }
+        if updated == multipliers {
+            break;
+        }
+        multipliers = updated;
+    }
+
+    let mut nodal = HashMap::<String, HashMap<String, Vector6<f64>>>::new();
+    for (id, constraint) in constraints.iter() {
+        let forces = constraint
+            .node_ids()
+            .into_iter()
+            .filter(|x| nodes.contains_key(*x))
+            .map(|x| (x.to_string(), Vector6::zeros()))
+            .collect();
+        nodal.insert(id.clone(), forces);
+    }
+    for (slave, (id, equation, terms)) in slaves.iter() {
+        let lambda = multipliers[slave];
+        let forces = nodal.get_mut(*id).unwrap();
+        if let Some(x) = forces.get_mut(&equation.slave_node_id) {
+            x[equation.slave_direction.index()] += lambda;
+        }
+        for (term, (_, c)) in equation.masters.iter().zip(terms.iter()) {
+            if let Some(x) = forces.get_mut(&term.node_id) {
+                x[term.direction.index()] -= c * lambda;
+            }
+        }
+    }
+    for forces in nodal.values_mut() {
+        for (node_id, force) in forces.iter_mut() {
+            *force = nodes[node_id].to_world(force);
+        }
+    }
+    nodal
+}
+
+/// Returns the total force exerted by all constraints on each node they connect
+fn sum_by_node(
+    constraint_forces: &HashMap<String, HashMap<String, Vector6<f64>>>,
+) -> HashMap<String, Vector6<f64>> {
+    let mut totals = HashMap::<String, Vector6<f64>>::new();
+    for forces in constraint_forces.values() {
+        for (node_id, force) in forces.iter() {
+            *totals.entry(node_id.clone()).or_insert_with(Vector6::zeros) += force;
+        }
+    }
+    totals
+}
+
+/**
+ * Returns the statics check comparing the resultant of the applied forces with the resultant
+ * of the reactions, along with the residual `k Δ − F − R − C` at every node.
+ *
+ * Reactions recovered as `k Δ − F − C` leave no residual at the supported degrees of freedom, so
+ * only the imbalance of the resultants and the residuals at the free degrees of freedom
+ * reveal an inaccurate solution or inconsistent reactions.
+ *
+ * # Arguments
+ *
+ * `nodes` - all nodes of the model
+ * `forces` - applied forces at every world degree of freedom
+ * `internal` - the product `k Δ` at every world degree of freedom
+ * `reactions` - reactions at every supported node
+ * `spring_forces` - forces exerted by the springs, which `k Δ` already accounts for
+ * `constraint_forces` - forces exerted by each constraint on the nodes it connects
+ */
+pub(crate) fn check_equilibrium(
+    nodes: &HashMap<String, Node>,
+    forces: &[f64],
+    internal: &[f64],
+    reactions: &HashMap<String, Vector6<f64>>,
+    spring_forces: &HashMap<String, Vector6<f64>>,
+    constraint_forces: &HashMap<String, HashMap<String, Vector6<f64>>>,
+) -> EquilibriumCheck {
+    let constraint_forces = sum_by_node(constraint_forces);
+    let mut applied = Vector6::<f64>::zeros();
+    let mut reaction_resultant = Vector6::<f64>::zeros();
+    let mut nodal_residuals = HashMap::<String, NodalResidual>::new();
+
+    for node in nodes.values() {
+        let dofs = node.degrees_of_freedom;
+        let load = node.to_world(&Vector6::from_iterator(dofs.iter().map(|dof| forces[*dof])));
+        let reaction = match reactions.get(&node.id) {
+            Some(x) => *x,
+            None => Vector6::zeros(),
+        };
+
+        applied += resultant_about_origin(&node.coordinate.coords, &load);
+        reaction_resultant += resultant_about_origin(&node.coordinate.coords, &reaction);
+        if let Some(spring_force) = spring_forces.get(&node.id) {
+            reaction_resultant += resultant_about_origin(&node.coordinate.coords, spring_force);
+        }
+        let constraint_force = match constraint_forces.get(&node.id) {
+            Some(x) => *x,
+            None => Vector6::zeros(),
+        };
+        reaction_resultant += resultant_about_origin(&node.coordinate.coords, &constraint_force);
+
+        let residual = node.to_world(&Vector6::from_iterator(
+            dofs.iter().map(|dof| internal[*dof]),
+        )) - load
+            - reaction
+            - constraint_force;
+        nodal_residuals.insert(
+            node.id.clone(),
+            NodalResidual {
+                force: Vector3::new(residual[0], residual[1], residual[2]).norm(),
+                moment: Vector3::new(residual[3], residual[4], residual[5]).norm(),
+            },
+        );
+    }
+
+    EquilibriumCheck {
+        applied,
+        reactions: reaction_resultant,
+        imbalance: applied + reaction_resultant,
+        nodal_residuals,
+    }
+}
+
+/// Moves the forces and moments acting at `position` to the world origin
+fn resultant_about_origin(position: &Vector3<f64>, actions: &Vector6<f64>) -> Vector6<f64> {
+    let force = Vector3::new(actions[0], actions[1], actions[2]);
+    let moment = Vector3::new(actions[3], actions[4], actions[5]) + position.cross(&force);
+    Vector6::new(force.x, force.y, force.z, moment.x, moment.y, moment.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::Point3;
+
+    struct Statics {
+        nodes: HashMap<String, Node>,
+        forces: Vec<f64>,
+        internal: Vec<f64>,
+        reactions: HashMap<String, Vector6<f64>>,
+    }
+
+    impl Statics {
+        fn check(&self) -> EquilibriumCheck {
+            check_equilibrium(
+                &self.nodes,
+                &self.forces,
+                &self.internal,
+                &self.reactions,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+        }
+    }
+
+    /// A cantilever of length 4 along x fixed at `a`, with its tip `b` loaded by 3 along y
+    fn loaded_cantilever() -> Statics {
+        let node = |id: &str, first: usize, x: f64| Node {
+            id: id.to_string(),
+            degrees_of_freedom: Vector6::from_iterator(first..first + 6),
+            coordinate: Point3::new(x, 0., 0.),
+            local_axes: None,
+        };
+        let nodes: HashMap<String, Node> = vec![node("a", 0, 0.), node("b", 6, 4.)]
+            .into_iter()
+            .map(|x| (x.id.clone(), x))
+            .collect();
+
+        let mut forces = vec![0.; 12];
+        forces[7] = 3.;
+        // The member carries the tip load as shear and a moment of 12 at the support
+        let mut internal = vec![0.; 12];
+        internal[1] = -3.;
+        internal[5] = -12.;
+        internal[7] = 3.;
+        let mut reactions = HashMap::<String, Vector6<f64>>::new();
+        reactions.insert("a".to_string(), Vector6::new(0., -3., 0., 0., 0., -12.));
+
+        Statics {
+            nodes,
+            forces,
+            internal,
+            reactions,
+        }
+    }
+
+    #[test]
+    fn supported_cantilever_balances_its_load() {
+        let check = loaded_cantilever().check();
+
+        assert_relative_eq!(check.applied[1], 3.);
+        assert_relative_eq!(check.applied[5], 12.);
+        assert_abs_diff_eq!(check.imbalance.amax(), 0.);
+        assert!(check.is_satisfied(1e-12));
+        for residual in check.nodal_residuals.values() {
+            assert_abs_diff_eq!(residual.force, 0.);
+            assert_abs_diff_eq!(residual.moment, 0.);
+        }
+    }
+
+    #[test]
+    fn tampered_reaction_fails_the_check() {
+        let mut statics = loaded_cantilever();
+        statics.reactions.get_mut("a").unwrap()[5] = -10.;
+        let check = statics.check();
+
+        assert_relative_eq!(check.imbalance[5], 2.);
+        assert!(!check.is_satisfied(1e-3));
+        assert_relative_eq!(check.nodal_residuals["a"].moment, 2.);
+        assert_abs_diff_eq!(check.nodal_residuals["b"].moment, 0.);
+    }
+}
@@ -1,9 +1,359 @@
+pub mod buckling;
+pub mod cable;
+pub mod diagnostics;
+pub mod frame_diagrams;
+pub mod harmonic;
+pub mod iterative;
+pub mod linear_static;
+pub mod modal;
+pub mod nonlinear_supports;
+pub mod p_delta;
+pub mod renumber;
+pub mod response_spectrum;
+pub mod time_history;
+
+use crate::elements::membrane::membrane_world_stiffness_matrix;
+use crate::elements::shell::shell_world_stiffness_matrix;
+use crate::models::constraint::Constraint;
 use crate::models::frame::FrameElement;
+use crate::models::membrane::MembraneElement;
+use crate::models::node::{Direction, Node};
+use crate::models::shell::ShellElement;
+use crate::models::support::{Support, SupportCondition};
 use crate::utils::ZERO_EPSILON;
 use crate::{ANALYSIS_DATA, ELEMENT_DATA};
-use na::{MatrixN, U12};
+use na::{DMatrix, DVector, Matrix3, Matrix6, MatrixN, Vector6, U12};
 use std::collections::HashMap;
 
+/// The world degrees of freedom split into those free to displace, those restrained by supports
+/// and those following other degrees of freedom through constraints.
+pub struct DegreeOfFreedomPartition {
+    /// Free degrees of freedom in the order they are solved, which follows the renumbering
+    /// method of the analysis settings
+    pub free: Vec<usize>,
+    /// Restrained degrees of freedom in ascending order
+    pub restrained: Vec<usize>,
+    /// Maps each restrained degree of freedom with a prescribed displacement to its value
+    pub prescribed: HashMap<usize, f64>,
+    /// Maps each free degree of freedom to its position in `free`
+    pub free_index: HashMap<usize, usize>,
+    /// Maps each slave degree of freedom of a constraint to the master degrees of freedom and
+    /// coefficients it follows. Masters are never slaves themselves.
+    pub constraints: HashMap<usize, Vec<(usize, f64)>>,
+    /// One more than the largest world degree of freedom
+    pub dof_count: usize,
+}
+
+impl DegreeOfFreedomPartition {
+    /// Returns the positions in `free` and coefficients making up the displacement at a world
+    /// degree of freedom, which are empty for restrained degrees of freedom.
+    pub fn free_components(&self, dof: usize) -> Vec<(usize, f64)> {
+        if let Some(i) = self.free_index.get(&dof) {
+            return vec![(*i, 1.)];
+        }
+        match self.constraints.get(&dof) {
+            Some(terms) => terms
+                .iter()
+                .filter_map(|(master, c)| self.free_index.get(master).map(|i| (*i, *c)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Expands values at the free degrees of freedom to every world degree of freedom, with
+    /// zero at the restrained ones and the constrained ones following their masters.
+    pub fn expand(&self, free_values: &[f64]) -> Vec<f64> {
+        let mut values = vec![0.; self.dof_count];
+        for (i, dof) in self.free.iter().enumerate() {
+            values[*dof] = free_values[i];
+        }
+        for (slave, terms) in self.constraints.iter() {
+            values[*slave] = terms.iter().map(|(master, c)| c * values[*master]).sum();
+        }
+        values
+    }
+
+    /// Returns the prescribed displacements at every world degree of freedom, with the
+    /// constrained ones following any prescribed masters and zero elsewhere.
+    pub fn prescribed_deflections(&self) -> Vec<f64> {
+        let mut values = vec![0.; self.dof_count];
+        for (dof, value) in self.prescribed.iter() {
+            values[*dof] = *value;
+        }
+        for (slave, terms) in self.constraints.iter() {
+            values[*slave] = terms.iter().map(|(master, c)| c * values[*master]).sum();
+        }
+        values
+    }
+
+    /**
+     * Returns the matrix `Tᵀ A T` over the world degrees of freedom, where `T` replaces each
+     * slave degree of freedom with its masters. Rows and columns of the slaves are left empty.
+     *
+     * # Arguments
+     *
+     * `entries` - non-zero entries of a matrix `A` over the world degrees of freedom
+     */
+    pub fn constrain(
+        &self,
+        entries: &HashMap<(usize, usize), f64>,
+    ) -> HashMap<(usize, usize), f64> {
+        if self.constraints.is_empty() {
+            return entries.clone();
+        }
+        let components = |dof: &usize| match self.constraints.get(dof) {
+            Some(terms) => terms.clone(),
+            None => vec![(*dof, 1.)],
+        };
+
+        let mut constrained = HashMap::<(usize, usize), f64>::new();
+        for ((i, j), value) in entries.iter() {
+            for (row, a) in components(i).iter() {
+                for (column, b) in components(j).iter() {
+                    *constrained.entry((*row, *column)).or_insert(0.) += a * value * b;
+                }
+            }
+        }
+        constrained
+    }
+}
+
+/**
+ * Returns the world degrees of freedom of all nodes split into free and restrained sets
+ * according to the supports, with the slaves of any constraints set apart.
+ *
+ * The free degrees of freedom are ordered by the renumbering method of the analysis settings,
+ * so every matrix reduced onto them is assembled in that order. They fall back to ascending
+ * order if renumbering is turned off or the element connectivity refers to missing nodes.
+ */
+pub fn partition_degrees_of_freedom() -> DegreeOfFreedomPartition {
+    let method = ANALYSIS_DATA.read().unwrap().renumbering;
+    let order = method
+        .and_then(|x| renumber::degree_of_freedom_ordering(x).ok())
+        .map(|x| x.dof_map);
+
+    let data = ELEMENT_DATA.read().unwrap();
+    let constraints = constraint_equations(&data.nodes, &data.supports, &data.constraints);
+
+    let mut free = Vec::<usize>::new();
+    let mut restrained = Vec::<usize>::new();
+    let mut prescribed = HashMap::<usize, f64>::new();
+    for node in data.nodes.values() {
+        let support = data.supports.get(&node.id);
+        for direction in Direction::ALL.iter() {
+            let dof = node.degrees_of_freedom[direction.index()];
+            match support {
+                Some(x) if x.is_fixed(*direction) => {
+                    restrained.push(dof);
+                    if let SupportCondition::Prescribed(value) = x.condition(*direction) {
+                        prescribed.insert(dof, *value);
+                    }
+                }
+                _ if constraints.contains_key(&dof) => (),
+                _ => free.push(dof),
+            }
+        }
+    }
+    match order {
+        Some(order) => free.sort_unstable_by_key(|dof| (order.get(dof).cloned(), *dof)),
+        None => free.sort_unstable(),
+    }
+    free.dedup();
+    restrained.sort_unstable();
+    restrained.dedup();
+
+    let dof_count = free
+        .iter()
+        .max()
+        .into_iter()
+        .chain(restrained.last())
+        .chain(constraints.keys().max())
+        .max()
+        .map_or(0, |x| x + 1);
+    let free_index = free.iter().enumerate().map(|(i, dof)| (*dof, i)).collect();
+
+    DegreeOfFreedomPartition {
+        free,
+        restrained,
+        prescribed,
+        free_index,
+        constraints,
+        dof_count,
+    }
+}
+
+/// Returns every slave degree of freedom with the master degrees of freedom it follows, after
+/// substituting for masters which are slaves of other constraints. Constraints on missing nodes
+/// or restrained degrees of freedom are left out; validation reports them.
+fn constraint_equations(
+    nodes: &HashMap<String, Node>,
+    supports: &HashMap<String, Support>,
+    constraints: &HashMap<String, Constraint>,
+) -> HashMap<usize, Vec<(usize, f64)>> {
+    let dof = |node_id: &str, direction: Direction| {
+        nodes
+            .get(node_id)
+            .map(|x| x.degrees_of_freedom[direction.index()])
+    };
+
+    let mut equations = HashMap::<usize, Vec<(usize, f64)>>::new();
+    for constraint in constraints.values() {
+        for equation in constraint.equations(nodes).unwrap_or_default() {
+            let fixed = matches!(
+                supports.get(&equation.slave_node_id),
+                Some(x) if x.is_fixed(equation.slave_direction)
+            );
+            let slave = match dof(&equation.slave_node_id, equation.slave_direction) {
+                Some(x) if !fixed => x,
+                _ => continue,
+            };
+            let terms: Option<Vec<(usize, f64)>> = equation
+                .masters
+                .iter()
+                .map(|x| dof(&x.node_id, x.direction).map(|y| (y, x.coefficient)))
+                .collect();
+            if let Some(terms) = terms {
+                equations.insert(slave, terms);
+            }
+        }
+    }
+
+    // Substitute for masters which are themselves slaves, a chain at a time
+    for _ in 0..equations.len() {
+        let resolved = equations
+            .values()
+            .all(|terms| terms.iter().all(|(x, _)| !equations.contains_key(x)));
+        if resolved {
+            break;
+        }
+        let previous = equations.clone();
+        for terms in equations.values_mut() {
+            *terms = terms
+                .iter()
+                .flat_map(|(master, c)| match previous.get(master) {
+                    Some(x) => x.iter().map(|(y, d)| (*y, c * d)).collect(),
+                    None => vec![(*master, *c)],
+                })
+                .collect();
+        }
+    }
+    // Masters left as slaves after the substitution form a cycle, which validation reports
+    let slaves: Vec<usize> = equations.keys().cloned().collect();
+    for terms in equations.values_mut() {
+        terms.retain(|(x, _)| !slaves.contains(x));
+    }
+    equations
+}
+
+/**
+ * Returns the entries of the world stiffness matrix coupling free degrees of freedom,
+ * renumbered to their positions in `partition.free`. The entries of constrained degrees of
+ * freedom are transferred to their masters.
+ *
+ * # Arguments
+ *
+ * `world` - non-zero entries of the assembled world stiffness matrix
+ * `partition` - the free and restrained degrees of freedom
+ */
+pub fn reduce_to_free_dofs(
+    world: &HashMap<(usize, usize), f64>,
+    partition: &DegreeOfFreedomPartition,
+) -> HashMap<(usize, usize), f64> {
+    if partition.constraints.is_empty() {
+        return world
+            .iter()
+            .filter_map(|((i, j), value)| {
+                let row = partition.free_index.get(i)?;
+                let column = partition.free_index.get(j)?;
+                Some(((*row, *column), *value))
+            })
+            .collect();
+    }
+
+    let mut reduced = HashMap::<(usize, usize), f64>::new();
+    for ((i, j), value) in world.iter() {
+        for (row, a) in partition.free_components(*i).iter() {
+            for (column, b) in partition.free_components(*j).iter() {
+                *reduced.entry((*row, *column)).or_insert(0.) += a * value * b;
+            }
+        }
+    }
+    reduced
+}
+
+/**
+ * Returns the entries coupling free degrees of freedom as a dense matrix ordered by
+ * `partition.free`.
+ *
+ * # Arguments
+ *
+ * `entries` - non-zero entries of a matrix over the world degrees of freedom
+ * `partition` - the free and restrained degrees of freedom
+ */
+pub fn reduce_to_free_dofs_dense(
+    entries: &HashMap<(usize, usize), f64>,
+    partition: &DegreeOfFreedomPartition,
+) -> DMatrix<f64> {
+    let n = partition.free.len();
+    let mut dense = DMatrix::<f64>::zeros(n, n);
+    for ((i, j), value) in reduce_to_free_dofs(entries, partition).iter() {
+        dense[(*i, *j)] += value;
+    }
+    dense
+}
+
+/**
+ * Returns the product `k Δ` of a sparse stiffness matrix and a displacement vector.
+ *
+ * # Arguments
+ *
+ * `stiffness` - non-zero entries of the stiffness matrix
+ * `displacements` - displacement at each degree of freedom
+ */
+pub fn multiply_stiffness(
+    stiffness: &HashMap<(usize, usize), f64>,
+    displacements: &[f64],
+) -> Vec<f64> {
+    let mut product = vec![0.; displacements.len()];
+    for ((i, j), value) in stiffness.iter() {
+        if *i < product.len() && *j < displacements.len() {
+            product[*i] += value * displacements[*j];
+        }
+    }
+    product
+}
+
+/// Scales the vector so that its component of largest magnitude equals one
+pub fn normalize_to_largest(vector: DVector<f64>) -> DVector<f64> {
+    let mut largest = 0.;
+    for value in vector.iter() {
+        if value.abs() > f64::abs(largest) {
+            largest = *value;
+        }
+    }
+    if largest == 0. {
+        vector
+    } else {
+        vector / largest
+    }
+}
+
+/// Expands a vector over the free degrees of freedom into nodal world components
+pub fn nodal_shape(
+    free_values: &DVector<f64>,
+    partition: &DegreeOfFreedomPartition,
+) -> HashMap<String, Vector6<f64>> {
+    let values = partition.expand(free_values.as_slice());
+    let data = ELEMENT_DATA.read().unwrap();
+    data.nodes
+        .values()
+        .map(|node| {
+            let nodal = node.degrees_of_freedom.map(|dof| values[dof]);
+            (node.id.clone(), node.to_world(&nodal))
+        })
+        .collect()
+}
+
 /**
  * Returns the world displacement vectors for all free degrees of freedom.
  *
@@ -29,25 +379,142 @@ pub fn solve_for_deflections(
     }
 }
 
+/**
+ * Returns the world displacement vectors for all free degrees of freedom under each load case.
+ *
+ * `sparse21` factors the stiffness matrix on its first solve and reuses the factors for every
+ * later right-hand side, so the stiffness is factored a single time and each load case only
+ * costs a back-substitution. `run_linear_static_analysis_by_load_case` solves the load cases
+ * of a model this way.
+ *
+ * # Arguments
+ *
+ * `forces` - forces acting at the free degrees of freedom, with one column per load case
+ * `stiffness` - world stiffness matrix for the free degrees of freedom
+ *
+ * The returned matrix contains one column of displacements per load case.
+ */
+pub fn solve_for_deflections_by_load_case(
+    stiffness: &mut sparse21::Matrix,
+    forces: &DMatrix<f64>,
+) -> Result<DMatrix<f64>, String> {
+    let mut deflections = DMatrix::<f64>::zeros(forces.nrows(), forces.ncols());
+    for case in 0..forces.ncols() {
+        let column: Vec<f64> = forces.column(case).iter().cloned().collect();
+        let solution: Result<Vec<f64>, &str> = stiffness.solve(column);
+
+        match solution {
+            Ok(result) => {
+                for (i, value) in result.iter().enumerate() {
+                    deflections[(i, case)] = *value;
+                }
+            }
+            Err(e) => return Err(format!("Load case {}: {}", case, e)),
+        }
+    }
+
+    Ok(deflections)
+}
+
 /**
  * Returns the non-zero entries comprising the assembled stiffness matrix in world coordinates.
  *
- * The individual stiffness contributions of each finite element stiffness matrix are summed
- * and combined in correspondence to the world degrees of freedom.
+ * The individual stiffness contributions of each frame, membrane and shell stiffness matrix are
+ * summed and combined in correspondence to the world degrees of freedom, and the stiffness of
+ * any support springs is added at the degrees of freedom of their nodes. The degrees of freedom
+ * of a node with local axes act along those axes. Constraints are left to the reduction onto the
+ * free degrees of freedom by `reduce_to_free_dofs`, so the entries of slave degrees of freedom
+ * remain available for recovering the constraint forces.
  */
 pub fn assemble_world_stiffness_matrix() -> Result<HashMap<(usize, usize), f64>, String> {
-    ELEMENT_DATA
-        .read()
-        .unwrap()
-        .frames
-        .values()
+    let (frames, membranes, shells, springs) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let frames: Vec<FrameElement> = data.frames.values().cloned().collect();
+        let element_nodes = |node_ids: &[String]| -> Option<Vec<Node>> {
+            node_ids
+                .iter()
+                .map(|y| data.nodes.get(y).cloned())
+                .collect()
+        };
+        let membranes: Vec<(MembraneElement, Option<Vec<Node>>)> = data
+            .membranes
+            .values()
+            .map(|x| (x.clone(), element_nodes(&x.node_ids)))
+            .collect();
+        let shells: Vec<(ShellElement, Option<Vec<Node>>)> = data
+            .shells
+            .values()
+            .map(|x| (x.clone(), element_nodes(&x.node_ids)))
+            .collect();
+        let springs: Vec<(Support, Option<Node>)> = data
+            .supports
+            .values()
+            .filter(|x| x.has_springs())
+            .map(|x| (x.clone(), data.nodes.get(&x.node_id).cloned()))
+            .collect();
+        (frames, membranes, shells, springs)
+    };
+
+    let mut assembled = frames
+        .iter()
         .try_fold(HashMap::<(usize, usize), f64>::new(), |acc, x| {
             merge_stiffness_matrix_at_frame_dofs(acc, x)
-        })
+        })?;
+
+    for (membrane, nodes) in membranes.iter() {
+        let nodes = match nodes {
+            Some(x) => x,
+            None => {
+                return Err(format!(
+                    "Failed to find the nodes of membrane {}",
+                    membrane.id
+                ))
+            }
+        };
+        let coordinates: Vec<_> = nodes.iter().map(|x| x.coordinate).collect();
+        let stiffness = membrane_world_stiffness_matrix(membrane, &coordinates);
+        assembled = merge_matrix_at_element_nodes(assembled, nodes, &stiffness, 3);
+    }
+
+    for (shell, nodes) in shells.iter() {
+        let nodes = match nodes {
+            Some(x) => x,
+            None => return Err(format!("Failed to find the nodes of shell {}", shell.id)),
+        };
+        let coordinates: Vec<_> = nodes.iter().map(|x| x.coordinate).collect();
+        let stiffness = shell_world_stiffness_matrix(shell, &coordinates);
+        assembled = merge_matrix_at_element_nodes(assembled, nodes, &stiffness, 6);
+    }
+
+    for (support, node) in springs.iter() {
+        let dofs = match node {
+            Some(x) => x.degrees_of_freedom,
+            None => return Err(format!("Failed to find node id = {}", &support.node_id)),
+        };
+        // Skewed springs are given in world coordinates, others act along the node's axes
+        let stiffness = match (&support.local_axes, node) {
+            (Some(_), Some(x)) => {
+                let transform = x.world_to_nodal_transform();
+                transform * support.spring_stiffness() * transform.transpose()
+            }
+            _ => support.spring_stiffness(),
+        };
+        for i in 0..6 {
+            for j in 0..6 {
+                if abs_diff_eq!(0., stiffness[(i, j)], epsilon = ZERO_EPSILON) {
+                    continue;
+                }
+                assembled =
+                    merge_stiffness_matrix_at_dof(assembled, dofs[i], dofs[j], stiffness[(i, j)]);
+            }
+        }
+    }
+
+    Ok(assembled)
 }
 
 fn merge_stiffness_matrix_at_frame_dofs(
-    mut assembled: HashMap<(usize, usize), f64>,
+    assembled: HashMap<(usize, usize), f64>,
     frame: &FrameElement,
 ) -> Result<HashMap<(usize, usize), f64>, String> {
     let stiffness: MatrixN<f64, U12> = match ANALYSIS_DATA
@@ -60,66 +527,141 @@ fn merge_stiffness_matrix_at_frame_dofs(
         None => return Err(format!("Failed to locate frame with id = {}", &frame.id)),
     };
 
-    let start_dofs = match frame.start_node() {
-        Some(x) => x.degrees_of_freedom,
+    merge_matrix_at_frame_dofs(assembled, frame, &stiffness)
+}
+
+/**
+ * Adds a 12x12 frame matrix in world coordinates into the assembled entries at the world
+ * degrees of freedom of the frame's start and end nodes, first rotating it into the local axes
+ * of either node where defined.
+ *
+ * # Arguments
+ *
+ * `assembled` - the non-zero entries assembled so far
+ * `frame` - the frame the matrix belongs to
+ * `matrix` - the frame matrix (stiffness, geometric stiffness, mass, ...) in world coordinates
+ */
+pub fn merge_matrix_at_frame_dofs(
+    mut assembled: HashMap<(usize, usize), f64>,
+    frame: &FrameElement,
+    matrix: &MatrixN<f64, U12>,
+) -> Result<HashMap<(usize, usize), f64>, String> {
+    let start = match frame.start_node() {
+        Some(x) => x,
         None => return Err(format!("Failed to find node id = {}", &frame.start_node_id)),
     };
-    let end_dofs = match frame.end_node() {
-        Some(x) => x.degrees_of_freedom,
+    let end = match frame.end_node() {
+        Some(x) => x,
         None => return Err(format!("Failed to find node id = {}", &frame.end_node_id)),
     };
+    let dofs: Vec<usize> = start
+        .degrees_of_freedom
+        .iter()
+        .chain(end.degrees_of_freedom.iter())
+        .cloned()
+        .collect();
 
-    for i in 0..6 {
-        println!("i = {}", i);
+    // Rotate into the local axes of any node with its own coordinate system
+    let nodal_matrix;
+    let matrix = if start.local_axes.is_some() || end.local_axes.is_some() {
+        let transform = world_to_frame_nodal_transform(&start, &end);
+        nodal_matrix = transform * matrix * transform.transpose();
+        &nodal_matrix
+    } else {
+        matrix
+    };
+
+    for i in 0..12 {
         for j in 0..12 {
             // Ignore very small values
-            if abs_diff_eq!(0., stiffness[(i, j)], epsilon = ZERO_EPSILON) {
+            if abs_diff_eq!(0., matrix[(i, j)], epsilon = ZERO_EPSILON) {
                 continue;
             }
+            assembled = merge_stiffness_matrix_at_dof(assembled, dofs[i], dofs[j], matrix[(i, j)]);
+        }
+    }
 
-            if j < 6 {
-                assembled = merge_stiffness_matrix_at_dof(
-                    assembled,
-                    start_dofs[i],
-                    start_dofs[j],
-                    stiffness[(i, j)],
-                )
-            } else {
-                assembled = merge_stiffness_matrix_at_dof(
-                    assembled,
-                    start_dofs[i],
-                    end_dofs[j - 6],
-                    stiffness[(i, j)],
-                )
-            }
+    Ok(assembled)
+}
+
+/// Returns the 12x12 transformation from world components at the start then end node of a frame
+/// to components along the nodes' degrees of freedom.
+pub fn world_to_frame_nodal_transform(start: &Node, end: &Node) -> MatrixN<f64, U12> {
+    let (start, end) = (
+        start.world_to_nodal_transform(),
+        end.world_to_nodal_transform(),
+    );
+    let mut transform = MatrixN::<f64, U12>::zeros();
+    for i in 0..6 {
+        for j in 0..6 {
+            transform[(i, j)] = start[(i, j)];
+            transform[(i + 6, j + 6)] = end[(i, j)];
         }
     }
+    transform
+}
 
-    for i in 6..12 {
-        for j in 0..12 {
-            // Ignore very small values
-            if abs_diff_eq!(0., stiffness[(i, j)], epsilon = ZERO_EPSILON) {
-                continue;
+/// Returns a stiffness block in world translations coupling node `a` to node `b` along the
+/// nodes' degrees of freedom, for elements without rotational stiffness
+pub(crate) fn translational_block_at_nodes(
+    a: &Node,
+    b: &Node,
+    block: &Matrix3<f64>,
+) -> Matrix6<f64> {
+    let mut world = Matrix6::<f64>::zeros();
+    for i in 0..3 {
+        for j in 0..3 {
+            world[(i, j)] = block[(i, j)];
+        }
+    }
+    a.world_to_nodal_transform() * world * b.world_to_nodal_transform().transpose()
+}
+
+/**
+ * Adds the world matrix of an element into the assembled entries at the degrees of freedom of
+ * its nodes, rotating each block into the local axes of the nodes where defined.
+ *
+ * # Arguments
+ *
+ * `assembled` - the non-zero entries assembled so far
+ * `nodes` - the nodes of the element in the order of its matrix
+ * `matrix` - the element matrix over the world directions of each node in turn
+ * `components` - the number of leading world directions `[Ux, Uy, Uz, Rx, Ry, Rz]` the matrix
+ * covers at each node
+ */
+fn merge_matrix_at_element_nodes(
+    mut assembled: HashMap<(usize, usize), f64>,
+    nodes: &[Node],
+    matrix: &DMatrix<f64>,
+    components: usize,
+) -> HashMap<(usize, usize), f64> {
+    for (a, start) in nodes.iter().enumerate() {
+        for (b, end) in nodes.iter().enumerate() {
+            let mut world = Matrix6::<f64>::zeros();
+            for i in 0..components {
+                for j in 0..components {
+                    world[(i, j)] = matrix[(components * a + i, components * b + j)];
+                }
             }
-            if j < 6 {
-                assembled = merge_stiffness_matrix_at_dof(
-                    assembled,
-                    end_dofs[i - 6],
-                    start_dofs[j],
-                    stiffness[(i, j)],
-                )
-            } else {
-                assembled = merge_stiffness_matrix_at_dof(
-                    assembled,
-                    end_dofs[i - 6],
-                    end_dofs[j - 6],
-                    stiffness[(i, j)],
-                )
+            let nodal = start.world_to_nodal_transform()
+                * world
+                * end.world_to_nodal_transform().transpose();
+            for i in 0..6 {
+                for j in 0..6 {
+                    if abs_diff_eq!(0., nodal[(i, j)], epsilon = ZERO_EPSILON) {
+                        continue;
+                    }
+                    assembled = merge_stiffness_matrix_at_dof(
+                        assembled,
+                        start.degrees_of_freedom[i],
+                        end.degrees_of_freedom[j],
+                        nodal[(i, j)],
+                    );
+                }
             }
         }
     }
-
-    Ok(assembled)
+    assembled
 }
 
 fn merge_stiffness_matrix_at_dof(
@@ -135,3 +677,40 @@ fn merge_stiffness_matrix_at_dof(
     }
     assembled
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stiffness() -> sparse21::Matrix {
+        let entries = [
+            (0, 0, 4.),
+            (0, 1, -1.),
+            (1, 0, -1.),
+            (1, 1, 4.),
+            (1, 2, -2.),
+            (2, 1, -2.),
+            (2, 2, 5.),
+        ];
+        let mut matrix = sparse21::Matrix::new();
+        for (i, j, value) in entries.iter() {
+            matrix.add_element(*i, *j, *value);
+        }
+        matrix
+    }
+
+    #[test]
+    fn load_cases_sharing_one_factorization_match_separate_solves() {
+        let cases = [vec![1., 0., 2.], vec![-3., 5., 0.5]];
+        let forces = DMatrix::from_fn(3, 2, |i, case| cases[case][i]);
+
+        let deflections = solve_for_deflections_by_load_case(&mut stiffness(), &forces).unwrap();
+
+        for (case, case_forces) in cases.iter().enumerate() {
+            let separate = solve_for_deflections(&mut stiffness(), case_forces.clone()).unwrap();
+            for i in 0..3 {
+                assert_relative_eq!(deflections[(i, case)], separate[i], max_relative = 1e-12);
+            }
+        }
+    }
+}
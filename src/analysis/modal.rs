@@ -0,0 +1,548 @@
+use crate::analysis::linear_static::assemble_checked_world_stiffness;
+use crate::analysis::*;
+use crate::elements::frame::{
+    frame_element_consistent_mass_matrix, frame_element_lumped_mass_matrix,
+    transform_frame_stiffness_local_to_world,
+};
+use crate::models::frame::FrameElement;
+use crate::models::validation::ValidationIssue;
+use crate::utils::ZERO_EPSILON;
+use crate::ELEMENT_DATA;
+use na::{DMatrix, Matrix6, Vector3, Vector6};
+use std::collections::HashMap;
+
+/// Reduced eigenvalues `μ = 1 / λ` at or below this fraction of the largest are massless modes.
+const MASSLESS_MODE_TOLERANCE: f64 = 1e-12;
+
+/// The Sturm sequence check counts the eigenvalues below the highest returned one raised by at
+/// least this fraction.
+const STURM_SHIFT: f64 = 1e-6;
+
+/// Times the subspace restarts from random vectors after the Sturm sequence check finds missed
+/// modes, before the solution is reported as not converged.
+const MAX_RESTARTS: u64 = 3;
+
+/// How the distributed mass of frame elements is represented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MassFormulation {
+    /// Mass matrix consistent with the element displacement functions
+    Consistent,
+    /// Diagonal mass matrix with half of the element mass at each end
+    Lumped,
+}
+
+/// Controls for the modal analysis.
+#[derive(Clone, Debug)]
+pub struct ModalSettings {
+    pub mode_count: usize,
+    pub mass_formulation: MassFormulation,
+    /// Convergence is reached once the relative change of every requested eigenvalue `ω²`
+    /// between iterations falls to or below this value
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for ModalSettings {
+    fn default() -> Self {
+        ModalSettings {
+            mode_count: 12,
+            mass_formulation: MassFormulation::Consistent,
+            tolerance: 1e-10,
+            max_iterations: 200,
+        }
+    }
+}
+
+pub struct Mode {
+    /// Circular frequency `ω` in radians per unit time
+    pub angular_frequency: f64,
+    /// Cyclic frequency `ω / 2π`
+    pub frequency: f64,
+    /// Period `2π / ω`
+    pub period: f64,
+    /// World mode shape `[Ux, Uy, Uz, Rx, Ry, Rz]` of every node, normalized so `φᵀ M φ = 1`
+    pub shape: HashMap<String, Vector6<f64>>,
+    /// Participation factors `φᵀ M r` for rigid body motion `r` along the world
+    /// `[X, Y, Z]` axes followed by rotation about the same axes through the world origin
+    pub participation_factors: Vector6<f64>,
+    /// Effective modal mass `(φᵀ M r)²` divided by the total mass `rᵀ M r`, in the same order
+    pub effective_mass_ratios: Vector6<f64>,
+}
+
+pub struct ModalResults {
+    /// Modes in order of increasing frequency
+    pub modes: Vec<Mode>,
+    /// Total mass `rᵀ M r` at the free degrees of freedom for each rigid body motion `r`
+    pub total_mass: Vector6<f64>,
+    /// Sum of the effective mass ratios of all returned modes
+    pub cumulative_mass_ratios: Vector6<f64>,
+    pub iterations: usize,
+    /// Whether the eigenvalues settled within the tolerance and the Sturm sequence check found
+    /// no missed mode
+    pub converged: bool,
+    /// Model validation warnings, which did not prevent the analysis
+    pub warnings: Vec<ValidationIssue>,
+}
+
+/// Eigenpairs of `k φ = ω² M φ` found by subspace iteration.
+pub struct SubspaceSolution {
+    /// Eigenvalues `ω²` in ascending order
+    pub eigenvalues: Vec<f64>,
+    /// Eigenvectors in the columns, normalized so `φᵀ M φ = 1`
+    pub eigenvectors: DMatrix<f64>,
+    pub iterations: usize,
+    /// Whether the eigenvalues settled within the tolerance and the Sturm sequence check found
+    /// no missed mode
+    pub converged: bool,
+}
+
+/**
+ * Runs a modal analysis, returning the lowest natural frequencies and mode shapes of the
+ * structure along with their participation in rigid body motion of the supports.
+ *
+ * The model is validated and the world stiffness is checked as for a linear static analysis.
+ * The mass comes from the density of every frame material plus any nodal masses. Fewer modes
+ * than requested are returned when fewer free degrees of freedom carry mass.
+ *
+ * # Arguments
+ *
+ * `settings` - number of modes, mass formulation, tolerance and iteration limit
+ */
+pub fn run_modal_analysis(settings: &ModalSettings) -> Result<ModalResults, String> {
+    let (world, warnings) = assemble_checked_world_stiffness()?;
+    let mass = assemble_world_mass_matrix(settings.mass_formulation)?;
+
+    let partition = partition_degrees_of_freedom();
+    let stiffness = reduce_to_free_dofs_dense(&world, &partition);
+    let mass = reduce_to_free_dofs_dense(&mass, &partition);
+
+    let solution = subspace_iteration(
+        &stiffness,
+        &mass,
+        settings.mode_count,
+        settings.tolerance,
+        settings.max_iterations,
+    )?;
+
+    let influence = rigid_body_influence(&partition);
+    let mass_influence = &mass * &influence;
+    let total_mass =
+        Vector6::from_iterator((0..6).map(|d| influence.column(d).dot(&mass_influence.column(d))));
+
+    let mut cumulative_mass_ratios = Vector6::<f64>::zeros();
+    let modes: Vec<Mode> = solution
+        .eigenvalues
+        .iter()
+        .enumerate()
+        .map(|(i, eigenvalue)| {
+            let shape = solution.eigenvectors.column(i).into_owned();
+            let participation_factors =
+                Vector6::from_iterator((0..6).map(|d| shape.dot(&mass_influence.column(d))));
+            let effective_mass_ratios = Vector6::from_iterator((0..6).map(|d| {
+                if total_mass[d] > 0. {
+                    participation_factors[d].powi(2) / total_mass[d]
+                } else {
+                    0.
+                }
+            }));
+            cumulative_mass_ratios += effective_mass_ratios;
+
+            let angular_frequency = eigenvalue.max(0.).sqrt();
+            Mode {
+                angular_frequency,
+                frequency: angular_frequency / (2. * std::f64::consts::PI),
+                period: 2. * std::f64::consts::PI / angular_frequency,
+                shape: nodal_shape(&shape, &partition),
+                participation_factors,
+                effective_mass_ratios,
+            }
+        })
+        .collect();
+
+    Ok(ModalResults {
+        modes,
+        total_mass,
+        cumulative_mass_ratios,
+        iterations: solution.iterations,
+        converged: solution.converged,
+        warnings,
+    })
+}
+
+/**
+ * Returns the non-zero entries of the world mass matrix, from the density of every frame
+ * material and the nodal masses.
+ *
+ * # Arguments
+ *
+ * `formulation` - whether frames use consistent or lumped mass matrices
+ */
+pub fn assemble_world_mass_matrix(
+    formulation: MassFormulation,
+) -> Result<HashMap<(usize, usize), f64>, String> {
+    let (frames, nodal_masses) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let frames: Vec<FrameElement> = data.frames.values().cloned().collect();
+        let nodal_masses: Vec<(Vector6<usize>, Matrix6<f64>)> = data
+            .nodal_masses
+            .values()
+            .filter_map(|x| {
+                data.nodes.get(&x.node_id).map(|node| {
+                    let transform = node.world_to_nodal_transform();
+                    let mass = transform * Matrix6::from_diagonal(&x.mass) * transform.transpose();
+                    (node.degrees_of_freedom, mass)
+                })
+            })
+            .collect();
+        (frames, nodal_masses)
+    };
+
+    let mut assembled =
+        frames
+            .iter()
+            .try_fold(HashMap::<(usize, usize), f64>::new(), |acc, frame| {
+                let local = match formulation {
+                    MassFormulation::Consistent => frame_element_consistent_mass_matrix(frame),
+                    MassFormulation::Lumped => frame_element_lumped_mass_matrix(frame),
+                };
+                let world = transform_frame_stiffness_local_to_world(frame, &local);
+                merge_matrix_at_frame_dofs(acc, frame, &world)
+            })?;
+
+    for (dofs, mass) in nodal_masses.iter() {
+        for i in 0..6 {
+            for j in 0..6 {
+                if !abs_diff_eq!(0., mass[(i, j)], epsilon = ZERO_EPSILON) {
+                    *assembled.entry((dofs[i], dofs[j])).or_insert(0.) += mass[(i, j)];
+                }
+            }
+        }
+    }
+
+    Ok(assembled)
+}
+
+/**
+ * Returns the lowest eigenpairs of `k φ = ω² M φ` using subspace iteration.
+ *
+ * A subspace of `min(2p, p + 8)` vectors is iterated by solving `k Y = M X` with a single
+ * Cholesky factor of `k`, then projecting both matrices onto `Y` and solving the small
+ * eigenproblem exactly. The number of modes `p` is limited to the rank of `M`, which is the
+ * number of degrees of freedom with mass on the diagonal unless constraints couple them. Coupled
+ * masses start the iteration from the dominant eigenvectors of `M` rather than from unit
+ * vectors, and otherwise the mass diagonal and unit vectors at the largest ratios of mass to
+ * stiffness start it. Either way the last starting vector is random.
+ *
+ * Once the eigenvalues settle, a Sturm sequence count of `k - σ M` just above the highest
+ * requested eigenvalue checks that none was missed. Missed modes restart the rest of the
+ * subspace from random vectors a few times, after which the solution is reported as not
+ * converged.
+ *
+ * # Arguments
+ *
+ * `stiffness` - the positive definite stiffness `k` of the free degrees of freedom
+ * `mass` - the positive semi-definite mass `M` of the free degrees of freedom
+ * `mode_count` - the number of eigenpairs `p` to return
+ * `tolerance` - largest relative change of the requested eigenvalues at convergence
+ * `max_iterations` - the iteration limit
+ */
+pub fn subspace_iteration(
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+    mode_count: usize,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<SubspaceSolution, String> {
+    let n = stiffness.nrows();
+
+    // Start from the degrees of freedom with the largest ratio of mass to stiffness
+    let mut ratios: Vec<(f64, usize)> = (0..n)
+        .filter(|i| mass[(*i, *i)] > 0.)
+        .map(|i| (mass[(i, i)] / stiffness[(i, i)], i))
+        .collect();
+    ratios.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+    // Constraints can couple the mass of several degrees of freedom, leaving fewer independent
+    // masses than degrees of freedom with mass
+    let massful: Vec<usize> = ratios.iter().map(|x| x.1).collect();
+    let massful_block = DMatrix::<f64>::from_fn(massful.len(), massful.len(), |i, j| {
+        mass[(massful[i], massful[j])]
+    });
+    let dependent = massful_block.clone().cholesky().is_none();
+    let (rank, dominant) = if dependent {
+        let eigen = massful_block.symmetric_eigen();
+        let largest = eigen.eigenvalues.iter().fold(0., |acc: f64, x| acc.max(*x));
+        let mut order: Vec<usize> = (0..massful.len())
+            .filter(|i| eigen.eigenvalues[*i] > MASSLESS_MODE_TOLERANCE * largest)
+            .collect();
+        order.sort_by(|a, b| {
+            eigen.eigenvalues[*b]
+                .partial_cmp(&eigen.eigenvalues[*a])
+                .unwrap()
+        });
+        (order.len(), Some((eigen.eigenvectors, order)))
+    } else {
+        (massful.len(), None)
+    };
+
+    let p = mode_count.min(rank);
+    if p == 0 {
+        return Err("There is no mass at the free degrees of freedom".to_string());
+    }
+    let q = (2 * p).min(p + 8).min(rank);
+
+    let cholesky = match stiffness.clone().cholesky() {
+        Some(x) => x,
+        None => return Err("The world stiffness matrix is not positive definite".to_string()),
+    };
+
+    // Bathe's starting vectors: the mass diagonal, unit vectors at the largest ratios of mass to
+    // stiffness and a random vector, which keeps the subspace from lying within a few invariant
+    // subspaces of a symmetric structure
+    let mut x = DMatrix::<f64>::zeros(n, q);
+    match dominant {
+        Some((vectors, order)) => {
+            for j in 0..q {
+                for (i, dof) in massful.iter().enumerate() {
+                    x[(*dof, j)] = vectors[(i, order[j])];
+                }
+            }
+        }
+        None => {
+            x.set_column(0, &mass.diagonal());
+            for j in 1..q {
+                x[(ratios[j - 1].1, j)] = 1.;
+            }
+        }
+    }
+    if q > 1 {
+        x.set_column(q - 1, &pseudo_random_vectors(n, 1, 0).column(0));
+    }
+
+    let mut eigenvalues = vec![f64::INFINITY; q];
+    let mut restarts = 0;
+    for iteration in 1..=max_iterations {
+        let mass_x = mass * &x;
+        let y = cholesky.solve(&mass_x);
+        let reduced_stiffness = y.transpose() * &mass_x;
+        let reduced_mass = y.transpose() * mass * &y;
+
+        let (values, vectors) = reduced_eigenpairs(&reduced_stiffness, &reduced_mass)?;
+        x = y * vectors;
+
+        let converged = values
+            .iter()
+            .zip(eigenvalues.iter())
+            .take(p)
+            .all(|(current, previous)| (current - previous).abs() <= tolerance * current.abs());
+        eigenvalues = values;
+
+        // The Sturm sequence count of k - σ M confirms that no eigenvalue below the highest
+        // returned one was missed
+        let complete = converged && {
+            let shift = eigenvalues[p - 1] * (1. + STURM_SHIFT.max(100. * tolerance));
+            let found = eigenvalues.iter().filter(|x| **x < shift).count();
+            eigenvalues_below(stiffness, mass, shift)? == found
+        };
+
+        // A subspace that missed modes keeps the modes it found, and the rest of its vectors
+        // restart from random vectors
+        if converged && !complete && restarts < MAX_RESTARTS && p < q {
+            restarts += 1;
+            let random = pseudo_random_vectors(n, q - p, restarts);
+            x.columns_mut(p, q - p).copy_from(&random);
+            eigenvalues = vec![f64::INFINITY; q];
+            continue;
+        }
+
+        if converged || iteration == max_iterations {
+            eigenvalues.truncate(p);
+            return Ok(SubspaceSolution {
+                eigenvectors: x.columns(0, p).into_owned(),
+                eigenvalues,
+                iterations: iteration,
+                converged: complete,
+            });
+        }
+    }
+
+    Err("Subspace iteration requires at least one iteration".to_string())
+}
+
+/**
+ * Solves the projected eigenproblem `kr φ = λ mr φ` of the subspace, returning the eigenvalues
+ * in ascending order and the eigenvectors normalized so `φᵀ mr φ = 1`.
+ *
+ * With the Cholesky factor `kr = L Lᵀ`, the problem becomes `C ψ = μ ψ` where
+ * `C = L⁻¹ mr L⁻ᵀ`, `μ = 1 / λ` and `φ = L⁻ᵀ ψ`. Vectors without mass have `μ = 0` and are
+ * ordered last with an infinite eigenvalue.
+ */
+fn reduced_eigenpairs(
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+) -> Result<(Vec<f64>, DMatrix<f64>), String> {
+    let symmetric = (stiffness + stiffness.transpose()) * 0.5;
+    let l = match symmetric.cholesky() {
+        Some(x) => x.l(),
+        None => return Err("Failed to reduce the stiffness onto the subspace".to_string()),
+    };
+    let half = match l.solve_lower_triangular(mass) {
+        Some(x) => x,
+        None => return Err("Failed to reduce the mass onto the subspace".to_string()),
+    };
+    let reduced = match l.solve_lower_triangular(&half.transpose()) {
+        Some(x) => x,
+        None => return Err("Failed to reduce the mass onto the subspace".to_string()),
+    };
+    let eigen = ((&reduced + reduced.transpose()) * 0.5).symmetric_eigen();
+    let lt = l.transpose();
+
+    let largest = eigen.eigenvalues.amax();
+    let mut order: Vec<usize> = (0..eigen.eigenvalues.len()).collect();
+    order.sort_by(|a, b| {
+        eigen.eigenvalues[*b]
+            .partial_cmp(&eigen.eigenvalues[*a])
+            .unwrap()
+    });
+
+    let mut values = Vec::<f64>::new();
+    let mut vectors = DMatrix::<f64>::zeros(stiffness.nrows(), order.len());
+    for (column, i) in order.into_iter().enumerate() {
+        let mu = eigen.eigenvalues[i];
+        let phi = match lt.solve_upper_triangular(&eigen.eigenvectors.column(i).into_owned()) {
+            Some(x) => x,
+            None => return Err("Failed to recover the subspace eigenvectors".to_string()),
+        };
+        if mu > MASSLESS_MODE_TOLERANCE * largest {
+            values.push(1. / mu);
+            vectors.set_column(column, &(phi / mu.sqrt()));
+        } else {
+            values.push(f64::INFINITY);
+            vectors.set_column(column, &phi);
+        }
+    }
+    Ok((values, vectors))
+}
+
+/**
+ * Counts the eigenvalues of `k φ = λ M φ` below `shift` from the number of negative pivots of the
+ * `L D Lᵀ` factorization of `k - shift M`, which equals the number of its negative eigenvalues.
+ */
+fn eigenvalues_below(
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+    shift: f64,
+) -> Result<usize, String> {
+    let n = stiffness.nrows();
+    let mut a = stiffness - mass * shift;
+    let scale = a.amax();
+    let mut negative = 0;
+    for j in 0..n {
+        let pivot = a[(j, j)];
+        if pivot.abs() <= ZERO_EPSILON * scale {
+            return Err("The shift of the Sturm sequence check is an eigenvalue".to_string());
+        }
+        if pivot < 0. {
+            negative += 1;
+        }
+        for i in j + 1..n {
+            let factor = a[(i, j)] / pivot;
+            if factor == 0. {
+                continue;
+            }
+            for k in i..n {
+                a[(k, i)] -= factor * a[(k, j)];
+            }
+        }
+    }
+    Ok(negative)
+}
+
+/// Returns `count` vectors of pseudo-random entries in `[-1, 1)`, the same for every `seed` so
+/// results are repeatable
+fn pseudo_random_vectors(n: usize, count: usize, seed: u64) -> DMatrix<f64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    DMatrix::from_fn(n, count, |_, _| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 52) as f64 - 1.
+    })
+}
+
+/// Returns the free degree of freedom components of unit rigid body translations along the
+/// world axes followed by unit rotations about the world axes through the origin
+fn rigid_body_influence(partition: &DegreeOfFreedomPartition) -> DMatrix<f64> {
+    let data = ELEMENT_DATA.read().unwrap();
+    let mut influence = DMatrix::<f64>::zeros(partition.free.len(), 6);
+
+    for node in data.nodes.values() {
+        let position = node.coordinate.coords;
+        for axis in 0..3 {
+            let mut unit = Vector3::<f64>::zeros();
+            unit[axis] = 1.;
+            let rotation_translation = unit.cross(&position);
+
+            let mut translation = Vector6::<f64>::zeros();
+            translation[axis] = 1.;
+            let mut rotation = Vector6::<f64>::zeros();
+            rotation[axis + 3] = 1.;
+            for i in 0..3 {
+                rotation[i] = rotation_translation[i];
+            }
+
+            let translation = node.from_world(&translation);
+            let rotation = node.from_world(&rotation);
+            for (j, dof) in node.degrees_of_freedom.iter().enumerate() {
+                if let Some(row) = partition.free_index.get(dof) {
+                    influence[(*row, axis)] = translation[j];
+                    influence[(*row, axis + 3)] = rotation[j];
+                }
+            }
+        }
+    }
+    influence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shear_building_matches_closed_form_frequencies() {
+        // Three equal storeys with unit storey stiffness and unit floor mass
+        #[rustfmt::skip]
+        let stiffness = DMatrix::from_row_slice(3, 3, &[ 2., -1.,  0.,
+                                                        -1.,  2., -1.,
+                                                         0., -1.,  1.]);
+        let mass = DMatrix::<f64>::identity(3, 3);
+
+        let solution = subspace_iteration(&stiffness, &mass, 2, 1e-12, 100).unwrap();
+        assert!(solution.converged);
+        assert_eq!(solution.eigenvalues.len(), 2);
+        for (j, eigenvalue) in solution.eigenvalues.iter().enumerate() {
+            let expected = 2. - 2. * ((2. * j as f64 + 1.) * std::f64::consts::PI / 7.).cos();
+            assert_relative_eq!(*eigenvalue, expected, max_relative = 1e-9);
+
+            let shape = solution.eigenvectors.column(j);
+            assert_relative_eq!(
+                (shape.transpose() * &mass * shape)[(0, 0)],
+                1.,
+                max_relative = 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn massless_degrees_of_freedom_limit_the_mode_count() {
+        #[rustfmt::skip]
+        let stiffness = DMatrix::from_row_slice(2, 2, &[ 2., -1.,
+                                                        -1.,  1.]);
+        let mass = DMatrix::from_row_slice(2, 2, &[0., 0., 0., 1.]);
+
+        let solution = subspace_iteration(&stiffness, &mass, 2, 1e-12, 100).unwrap();
+        assert_eq!(solution.eigenvalues.len(), 1);
+        // Condensing out the massless dof leaves a stiffness of 1 / 2
+        assert_relative_eq!(solution.eigenvalues[0], 0.5, max_relative = 1e-9);
+    }
+}
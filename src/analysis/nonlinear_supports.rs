@@ -0,0 +1,198 @@
+use crate::analysis::linear_static::*;
+use crate::analysis::*;
+use crate::models::node::{Direction, Node};
+use crate::models::support::SupportCondition;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::Vector6;
+use std::collections::HashMap;
+
+/// Controls for the iteration on the stiffness of nonlinear support springs.
+#[derive(Clone, Debug)]
+pub struct NonlinearSupportSettings {
+    /// Convergence is reached once `‖Δᵢ − Δᵢ₋₁‖ / ‖Δᵢ‖` falls to or below this value
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for NonlinearSupportSettings {
+    fn default() -> Self {
+        NonlinearSupportSettings {
+            tolerance: 1e-8,
+            max_iterations: 50,
+        }
+    }
+}
+
+pub struct NonlinearSupportResults {
+    /// Displacements, reactions and frame end forces at the converged spring stiffnesses. The
+    /// spring forces follow the force-displacement curve of each spring.
+    pub results: LinearStaticResults,
+    pub iterations: usize,
+    pub converged: bool,
+    /// Nodes and directions of the nonlinear springs carrying no force while their node has
+    /// moved, such as compression-only springs which have lifted off
+    pub lifted_off: Vec<(String, Direction)>,
+}
+
+/// A compression-only or multilinear spring acting at a single degree of freedom.
+struct NonlinearSpring {
+    node_id: String,
+    direction: Direction,
+    dof: usize,
+    condition: SupportCondition,
+}
+
+/**
+ * Runs a static analysis of a model with compression-only or multilinear support springs.
+ *
+ * Every nonlinear spring starts at its initial stiffness. After each solution of `F = k Δ` the
+ * stiffness of each spring is replaced with its secant stiffness `F(δ) / δ` at the new
+ * displacement, which for compression-only springs switches them between active and lifted
+ * off. The iteration stops once the displacements settle.
+ *
+ * # Arguments
+ *
+ * `settings` - convergence tolerance and iteration limit
+ */
+pub fn run_nonlinear_support_analysis(
+    settings: &NonlinearSupportSettings,
+) -> Result<NonlinearSupportResults, String> {
+    let (world, warnings) = assemble_checked_world_stiffness()?;
+
+    let partition = partition_degrees_of_freedom();
+    let mut forces = ANALYSIS_DATA.read().unwrap().applied_forces.clone();
+    forces.resize(partition.dof_count, 0.);
+
+    let (nodes, springs) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let mut springs = Vec::<NonlinearSpring>::new();
+        for support in data.supports.values() {
+            let node = match data.nodes.get(&support.node_id) {
+                Some(x) => x,
+                None => continue,
+            };
+            for direction in Direction::ALL.iter() {
+                let condition = support.condition(*direction);
+                if condition.is_nonlinear() {
+                    springs.push(NonlinearSpring {
+                        node_id: node.id.clone(),
+                        direction: *direction,
+                        dof: node.degrees_of_freedom[direction.index()],
+                        condition: condition.clone(),
+                    });
+                }
+            }
+        }
+        (data.nodes.clone(), springs)
+    };
+
+    let initial: Vec<f64> = springs
+        .iter()
+        .map(|x| x.condition.initial_stiffness())
+        .collect();
+    let mut stiffnesses = initial.clone();
+    let mut previous = vec![0.; partition.dof_count];
+    let mut secant_world = world.clone();
+    let mut iterations = 0;
+    let mut converged = false;
+
+    for iteration in 1..=settings.max_iterations.max(1) {
+        secant_world = with_spring_stiffnesses(&world, &springs, &initial, &stiffnesses);
+        let deflections = solve_with_prescribed_displacements(&secant_world, &forces, &partition);
+        let deflections = deflections.map_err(|e| {
+            format!(
+                "Stiffness is singular at iteration {}, so the structure is unstable once its \
+                 supports lift off or soften: {}",
+                iteration, e
+            )
+        })?;
+
+        let norm = deflections.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let change = deflections
+            .iter()
+            .zip(previous.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        let relative_change = if norm > 0. { change / norm } else { 0. };
+
+        stiffnesses = springs
+            .iter()
+            .map(|x| x.condition.secant_stiffness(deflections[x.dof]))
+            .collect();
+        previous = deflections;
+        iterations = iteration;
+
+        if springs.is_empty() || relative_change <= settings.tolerance {
+            converged = true;
+            break;
+        }
+    }
+    let deflections = previous;
+
+    let mut results = collect_results(&secant_world, &forces, &deflections);
+    results.warnings = warnings;
+    correct_spring_forces(&mut results, &nodes, &springs, &deflections);
+    let internal = multiply_stiffness(&secant_world, &deflections);
+    results.equilibrium = check_equilibrium(
+        &nodes,
+        &forces,
+        &internal,
+        &results.reactions,
+        &results.spring_forces,
+        &results.constraint_forces,
+    );
+
+    let lifted_off = springs
+        .iter()
+        .filter(|x| {
+            let displacement = deflections[x.dof];
+            displacement != 0. && x.condition.spring_force(displacement) == 0.
+        })
+        .map(|x| (x.node_id.clone(), x.direction))
+        .collect();
+
+    Ok(NonlinearSupportResults {
+        results,
+        iterations,
+        converged,
+        lifted_off,
+    })
+}
+
+/// Returns the world stiffness with the initial stiffness of each nonlinear spring replaced by
+/// the given stiffness
+fn with_spring_stiffnesses(
+    world: &HashMap<(usize, usize), f64>,
+    springs: &[NonlinearSpring],
+    initial: &[f64],
+    stiffnesses: &[f64],
+) -> HashMap<(usize, usize), f64> {
+    let mut updated = world.clone();
+    for (i, spring) in springs.iter().enumerate() {
+        *updated.entry((spring.dof, spring.dof)).or_insert(0.) += stiffnesses[i] - initial[i];
+    }
+    updated
+}
+
+/// Replaces the forces of the nonlinear springs at their initial stiffness with the forces
+/// from their curves
+fn correct_spring_forces(
+    results: &mut LinearStaticResults,
+    nodes: &HashMap<String, Node>,
+    springs: &[NonlinearSpring],
+    deflections: &[f64],
+) {
+    for spring in springs.iter() {
+        let node = &nodes[&spring.node_id];
+        let world = match results.spring_forces.get(&spring.node_id) {
+            Some(x) => *x,
+            None => Vector6::zeros(),
+        };
+        let mut nodal = node.from_world(&world);
+        nodal[spring.direction.index()] = -spring.condition.spring_force(deflections[spring.dof]);
+        results
+            .spring_forces
+            .insert(spring.node_id.clone(), node.to_world(&nodal));
+    }
+}
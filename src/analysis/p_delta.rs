@@ -0,0 +1,388 @@
+use crate::analysis::linear_static::*;
+use crate::analysis::*;
+use crate::elements::frame::{
+    frame_element_geometric_stiffness_matrix, frame_element_string_stiffness_matrix,
+    frame_end_forces_at_faces, transform_frame_stiffness_local_to_world,
+};
+use crate::models::frame::FrameElement;
+use crate::models::node::Node;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::{MatrixN, U12};
+use std::collections::HashMap;
+
+/// Second-order effects included in a P-Delta analysis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecondOrderEffects {
+    /// Axial forces acting through the relative displacement of the member ends (P-Δ), using
+    /// the string stiffness `P / L`
+    PDelta,
+    /// P-Δ plus axial forces acting through the curvature of the member between its ends
+    /// (P-δ), using the consistent geometric stiffness
+    PDeltaAndPdelta,
+}
+
+/// Controls for the P-Delta iteration.
+#[derive(Clone, Debug)]
+pub struct PDeltaSettings {
+    pub effects: SecondOrderEffects,
+    /// Convergence is reached once `‖Δᵢ − Δᵢ₋₁‖ / ‖Δᵢ‖` falls to or below this value
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for PDeltaSettings {
+    fn default() -> Self {
+        PDeltaSettings {
+            effects: SecondOrderEffects::PDeltaAndPdelta,
+            tolerance: 1e-6,
+            max_iterations: 50,
+        }
+    }
+}
+
+pub struct PDeltaResults {
+    /// Second-order displacements, reactions and frame end forces. The end forces include the
+    /// geometric stiffness, and the equilibrium imbalance is taken about the undeformed geometry
+    /// so it contains the second-order overturning moments.
+    pub results: LinearStaticResults,
+    /// Results of the first-order analysis the iteration started from
+    pub first_order: LinearStaticResults,
+    pub iterations: usize,
+    pub converged: bool,
+    /// Relative change in displacement `‖Δᵢ − Δᵢ₋₁‖ / ‖Δᵢ‖` after every iteration
+    pub displacement_change_history: Vec<f64>,
+    /// Largest ratio of a second-order to a first-order nodal translation component
+    pub amplification: f64,
+}
+
+/// Outcome of iterating the second-order equilibrium equations.
+pub struct SecondOrderSolution {
+    /// Deflections at every world degree of freedom
+    pub deflections: Vec<f64>,
+    /// Non-zero entries of `k + kg` for the final axial forces
+    pub tangent_stiffness: HashMap<(usize, usize), f64>,
+    pub iterations: usize,
+    pub converged: bool,
+    pub displacement_change_history: Vec<f64>,
+}
+
+/**
+ * Runs a second-order static analysis under the applied forces.
+ *
+ * A linear static analysis provides the initial frame axial forces. The geometric stiffness of
+ * those forces is added to the elastic stiffness, the equations `F = (k + kg) Δ` are solved, and
+ * the axial forces are recomputed from the new displacements until the displacements converge.
+ *
+ * An error is returned if the model fails the linear static analysis, or if the iteration
+ * diverges because the loads approach or exceed the elastic critical load of the structure. A
+ * result is returned when the iteration limit is reached before convergence; check `converged`
+ * and `displacement_change_history`.
+ *
+ * # Arguments
+ *
+ * `settings` - second-order effects, tolerance and iteration limit
+ */
+pub fn run_p_delta_analysis(settings: &PDeltaSettings) -> Result<PDeltaResults, String> {
+    let first_order = run_linear_static_analysis()?;
+
+    let world = ANALYSIS_DATA.read().unwrap().world_stiffness.clone();
+    let partition = partition_degrees_of_freedom();
+    let mut forces = ANALYSIS_DATA.read().unwrap().applied_forces.clone();
+    forces.resize(partition.dof_count, 0.);
+
+    let (nodes, frames) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let frames: Vec<FrameElement> = data.frames.values().cloned().collect();
+        (data.nodes.clone(), frames)
+    };
+    let local_stiffnesses: HashMap<String, MatrixN<f64, U12>> = ANALYSIS_DATA
+        .read()
+        .unwrap()
+        .frame_stiffnesses
+        .iter()
+        .map(|(id, x)| (id.clone(), x.local))
+        .collect();
+
+    let mut initial = vec![0.; partition.dof_count];
+    for node in nodes.values() {
+        if let Some(displacement) = first_order.displacements.get(&node.id) {
+            let displacement = node.from_world(displacement);
+            for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+                initial[*dof] = displacement[i];
+            }
+        }
+    }
+
+    let solution = iterate_second_order(
+        &world,
+        &forces,
+        &partition,
+        initial,
+        settings,
+        |deflections| {
+            frames
+                .iter()
+                .try_fold(HashMap::<(usize, usize), f64>::new(), |acc, frame| {
+                    let local = local_geometric_stiffness(
+                        frame,
+                        &nodes,
+                        &local_stiffnesses,
+                        deflections,
+                        settings.effects,
+                    )?;
+                    let world = transform_frame_stiffness_local_to_world(frame, &local);
+                    merge_matrix_at_frame_dofs(acc, frame, &world)
+                })
+        },
+    )?;
+
+    let mut results = collect_results(&solution.tangent_stiffness, &forces, &solution.deflections);
+    for frame in frames.iter() {
+        let local = local_geometric_stiffness(
+            frame,
+            &nodes,
+            &local_stiffnesses,
+            &solution.deflections,
+            settings.effects,
+        )?;
+        if let (Some(end_forces), Some(start), Some(end)) = (
+            results.frame_end_forces.get_mut(&frame.id),
+            nodes.get(&frame.start_node_id),
+            nodes.get(&frame.end_node_id),
+        ) {
+            let forces =
+                local * frame_local_displacements(frame, start, end, &solution.deflections);
+            *end_forces += frame_end_forces_at_faces(frame, &forces);
+        }
+    }
+    results.warnings = first_order.warnings.clone();
+
+    let amplification = translation_amplification(&results, &first_order);
+
+    Ok(PDeltaResults {
+        results,
+        first_order,
+        iterations: solution.iterations,
+        converged: solution.converged,
+        displacement_change_history: solution.displacement_change_history,
+        amplification,
+    })
+}
+
+/**
+ * Iterates `F = (k + kg(Δ)) Δ` from an initial estimate of the deflections.
+ *
+ * The iteration is stopped with an error if the work `F · Δ` done by the loads is negative,
+ * which means `k + kg` is no longer positive definite, or if the change in displacement grows
+ * for three iterations in a row.
+ *
+ * # Arguments
+ *
+ * `world` - non-zero entries of the elastic world stiffness matrix
+ * `forces` - applied forces at every world degree of freedom
+ * `partition` - the free and restrained degrees of freedom
+ * `initial` - deflections at every world degree of freedom used to start the iteration
+ * `settings` - tolerance and iteration limit
+ * `geometric` - returns the world geometric stiffness for the axial forces of given deflections
+ */
+pub fn iterate_second_order<F>(
+    world: &HashMap<(usize, usize), f64>,
+    forces: &[f64],
+    partition: &DegreeOfFreedomPartition,
+    initial: Vec<f64>,
+    settings: &PDeltaSettings,
+    mut geometric: F,
+) -> Result<SecondOrderSolution, String>
+where
+    F: FnMut(&[f64]) -> Result<HashMap<(usize, usize), f64>, String>,
+{
+    let mut previous = initial;
+    let mut tangent_stiffness = world.clone();
+    let mut displacement_change_history = Vec::<f64>::new();
+
+    for iteration in 1..=settings.max_iterations {
+        tangent_stiffness = world.clone();
+        for (key, value) in geometric(&previous)?.into_iter() {
+            *tangent_stiffness.entry(key).or_insert(0.) += value;
+        }
+
+        let deflections =
+            solve_with_prescribed_displacements(&tangent_stiffness, forces, partition).map_err(
+                |e| {
+                    format!(
+                        "Second-order stiffness is singular at iteration {}: {}",
+                        iteration, e
+                    )
+                },
+            )?;
+
+        let work: f64 = forces
+            .iter()
+            .zip(deflections.iter())
+            .map(|(f, d)| f * d)
+            .sum();
+        if deflections.iter().any(|x| !x.is_finite()) || work < 0. {
+            return Err(format!(
+                "Second-order stiffness lost positive definiteness at iteration {}; the loads exceed the critical load",
+                iteration
+            ));
+        }
+
+        let norm = deflections.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let change = deflections
+            .iter()
+            .zip(previous.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        let relative_change = if norm > 0. { change / norm } else { 0. };
+        displacement_change_history.push(relative_change);
+        previous = deflections;
+
+        if relative_change <= settings.tolerance {
+            return Ok(SecondOrderSolution {
+                deflections: previous,
+                tangent_stiffness,
+                iterations: iteration,
+                converged: true,
+                displacement_change_history,
+            });
+        }
+
+        let growing = displacement_change_history
+            .windows(2)
+            .rev()
+            .take(3)
+            .filter(|x| x[1] > x[0])
+            .count();
+        if growing == 3 {
+            return Err(format!(
+                "P-Delta iteration diverged at iteration {} with a relative displacement change of {:e}",
+                iteration, relative_change
+            ));
+        }
+    }
+
+    Ok(SecondOrderSolution {
+        deflections: previous,
+        tangent_stiffness,
+        iterations: settings.max_iterations,
+        converged: false,
+        displacement_change_history,
+    })
+}
+
+/// Returns the local geometric stiffness of a frame for the axial force implied by `deflections`
+fn local_geometric_stiffness(
+    frame: &FrameElement,
+    nodes: &HashMap<String, Node>,
+    local_stiffnesses: &HashMap<String, MatrixN<f64, U12>>,
+    deflections: &[f64],
+    effects: SecondOrderEffects,
+) -> Result<MatrixN<f64, U12>, String> {
+    let (start, end) = match (
+        nodes.get(&frame.start_node_id),
+        nodes.get(&frame.end_node_id),
+    ) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return Err(format!("Failed to locate nodes of frame id = {}", frame.id)),
+    };
+    let stiffness = match local_stiffnesses.get(&frame.id) {
+        Some(x) => x,
+        None => {
+            return Err(format!(
+                "Failed to locate stiffness of frame id = {}",
+                frame.id
+            ))
+        }
+    };
+
+    // The force on the end node along local x is the axial force, tension positive
+    let axial_force = (stiffness * frame_local_displacements(frame, start, end, deflections))[6];
+    Ok(match effects {
+        SecondOrderEffects::PDelta => frame_element_string_stiffness_matrix(frame, axial_force),
+        SecondOrderEffects::PDeltaAndPdelta => {
+            frame_element_geometric_stiffness_matrix(frame, axial_force)
+        }
+    })
+}
+
+/// Largest ratio of a second-order to a first-order translation component, considering only
+/// components of at least one percent of the largest first-order translation
+fn translation_amplification(
+    second_order: &LinearStaticResults,
+    first_order: &LinearStaticResults,
+) -> f64 {
+    let largest = first_order
+        .displacements
+        .values()
+        .flat_map(|x| x.iter().take(3))
+        .fold(0., |acc: f64, x| acc.max(x.abs()));
+
+    let mut amplification = 1.;
+    for (id, first) in first_order.displacements.iter() {
+        let second = match second_order.displacements.get(id) {
+            Some(x) => x,
+            None => continue,
+        };
+        for i in 0..3 {
+            if first[i].abs() >= 0.01 * largest && first[i] != 0. {
+                amplification = f64::max(amplification, (second[i] / first[i]).abs());
+            }
+        }
+    }
+    amplification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single free degree of freedom with elastic stiffness `k = 4`
+    fn single_spring() -> (HashMap<(usize, usize), f64>, DegreeOfFreedomPartition) {
+        let mut world = HashMap::<(usize, usize), f64>::new();
+        world.insert((0, 0), 4.);
+        let partition = DegreeOfFreedomPartition {
+            free: vec![0],
+            restrained: Vec::new(),
+            prescribed: HashMap::new(),
+            free_index: vec![(0, 0)].into_iter().collect(),
+            constraints: HashMap::new(),
+            dof_count: 1,
+        };
+        (world, partition)
+    }
+
+    #[test]
+    fn compression_amplifies_deflection() {
+        let (world, partition) = single_spring();
+        // The geometric stiffness halves the elastic stiffness, i.e. P / Pcr = 0.5
+        let solution = iterate_second_order(
+            &world,
+            &[1.],
+            &partition,
+            vec![0.25],
+            &PDeltaSettings::default(),
+            |_| Ok(vec![((0, 0), -2.)].into_iter().collect()),
+        )
+        .unwrap();
+        assert!(solution.converged);
+        assert_eq!(solution.iterations, 2);
+        // Amplified by 1 / (1 - P / Pcr) = 2
+        assert_relative_eq!(solution.deflections[0], 0.5, max_relative = 1e-12);
+    }
+
+    #[test]
+    fn loads_beyond_critical_report_divergence() {
+        let (world, partition) = single_spring();
+        let result = iterate_second_order(
+            &world,
+            &[1.],
+            &partition,
+            vec![0.25],
+            &PDeltaSettings::default(),
+            |_| Ok(vec![((0, 0), -8.)].into_iter().collect()),
+        );
+        assert!(result.is_err());
+    }
+}
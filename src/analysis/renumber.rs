@@ -0,0 +1,416 @@
+use crate::models::constraint::Constraint;
+use crate::ELEMENT_DATA;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Graph reordering strategies used to order the world degrees of freedom for the solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenumberingMethod {
+    /// Reverse Cuthill–McKee, which minimizes the bandwidth and profile
+    ReverseCuthillMckee,
+    /// Minimum degree, which minimizes the fill-in produced during factorization
+    MinimumDegree,
+}
+
+/// Bandwidth and profile of the world stiffness matrix before and after renumbering.
+pub struct RenumberingReport {
+    pub bandwidth_before: usize,
+    pub bandwidth_after: usize,
+    pub profile_before: usize,
+    pub profile_after: usize,
+    /// Maps each world degree of freedom to its position in the solution order
+    pub dof_map: HashMap<usize, usize>,
+}
+
+/**
+ * Returns the order in which to solve the world degrees of freedom so as to reduce the
+ * bandwidth or fill-in of the assembled world stiffness matrix, along with the bandwidth and
+ * profile before and after reordering.
+ *
+ * Nodes are reordered over the node connectivity graph implied by the elements, with each
+ * constraint joining its slave nodes to their masters. Each node keeps its six degrees of
+ * freedom together, so the node at position `k` of the new ordering takes positions
+ * `6k..6k + 6`. The nodes keep their own degree of freedom numbers:
+ * `partition_degrees_of_freedom` orders the free degrees of freedom by these positions, which
+ * only permutes the equations handed to the solver.
+ *
+ * # Arguments
+ *
+ * `method` - the reordering strategy to apply
+ */
+pub fn degree_of_freedom_ordering(method: RenumberingMethod) -> Result<RenumberingReport, String> {
+    let data = ELEMENT_DATA.read().unwrap();
+
+    // Order the nodes by their own numbering so the "before" ordering is reproducible
+    let mut node_ids: Vec<&String> = data.nodes.keys().collect();
+    node_ids.sort_by_key(|id| {
+        (
+            data.nodes[*id].degrees_of_freedom.iter().min().cloned(),
+            (*id).clone(),
+        )
+    });
+
+    let index: HashMap<&String, usize> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect();
+
+    let mut element_nodes = Vec::<Vec<usize>>::with_capacity(data.frames.len() + data.cables.len());
+    let element_ends = data
+        .frames
+        .values()
+        .map(|x| (&x.start_node_id, &x.end_node_id))
+        .chain(
+            data.cables
+                .values()
+                .map(|x| (&x.start_node_id, &x.end_node_id)),
+        );
+    for (start_node_id, end_node_id) in element_ends {
+        let start = match index.get(start_node_id) {
+            Some(x) => *x,
+            None => return Err(format!("Failed to find node id = {}", start_node_id)),
+        };
+        let end = match index.get(end_node_id) {
+            Some(x) => *x,
+            None => return Err(format!("Failed to find node id = {}", end_node_id)),
+        };
+        element_nodes.push(vec![start, end]);
+    }
+
+    let surfaces = data
+        .membranes
+        .values()
+        .map(|x| ("membrane", &x.id, &x.node_ids))
+        .chain(data.shells.values().map(|x| ("shell", &x.id, &x.node_ids)));
+    for (kind, id, node_ids) in surfaces {
+        let nodes: Option<Vec<usize>> = node_ids.iter().map(|x| index.get(x).cloned()).collect();
+        match nodes {
+            Some(x) => element_nodes.push(x),
+            None => return Err(format!("Failed to find the nodes of {} {}", kind, id)),
+        }
+    }
+
+    // A constraint couples each slave to its masters once the slave is condensed out
+    for (id, constraint) in data.constraints.iter() {
+        let pairs: Vec<(&String, &String)> = match constraint {
+            Constraint::RigidLink(x) => vec![(&x.slave_node_id, &x.master_node_id)],
+            Constraint::Diaphragm(x) => x
+                .slave_node_ids
+                .iter()
+                .map(|y| (y, &x.master_node_id))
+                .collect(),
+            Constraint::MultiPoint(x) => x
+                .masters
+                .iter()
+                .map(|y| (&x.slave_node_id, &y.node_id))
+                .collect(),
+        };
+        for (slave, master) in pairs {
+            match (index.get(slave), index.get(master)) {
+                (Some(a), Some(b)) if a != b => element_nodes.push(vec![*a, *b]),
+                (Some(_), Some(_)) => (),
+                _ => return Err(format!("Failed to find the nodes of constraint {}", id)),
+            }
+        }
+    }
+
+    let node_dofs: Vec<Vec<usize>> = node_ids
+        .iter()
+        .map(|id| data.nodes[*id].degrees_of_freedom.iter().cloned().collect())
+        .collect();
+
+    let adjacency = node_adjacency(node_ids.len(), &element_nodes);
+    let ordering = match method {
+        RenumberingMethod::ReverseCuthillMckee => reverse_cuthill_mckee(&adjacency),
+        RenumberingMethod::MinimumDegree => minimum_degree(&adjacency),
+    };
+
+    let mut dof_map = HashMap::<usize, usize>::new();
+    for (position, node) in ordering.iter().enumerate() {
+        for (j, dof) in node_dofs[*node].iter().enumerate() {
+            dof_map.insert(*dof, 6 * position + j);
+        }
+    }
+
+    let element_dofs_before = element_degrees_of_freedom(&element_nodes, &node_dofs);
+    let element_dofs_after: Vec<Vec<usize>> = element_dofs_before
+        .iter()
+        .map(|dofs| dofs.iter().map(|dof| dof_map[dof]).collect())
+        .collect();
+
+    Ok(RenumberingReport {
+        bandwidth_before: bandwidth(&element_dofs_before),
+        bandwidth_after: bandwidth(&element_dofs_after),
+        profile_before: profile(&element_dofs_before),
+        profile_after: profile(&element_dofs_after),
+        dof_map,
+    })
+}
+
+/**
+ * Returns the ordering of graph vertices produced by the reverse Cuthill–McKee algorithm.
+ *
+ * Each connected component is traversed breadth first from a pseudo-peripheral vertex,
+ * visiting neighbours in order of increasing degree. The concatenated traversal is reversed.
+ *
+ * # Arguments
+ *
+ * `adjacency` - the neighbours of each vertex
+ */
+pub fn reverse_cuthill_mckee(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut visited = vec![false; n];
+    let mut ordering = Vec::<usize>::with_capacity(n);
+
+    loop {
+        let seed = (0..n)
+            .filter(|v| !visited[*v])
+            .min_by_key(|v| (adjacency[*v].len(), *v));
+        let seed = match seed {
+            Some(x) => x,
+            None => break,
+        };
+        let start = pseudo_peripheral_vertex(adjacency, seed);
+
+        let mut queue = VecDeque::<usize>::new();
+        visited[start] = true;
+        queue.push_back(start);
+        while let Some(v) = queue.pop_front() {
+            ordering.push(v);
+            let mut neighbours: Vec<usize> = adjacency[v]
+                .iter()
+                .cloned()
+                .filter(|w| !visited[*w])
+                .collect();
+            neighbours.sort_by_key(|w| (adjacency[*w].len(), *w));
+            for w in neighbours {
+                visited[w] = true;
+                queue.push_back(w);
+            }
+        }
+    }
+
+    ordering.reverse();
+    ordering
+}
+
+/**
+ * Returns the ordering of graph vertices produced by a minimum degree elimination.
+ *
+ * Vertices are eliminated one at a time, always choosing the remaining vertex with the fewest
+ * neighbours in the elimination graph. Eliminating a vertex joins its neighbours into a clique,
+ * which models the fill-in created by the factorization. Degrees are exact rather than the
+ * approximate degrees of AMD, and are only refreshed for the neighbours of the eliminated
+ * vertex. Ties are broken by the original numbering.
+ *
+ * # Arguments
+ *
+ * `adjacency` - the neighbours of each vertex
+ */
+pub fn minimum_degree(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut graph: Vec<HashSet<usize>> = adjacency
+        .iter()
+        .enumerate()
+        .map(|(v, neighbours)| neighbours.iter().cloned().filter(|w| *w != v).collect())
+        .collect();
+    let mut queue: BTreeSet<(usize, usize)> = graph
+        .iter()
+        .enumerate()
+        .map(|(v, neighbours)| (neighbours.len(), v))
+        .collect();
+    let mut ordering = Vec::<usize>::with_capacity(n);
+
+    while let Some(&(degree, pivot)) = queue.iter().next() {
+        queue.remove(&(degree, pivot));
+        ordering.push(pivot);
+
+        let neighbours: Vec<usize> = graph[pivot].drain().collect();
+        for v in neighbours.iter() {
+            queue.remove(&(graph[*v].len(), *v));
+            graph[*v].remove(&pivot);
+            for w in neighbours.iter() {
+                if v != w {
+                    graph[*v].insert(*w);
+                }
+            }
+            queue.insert((graph[*v].len(), *v));
+        }
+    }
+
+    ordering
+}
+
+/**
+ * Returns the half-bandwidth of the matrix assembled from the given elements.
+ *
+ * # Arguments
+ *
+ * `element_dofs` - the degrees of freedom coupled by each element
+ */
+pub fn bandwidth(element_dofs: &[Vec<usize>]) -> usize {
+    element_dofs
+        .iter()
+        .filter(|dofs| !dofs.is_empty())
+        .map(|dofs| dofs.iter().max().unwrap() - dofs.iter().min().unwrap())
+        .max()
+        .unwrap_or(0)
+}
+
+/**
+ * Returns the profile (envelope size) of the lower triangle of the matrix assembled from the
+ * given elements.
+ *
+ * The profile is the sum over all rows of the distance from the first non-zero entry
+ * to the diagonal.
+ *
+ * # Arguments
+ *
+ * `element_dofs` - the degrees of freedom coupled by each element
+ */
+pub fn profile(element_dofs: &[Vec<usize>]) -> usize {
+    let mut first_coupled = HashMap::<usize, usize>::new();
+    for dofs in element_dofs.iter().filter(|dofs| !dofs.is_empty()) {
+        let lowest = *dofs.iter().min().unwrap();
+        for dof in dofs.iter() {
+            let entry = first_coupled.entry(*dof).or_insert(*dof);
+            if lowest < *entry {
+                *entry = lowest;
+            }
+        }
+    }
+    first_coupled.iter().map(|(dof, first)| dof - first).sum()
+}
+
+fn node_adjacency(node_count: usize, element_nodes: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); node_count];
+    for nodes in element_nodes.iter() {
+        for a in nodes.iter() {
+            for b in nodes.iter() {
+                if a != b {
+                    adjacency[*a].insert(*b);
+                }
+            }
+        }
+    }
+    adjacency
+        .into_iter()
+        .map(|x| {
+            let mut neighbours: Vec<usize> = x.into_iter().collect();
+            neighbours.sort_unstable();
+            neighbours
+        })
+        .collect()
+}
+
+fn element_degrees_of_freedom(
+    element_nodes: &[Vec<usize>],
+    node_dofs: &[Vec<usize>],
+) -> Vec<Vec<usize>> {
+    element_nodes
+        .iter()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .flat_map(|node| node_dofs[*node].iter().cloned())
+                .collect()
+        })
+        .collect()
+}
+
+/// Finds a vertex of (nearly) maximum eccentricity in the component containing `seed`
+/// using the George–Liu iteration over rooted level structures.
+fn pseudo_peripheral_vertex(adjacency: &[Vec<usize>], seed: usize) -> usize {
+    let mut root = seed;
+    let mut levels = level_structure(adjacency, root);
+    loop {
+        let candidate = *levels
+            .last()
+            .unwrap()
+            .iter()
+            .min_by_key(|v| (adjacency[**v].len(), **v))
+            .unwrap();
+        let candidate_levels = level_structure(adjacency, candidate);
+        if candidate_levels.len() <= levels.len() {
+            return root;
+        }
+        root = candidate;
+        levels = candidate_levels;
+    }
+}
+
+fn level_structure(adjacency: &[Vec<usize>], root: usize) -> Vec<Vec<usize>> {
+    let mut visited = HashSet::<usize>::new();
+    visited.insert(root);
+    let mut levels = vec![vec![root]];
+    loop {
+        let mut next = Vec::<usize>::new();
+        for v in levels.last().unwrap().iter() {
+            for w in adjacency[*v].iter() {
+                if visited.insert(*w) {
+                    next.push(*w);
+                }
+            }
+        }
+        if next.is_empty() {
+            return levels;
+        }
+        levels.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chain of six nodes, 0 - 3 - 5 - 1 - 4 - 2, each carrying six degrees of freedom
+    fn scrambled_chain() -> Vec<Vec<usize>> {
+        let links = [(0, 3), (3, 5), (5, 1), (1, 4), (4, 2)];
+        let mut adjacency = vec![Vec::<usize>::new(); 6];
+        for (a, b) in links.iter() {
+            adjacency[*a].push(*b);
+            adjacency[*b].push(*a);
+        }
+        adjacency
+    }
+
+    fn chain_element_dofs(ordering: &[usize]) -> Vec<Vec<usize>> {
+        let mut position = vec![0; ordering.len()];
+        for (i, node) in ordering.iter().enumerate() {
+            position[*node] = i;
+        }
+        let links = [(0, 3), (3, 5), (5, 1), (1, 4), (4, 2)];
+        links
+            .iter()
+            .map(|(a, b)| {
+                (6 * position[*a]..6 * position[*a] + 6)
+                    .chain(6 * position[*b]..6 * position[*b] + 6)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reverse_cuthill_mckee_reduces_chain_to_tridiagonal_blocks() {
+        let adjacency = scrambled_chain();
+        let identity: Vec<usize> = (0..6).collect();
+        let ordering = reverse_cuthill_mckee(&adjacency);
+
+        let mut sorted = ordering.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, identity);
+
+        assert_eq!(bandwidth(&chain_element_dofs(&identity)), 29);
+        assert_eq!(bandwidth(&chain_element_dofs(&ordering)), 11);
+        assert!(profile(&chain_element_dofs(&ordering)) < profile(&chain_element_dofs(&identity)));
+    }
+
+    #[test]
+    fn minimum_degree_eliminates_chain_from_the_ends() {
+        let ordering = minimum_degree(&scrambled_chain());
+
+        assert_eq!(ordering.len(), 6);
+        // The chain ends have a single neighbour, so one of them is always eliminated first
+        assert!(ordering[0] == 0 || ordering[0] == 2);
+    }
+}
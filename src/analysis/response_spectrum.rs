@@ -0,0 +1,431 @@
+use crate::analysis::linear_static::{collect_results, LinearStaticResults};
+use crate::analysis::modal::*;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::{DMatrix, Vector3, Vector6, VectorN, U12};
+use std::collections::HashMap;
+
+/// Spectral acceleration as a function of the natural period.
+#[derive(Clone, Debug)]
+pub enum DesignSpectrum {
+    /// Pairs of period and spectral acceleration in ascending order of period, interpolated
+    /// linearly and held constant beyond the first and last periods
+    UserDefined(Vec<(f64, f64)>),
+    /// Design response spectrum of ASCE 7-16 Section 11.4.6, in units of g
+    Asce7 {
+        /// Design spectral acceleration at short periods, `SDS`
+        sds: f64,
+        /// Design spectral acceleration at a period of one second, `SD1`
+        sd1: f64,
+        /// Long-period transition period `TL` in seconds
+        long_period: f64,
+    },
+}
+
+impl DesignSpectrum {
+    /// Returns the spectral acceleration at the given period
+    pub fn acceleration(&self, period: f64) -> f64 {
+        match self {
+            DesignSpectrum::UserDefined(points) => interpolate(points, period),
+            DesignSpectrum::Asce7 {
+                sds,
+                sd1,
+                long_period,
+            } => {
+                let short_period = sd1 / sds;
+                let initial_period = 0.2 * short_period;
+                if period < initial_period {
+                    sds * (0.4 + 0.6 * period / initial_period)
+                } else if period <= short_period {
+                    *sds
+                } else if period <= *long_period {
+                    sd1 / period
+                } else {
+                    sd1 * long_period / (period * period)
+                }
+            }
+        }
+    }
+}
+
+/// How the peak responses of the individual modes are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModalCombination {
+    /// Square root of the sum of the squares, for well separated frequencies
+    Srss,
+    /// Complete quadratic combination with the same damping ratio in every mode
+    Cqc { damping_ratio: f64 },
+}
+
+/// How the combined responses to excitation along each world direction are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirectionalCombination {
+    /// Square root of the sum of the squares of the directional responses
+    Srss,
+    /// Largest of 100% of one direction plus 30% of each of the others
+    OneHundredThirty,
+}
+
+#[derive(Clone, Debug)]
+pub struct ResponseSpectrumSettings {
+    pub spectrum: DesignSpectrum,
+    /// Factors converting the spectral acceleration to model units for excitation along the
+    /// world `[X, Y, Z]` axes, e.g. `g Ie / R`; zero omits the direction
+    pub scale_factors: Vector3<f64>,
+    pub modal_combination: ModalCombination,
+    pub directional_combination: DirectionalCombination,
+    pub modal: ModalSettings,
+}
+
+/// Peak responses, which are magnitudes without sign.
+pub struct SpectrumResponse {
+    /// World displacements `[Ux, Uy, Uz, Rx, Ry, Rz]` of every node
+    pub displacements: HashMap<String, Vector6<f64>>,
+    /// World reactions `[Fx, Fy, Fz, Mx, My, Mz]` at every supported node
+    pub reactions: HashMap<String, Vector6<f64>>,
+    /// World forces `[Fx, Fy, Fz, Mx, My, Mz]` exerted on every spring-supported node
+    pub spring_forces: HashMap<String, Vector6<f64>>,
+    /// Local end forces of every frame, acting on the start node then the end node
+    pub frame_end_forces: HashMap<String, VectorN<f64, U12>>,
+}
+
+pub struct ResponseSpectrumResults {
+    /// Modes used in the analysis. Design codes typically require enough modes for the
+    /// cumulative effective mass ratios to reach 90% in each direction.
+    pub modal: ModalResults,
+    /// Responses to excitation along each world axis `[X, Y, Z]` after modal combination, or
+    /// `None` for directions with a zero scale factor
+    pub directional: Vec<Option<SpectrumResponse>>,
+    /// Responses after directional combination
+    pub combined: SpectrumResponse,
+}
+
+/**
+ * Runs a response spectrum analysis using the modes of a modal analysis.
+ *
+ * For excitation along each world axis `d`, the peak displacement of mode `n` is
+ * `φn Γnd Sa(Tn) / ωn²` where `Γnd` is the participation factor. The displacements, reactions,
+ * spring forces and frame end forces of every mode are combined by SRSS or CQC, and the results of the three
+ * directions are then combined by SRSS or the 100/30 rule.
+ *
+ * # Arguments
+ *
+ * `settings` - spectrum, scale factors, combination rules and modal settings
+ */
+pub fn run_response_spectrum_analysis(
+    settings: &ResponseSpectrumSettings,
+) -> Result<ResponseSpectrumResults, String> {
+    let modal = run_modal_analysis(&settings.modal)?;
+    if modal.modes.is_empty() {
+        return Err("The modal analysis found no modes".to_string());
+    }
+
+    let world = ANALYSIS_DATA.read().unwrap().world_stiffness.clone();
+    let (nodes, layout) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let mut layout = ResponseLayout {
+            node_ids: data.nodes.keys().cloned().collect(),
+            support_ids: data
+                .supports
+                .keys()
+                .filter(|x| data.nodes.contains_key(*x))
+                .cloned()
+                .collect(),
+            spring_ids: data
+                .supports
+                .iter()
+                .filter(|(id, x)| data.nodes.contains_key(*id) && x.has_springs())
+                .map(|(id, _)| id.clone())
+                .collect(),
+            frame_ids: data.frames.keys().cloned().collect(),
+        };
+        layout.node_ids.sort();
+        layout.support_ids.sort();
+        layout.spring_ids.sort();
+        layout.frame_ids.sort();
+        (data.nodes.clone(), layout)
+    };
+    let dof_count = nodes
+        .values()
+        .flat_map(|x| x.degrees_of_freedom.iter())
+        .max()
+        .map_or(0, |x| x + 1);
+    let no_forces = vec![0.; dof_count];
+
+    // Responses of each mode to a unit modal displacement
+    let unit_responses: Vec<Vec<f64>> = modal
+        .modes
+        .iter()
+        .map(|mode| {
+            let mut deflections = vec![0.; dof_count];
+            for node in nodes.values() {
+                if let Some(shape) = mode.shape.get(&node.id) {
+                    let shape = node.from_world(shape);
+                    for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+                        deflections[*dof] = shape[i];
+                    }
+                }
+            }
+            layout.flatten(&collect_results(&world, &no_forces, &deflections))
+        })
+        .collect();
+
+    let frequencies: Vec<f64> = modal.modes.iter().map(|x| x.angular_frequency).collect();
+    let correlation = modal_correlation(&frequencies, settings.modal_combination);
+
+    let mut directional = Vec::<Option<SpectrumResponse>>::new();
+    let mut directional_values = Vec::<Vec<f64>>::new();
+    for axis in 0..3 {
+        if settings.scale_factors[axis] == 0. {
+            directional.push(None);
+            continue;
+        }
+        let modal_displacements: Vec<f64> = modal
+            .modes
+            .iter()
+            .map(|mode| {
+                let acceleration =
+                    settings.scale_factors[axis] * settings.spectrum.acceleration(mode.period);
+                mode.participation_factors[axis] * acceleration / mode.angular_frequency.powi(2)
+            })
+            .collect();
+
+        let values: Vec<f64> = (0..layout.len())
+            .map(|i| {
+                let peaks: Vec<f64> = unit_responses
+                    .iter()
+                    .zip(modal_displacements.iter())
+                    .map(|(response, q)| response[i] * q)
+                    .collect();
+                combine_modal_responses(&peaks, &correlation)
+            })
+            .collect();
+        directional.push(Some(layout.unflatten(&values)));
+        directional_values.push(values);
+    }
+
+    let combined: Vec<f64> = (0..layout.len())
+        .map(|i| {
+            let peaks: Vec<f64> = directional_values.iter().map(|x| x[i]).collect();
+            combine_directional_responses(&peaks, settings.directional_combination)
+        })
+        .collect();
+
+    Ok(ResponseSpectrumResults {
+        combined: layout.unflatten(&combined),
+        directional,
+        modal,
+    })
+}
+
+/**
+ * Returns the correlation coefficients `ρij` between the modes.
+ *
+ * SRSS treats the modes as uncorrelated. CQC uses the coefficient of Der Kiureghian for equal
+ * modal damping `ζ`, `ρij = 8 ζ² (1 + r) r^1.5 / ((1 − r²)² + 4 ζ² r (1 + r)²)` with
+ * `r = ωi / ωj`.
+ *
+ * # Arguments
+ *
+ * `angular_frequencies` - circular frequency of every mode
+ * `combination` - the modal combination rule
+ */
+pub fn modal_correlation(
+    angular_frequencies: &[f64],
+    combination: ModalCombination,
+) -> DMatrix<f64> {
+    let n = angular_frequencies.len();
+    match combination {
+        ModalCombination::Srss => DMatrix::identity(n, n),
+        ModalCombination::Cqc { damping_ratio } => {
+            let z2 = damping_ratio * damping_ratio;
+            DMatrix::from_fn(n, n, |i, j| {
+                let r = angular_frequencies[i] / angular_frequencies[j];
+                8. * z2 * (1. + r) * r.powf(1.5)
+                    / ((1. - r * r).powi(2) + 4. * z2 * r * (1. + r).powi(2))
+            })
+        }
+    }
+}
+
+/// Returns `√(Σ Σ ρij Ri Rj)` for the peak modal responses `R`
+pub fn combine_modal_responses(peaks: &[f64], correlation: &DMatrix<f64>) -> f64 {
+    let mut sum = 0.;
+    for (i, a) in peaks.iter().enumerate() {
+        for (j, b) in peaks.iter().enumerate() {
+            sum += correlation[(i, j)] * a * b;
+        }
+    }
+    sum.max(0.).sqrt()
+}
+
+/// Combines the peak responses to excitation along each direction
+pub fn combine_directional_responses(peaks: &[f64], combination: DirectionalCombination) -> f64 {
+    match combination {
+        DirectionalCombination::Srss => peaks.iter().map(|x| x * x).sum::<f64>().sqrt(),
+        DirectionalCombination::OneHundredThirty => {
+            let total: f64 = peaks.iter().map(|x| x.abs()).sum();
+            peaks
+                .iter()
+                .map(|x| x.abs() + 0.3 * (total - x.abs()))
+                .fold(0., f64::max)
+        }
+    }
+}
+
+fn interpolate(points: &[(f64, f64)], period: f64) -> f64 {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return 0.,
+    };
+    if period <= first.0 {
+        return first.1;
+    }
+    if period >= last.0 {
+        return last.1;
+    }
+    for pair in points.windows(2) {
+        let ((t0, a0), (t1, a1)) = (pair[0], pair[1]);
+        if period <= t1 {
+            if t1 == t0 {
+                return a1;
+            }
+            return a0 + (a1 - a0) * (period - t0) / (t1 - t0);
+        }
+    }
+    last.1
+}
+
+/// Orders every response component in a single vector so all can be combined alike
+struct ResponseLayout {
+    node_ids: Vec<String>,
+    support_ids: Vec<String>,
+    spring_ids: Vec<String>,
+    frame_ids: Vec<String>,
+}
+
+impl ResponseLayout {
+    fn len(&self) -> usize {
+        6 * (self.node_ids.len() + self.support_ids.len() + self.spring_ids.len())
+            + 12 * self.frame_ids.len()
+    }
+    fn flatten(&self, results: &LinearStaticResults) -> Vec<f64> {
+        let mut values = Vec::<f64>::with_capacity(self.len());
+        for id in self.node_ids.iter() {
+            values.extend(results.displacements[id].iter());
+        }
+        for id in self.support_ids.iter() {
+            values.extend(results.reactions[id].iter());
+        }
+        for id in self.spring_ids.iter() {
+            values.extend(results.spring_forces[id].iter());
+        }
+        for id in self.frame_ids.iter() {
+            match results.frame_end_forces.get(id) {
+                Some(x) => values.extend(x.iter()),
+                None => values.extend([0.; 12].iter()),
+            }
+        }
+        values
+    }
+    fn unflatten(&self, values: &[f64]) -> SpectrumResponse {
+        let mut chunks = values.chunks(6);
+        let displacements = self
+            .node_ids
+            .iter()
+            .map(|id| {
+                (
+                    id.clone(),
+                    Vector6::from_column_slice(chunks.next().unwrap()),
+                )
+            })
+            .collect();
+        let reactions = self
+            .support_ids
+            .iter()
+            .map(|id| {
+                (
+                    id.clone(),
+                    Vector6::from_column_slice(chunks.next().unwrap()),
+                )
+            })
+            .collect();
+        let spring_forces = self
+            .spring_ids
+            .iter()
+            .map(|id| {
+                (
+                    id.clone(),
+                    Vector6::from_column_slice(chunks.next().unwrap()),
+                )
+            })
+            .collect();
+        let offset = 6 * (self.node_ids.len() + self.support_ids.len() + self.spring_ids.len());
+        let frame_end_forces = self
+            .frame_ids
+            .iter()
+            .zip(values[offset..].chunks(12))
+            .map(|(id, x)| (id.clone(), VectorN::<f64, U12>::from_column_slice(x)))
+            .collect();
+        SpectrumResponse {
+            displacements,
+            reactions,
+            spring_forces,
+            frame_end_forces,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asce7_spectrum_branches() {
+        let spectrum = DesignSpectrum::Asce7 {
+            sds: 1.,
+            sd1: 0.6,
+            long_period: 8.,
+        };
+        // T0 = 0.12 s and TS = 0.6 s
+        assert_relative_eq!(spectrum.acceleration(0.), 0.4);
+        assert_relative_eq!(spectrum.acceleration(0.06), 0.7);
+        assert_relative_eq!(spectrum.acceleration(0.3), 1.);
+        assert_relative_eq!(spectrum.acceleration(1.2), 0.5);
+        assert_relative_eq!(spectrum.acceleration(10.), 0.6 * 8. / 100.);
+
+        let user = DesignSpectrum::UserDefined(vec![(0., 0.5), (1., 1.5), (2., 0.5)]);
+        assert_relative_eq!(user.acceleration(0.5), 1.);
+        assert_relative_eq!(user.acceleration(1.5), 1.);
+        assert_relative_eq!(user.acceleration(3.), 0.5);
+    }
+
+    #[test]
+    fn cqc_approaches_absolute_sum_for_equal_frequencies() {
+        let cqc = ModalCombination::Cqc {
+            damping_ratio: 0.05,
+        };
+
+        let close = modal_correlation(&[10., 10.], cqc);
+        assert_relative_eq!(close[(0, 1)], 1., max_relative = 1e-12);
+        assert_relative_eq!(combine_modal_responses(&[3., 4.], &close), 7.);
+
+        // Well separated modes are nearly uncorrelated, so CQC approaches SRSS
+        let separated = modal_correlation(&[1., 10.], cqc);
+        assert!(separated[(0, 1)] < 1e-3);
+        let srss = modal_correlation(&[1., 10.], ModalCombination::Srss);
+        assert_relative_eq!(combine_modal_responses(&[3., 4.], &srss), 5.);
+    }
+
+    #[test]
+    fn one_hundred_thirty_takes_the_governing_direction() {
+        let peaks = [10., 2., 0.];
+        assert_relative_eq!(
+            combine_directional_responses(&peaks, DirectionalCombination::OneHundredThirty),
+            10.6
+        );
+        assert_relative_eq!(
+            combine_directional_responses(&peaks, DirectionalCombination::Srss),
+            104f64.sqrt()
+        );
+    }
+}
@@ -0,0 +1,501 @@
+use crate::analysis::linear_static::{assemble_checked_world_stiffness, frame_local_displacements};
+use crate::analysis::modal::{assemble_world_mass_matrix, MassFormulation};
+use crate::analysis::*;
+use crate::elements::frame::frame_end_forces_at_faces;
+use crate::models::frame::FrameElement;
+use crate::{ANALYSIS_DATA, ELEMENT_DATA};
+use na::{DMatrix, DVector, MatrixN, Vector3, Vector6, VectorN, U12};
+use std::collections::HashMap;
+
+/// Direct integration scheme for the equations of motion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntegrationScheme {
+    /// Newmark-β, which is unconditionally stable for `2β ≥ γ ≥ 1/2`
+    Newmark { gamma: f64, beta: f64 },
+    /// Hilber-Hughes-Taylor α with `-1/3 ≤ α ≤ 0`, which damps spurious high frequencies.
+    /// Newmark parameters follow as `γ = (1 − 2α) / 2` and `β = (1 − α)² / 4`.
+    HilberHughesTaylor { alpha: f64 },
+}
+
+impl IntegrationScheme {
+    /// Newmark-β with constant average acceleration, `γ = 1/2` and `β = 1/4`
+    pub fn average_acceleration() -> IntegrationScheme {
+        IntegrationScheme::Newmark {
+            gamma: 0.5,
+            beta: 0.25,
+        }
+    }
+
+    /// Returns `(α, γ, β)`
+    fn parameters(self) -> (f64, f64, f64) {
+        match self {
+            IntegrationScheme::Newmark { gamma, beta } => (0., gamma, beta),
+            IntegrationScheme::HilberHughesTaylor { alpha } => {
+                (alpha, (1. - 2. * alpha) / 2., (1. - alpha).powi(2) / 4.)
+            }
+        }
+    }
+}
+
+/// Damping matrix `C = a0 M + a1 k`.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct RayleighDamping {
+    pub mass_coefficient: f64,
+    pub stiffness_coefficient: f64,
+}
+
+impl RayleighDamping {
+    /// Returns the coefficients giving damping ratio `ζi` at circular frequency `ωi` and `ζj`
+    /// at `ωj`, from `ζ = a0 / 2ω + a1 ω / 2`
+    pub fn from_damping_ratios(
+        omega_i: f64,
+        zeta_i: f64,
+        omega_j: f64,
+        zeta_j: f64,
+    ) -> RayleighDamping {
+        let determinant = omega_j / omega_i - omega_i / omega_j;
+        RayleighDamping {
+            mass_coefficient: 2. * (zeta_i * omega_j - zeta_j * omega_i) / determinant,
+            stiffness_coefficient: 2. * (zeta_j / omega_i - zeta_i / omega_j) / determinant,
+        }
+    }
+}
+
+/// Values at increasing times, interpolated linearly and zero outside of the record.
+#[derive(Clone, Debug, Default)]
+pub struct TimeSeries {
+    pub points: Vec<(f64, f64)>,
+}
+
+impl TimeSeries {
+    /**
+     * Parses a record with one value per line, or a time and value per line, separated by
+     * whitespace or commas. Blank lines and lines starting with `#` are ignored.
+     *
+     * # Arguments
+     *
+     * `text` - the record
+     * `time_step` - spacing of values in a single column record, starting at time zero
+     */
+    pub fn parse(text: &str, time_step: Option<f64>) -> Result<TimeSeries, String> {
+        let mut points = Vec::<(f64, f64)>::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|e| format!("Failed to parse line {}: {}", i + 1, e))?;
+            match (values.as_slice(), time_step) {
+                ([time, value], _) => points.push((*time, *value)),
+                ([value], Some(step)) => points.push((step * points.len() as f64, *value)),
+                ([_], None) => {
+                    return Err(format!(
+                        "Line {} has a single value but no time step was given",
+                        i + 1
+                    ))
+                }
+                _ => {
+                    return Err(format!(
+                        "Line {} has {} values, expected one or two",
+                        i + 1,
+                        values.len()
+                    ))
+                }
+            }
+        }
+
+        if points.windows(2).any(|x| x[1].0 <= x[0].0) {
+            return Err("Times of the record must increase".to_string());
+        }
+        Ok(TimeSeries { points })
+    }
+
+    /// Reads and parses a record file, see `parse`
+    pub fn read(path: &str, time_step: Option<f64>) -> Result<TimeSeries, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read record {}: {}", path, e))?;
+        TimeSeries::parse(&text, time_step)
+    }
+
+    /// Returns the value at the given time
+    pub fn value_at(&self, time: f64) -> f64 {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return 0.,
+        };
+        if time < first.0 || time > last.0 {
+            return 0.;
+        }
+        let i = match self.points.iter().position(|x| x.0 > time) {
+            Some(x) => x,
+            None => return last.1,
+        };
+        let ((t0, v0), (t1, v1)) = (self.points[i - 1], self.points[i]);
+        v0 + (v1 - v0) * (time - t0) / (t1 - t0)
+    }
+}
+
+/// A dynamic load varying with time.
+#[derive(Clone, Debug)]
+pub enum DynamicLoad {
+    /// World forces and moments `[Fx, Fy, Fz, Mx, My, Mz]` at the node, multiplied by the series
+    NodalForce {
+        node_id: String,
+        force: Vector6<f64>,
+        series: TimeSeries,
+    },
+    /// Acceleration of every support along the world direction, multiplied by the series
+    BaseAcceleration {
+        direction: Vector3<f64>,
+        series: TimeSeries,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TimeHistorySettings {
+    pub time_step: f64,
+    pub step_count: usize,
+    pub scheme: IntegrationScheme,
+    pub damping: RayleighDamping,
+    pub mass_formulation: MassFormulation,
+    pub loads: Vec<DynamicLoad>,
+    /// Nodes whose histories are recorded
+    pub output_node_ids: Vec<String>,
+    /// Frames whose end force histories are recorded
+    pub output_frame_ids: Vec<String>,
+}
+
+/// World histories `[Ux, Uy, Uz, Rx, Ry, Rz]` of a node, with one entry per output time.
+#[derive(Default)]
+pub struct NodeHistory {
+    pub displacements: Vec<Vector6<f64>>,
+    pub velocities: Vec<Vector6<f64>>,
+    pub accelerations: Vec<Vector6<f64>>,
+}
+
+pub struct TimeHistoryResults {
+    /// Output times, starting at zero
+    pub times: Vec<f64>,
+    /// Histories of the requested nodes. Under base acceleration these are relative to the
+    /// supports; add the ground motion for absolute values.
+    pub nodes: HashMap<String, NodeHistory>,
+    /// Local end force histories of the requested frames
+    pub frame_end_forces: HashMap<String, Vec<VectorN<f64, U12>>>,
+}
+
+/// Displacements, velocities and accelerations of the free degrees of freedom at every step.
+pub struct IntegrationHistory {
+    pub displacements: Vec<DVector<f64>>,
+    pub velocities: Vec<DVector<f64>>,
+    pub accelerations: Vec<DVector<f64>>,
+}
+
+/**
+ * Runs a linear time history analysis by direct integration of `M a + C v + k u = F(t)`,
+ * starting from rest.
+ *
+ * The model is validated and the world stiffness is checked as for a linear static analysis.
+ * Base accelerations `ag` are applied as the effective forces `-M r ag` where `r` is a rigid
+ * translation along the direction of the motion.
+ *
+ * # Arguments
+ *
+ * `settings` - time step, loads, integration scheme, damping and requested output
+ */
+pub fn run_time_history_analysis(
+    settings: &TimeHistorySettings,
+) -> Result<TimeHistoryResults, String> {
+    if settings.time_step.is_nan() || settings.time_step <= 0. {
+        return Err(format!(
+            "Time step must be positive, found {}",
+            settings.time_step
+        ));
+    }
+
+    let (world, _) = assemble_checked_world_stiffness()?;
+    let mass = assemble_world_mass_matrix(settings.mass_formulation)?;
+    let partition = partition_degrees_of_freedom();
+    let stiffness = reduce_to_free_dofs_dense(&world, &partition);
+    let mass = reduce_to_free_dofs_dense(&mass, &partition);
+    let damping = &mass * settings.damping.mass_coefficient
+        + &stiffness * settings.damping.stiffness_coefficient;
+
+    let (nodes, frames) = {
+        let data = ELEMENT_DATA.read().unwrap();
+        let frames = settings
+            .output_frame_ids
+            .iter()
+            .map(|id| match data.frames.get(id) {
+                Some(x) => Ok(x.clone()),
+                None => Err(format!("Failed to locate frame id = {}", id)),
+            })
+            .collect::<Result<Vec<FrameElement>, String>>()?;
+        (data.nodes.clone(), frames)
+    };
+    for id in settings.output_node_ids.iter() {
+        if !nodes.contains_key(id) {
+            return Err(format!("Failed to locate node id = {}", id));
+        }
+    }
+
+    // Spatial distribution of every load over the free degrees of freedom
+    let n = partition.free.len();
+    let mut patterns = Vec::<(DVector<f64>, &TimeSeries)>::new();
+    for load in settings.loads.iter() {
+        match load {
+            DynamicLoad::NodalForce {
+                node_id,
+                force,
+                series,
+            } => {
+                let node = match nodes.get(node_id) {
+                    Some(x) => x,
+                    None => return Err(format!("Failed to locate node id = {}", node_id)),
+                };
+                let force = node.from_world(force);
+                let mut pattern = DVector::<f64>::zeros(n);
+                for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+                    for (row, c) in partition.free_components(*dof).iter() {
+                        pattern[*row] += c * force[i];
+                    }
+                }
+                patterns.push((pattern, series));
+            }
+            DynamicLoad::BaseAcceleration { direction, series } => {
+                let mut rigid = DVector::<f64>::zeros(n);
+                let translation = Vector6::new(direction.x, direction.y, direction.z, 0., 0., 0.);
+                for node in nodes.values() {
+                    let nodal = node.from_world(&translation);
+                    for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+                        if let Some(row) = partition.free_index.get(dof) {
+                            rigid[*row] = nodal[i];
+                        }
+                    }
+                }
+                patterns.push((-(&mass * rigid), series));
+            }
+        }
+    }
+
+    let forces: Vec<DVector<f64>> = (0..=settings.step_count)
+        .map(|step| {
+            let time = step as f64 * settings.time_step;
+            patterns
+                .iter()
+                .fold(DVector::<f64>::zeros(n), |acc, (pattern, series)| {
+                    acc + pattern * series.value_at(time)
+                })
+        })
+        .collect();
+
+    let history = integrate_equations_of_motion(
+        &stiffness,
+        &mass,
+        &damping,
+        &forces,
+        settings.time_step,
+        settings.scheme,
+    )?;
+
+    let expand = |free: &DVector<f64>| partition.expand(free.as_slice());
+    let local_stiffnesses: HashMap<String, MatrixN<f64, U12>> = ANALYSIS_DATA
+        .read()
+        .unwrap()
+        .frame_stiffnesses
+        .iter()
+        .map(|(id, x)| (id.clone(), x.local))
+        .collect();
+
+    let mut node_histories: HashMap<String, NodeHistory> = settings
+        .output_node_ids
+        .iter()
+        .map(|id| (id.clone(), NodeHistory::default()))
+        .collect();
+    let mut frame_end_forces: HashMap<String, Vec<VectorN<f64, U12>>> =
+        frames.iter().map(|x| (x.id.clone(), Vec::new())).collect();
+
+    for step in 0..history.displacements.len() {
+        let displacements = expand(&history.displacements[step]);
+        let velocities = expand(&history.velocities[step]);
+        let accelerations = expand(&history.accelerations[step]);
+        let at_node = |values: &[f64], id: &str| {
+            let node = &nodes[id];
+            node.to_world(&Vector6::from_iterator(
+                node.degrees_of_freedom.iter().map(|dof| values[*dof]),
+            ))
+        };
+
+        for (id, node_history) in node_histories.iter_mut() {
+            node_history.displacements.push(at_node(&displacements, id));
+            node_history.velocities.push(at_node(&velocities, id));
+            node_history.accelerations.push(at_node(&accelerations, id));
+        }
+        for frame in frames.iter() {
+            let (start, end) = match (
+                nodes.get(&frame.start_node_id),
+                nodes.get(&frame.end_node_id),
+            ) {
+                (Some(x), Some(y)) => (x, y),
+                _ => continue,
+            };
+            if let (Some(stiffness), Some(forces)) = (
+                local_stiffnesses.get(&frame.id),
+                frame_end_forces.get_mut(&frame.id),
+            ) {
+                let node_forces =
+                    stiffness * frame_local_displacements(frame, start, end, &displacements);
+                forces.push(frame_end_forces_at_faces(frame, &node_forces));
+            }
+        }
+    }
+
+    Ok(TimeHistoryResults {
+        times: (0..=settings.step_count)
+            .map(|x| x as f64 * settings.time_step)
+            .collect(),
+        nodes: node_histories,
+        frame_end_forces,
+    })
+}
+
+/**
+ * Integrates `M a + C v + k u = F(t)` from rest with the Newmark-β or HHT-α method.
+ *
+ * The effective stiffness is factored once, so each step costs a single forward and back
+ * substitution. The initial acceleration is found from `M a = F(0)`; where the mass matrix is
+ * singular, the pseudo-inverse gives the acceleration of the degrees of freedom with mass.
+ *
+ * # Arguments
+ *
+ * `stiffness` - stiffness `k` of the free degrees of freedom
+ * `mass` - mass `M` of the free degrees of freedom
+ * `damping` - damping `C` of the free degrees of freedom
+ * `forces` - forces at the free degrees of freedom at every time step, starting at time zero
+ * `time_step` - the constant time step
+ * `scheme` - the integration scheme
+ */
+pub fn integrate_equations_of_motion(
+    stiffness: &DMatrix<f64>,
+    mass: &DMatrix<f64>,
+    damping: &DMatrix<f64>,
+    forces: &[DVector<f64>],
+    time_step: f64,
+    scheme: IntegrationScheme,
+) -> Result<IntegrationHistory, String> {
+    let n = stiffness.nrows();
+    let (alpha, gamma, beta) = scheme.parameters();
+    let dt = time_step;
+
+    let initial_force = match forces.first() {
+        Some(x) => x.clone(),
+        None => DVector::<f64>::zeros(n),
+    };
+    let initial_acceleration = match mass.clone().cholesky() {
+        Some(x) => x.solve(&initial_force),
+        None => match mass.clone().pseudo_inverse(1e-12 * mass.amax()) {
+            Ok(x) => x * &initial_force,
+            Err(e) => return Err(format!("Failed to find the initial acceleration: {}", e)),
+        },
+    };
+
+    let effective = mass / (beta * dt * dt)
+        + damping * ((1. + alpha) * gamma / (beta * dt))
+        + stiffness * (1. + alpha);
+    let effective = match effective.cholesky() {
+        Some(x) => x,
+        None => return Err("The effective stiffness is not positive definite".to_string()),
+    };
+
+    let mut history = IntegrationHistory {
+        displacements: vec![DVector::<f64>::zeros(n)],
+        velocities: vec![DVector::<f64>::zeros(n)],
+        accelerations: vec![initial_acceleration],
+    };
+
+    for step in 1..forces.len() {
+        let (u, v, a) = (
+            &history.displacements[step - 1],
+            &history.velocities[step - 1],
+            &history.accelerations[step - 1],
+        );
+
+        let inertia = u / (beta * dt * dt) + v / (beta * dt) + a * (0.5 / beta - 1.);
+        let viscous = u * (gamma / (beta * dt))
+            - v * (1. - gamma / beta)
+            - a * (dt * (1. - 0.5 * gamma / beta));
+        let rhs = &forces[step] * (1. + alpha) - &forces[step - 1] * alpha
+            + (damping * v + stiffness * u) * alpha
+            + mass * inertia
+            + damping * viscous * (1. + alpha);
+
+        let u_next = effective.solve(&rhs);
+        let a_next = (&u_next - u - v * dt) / (beta * dt * dt) - a * (0.5 / beta - 1.);
+        let v_next = v + a * (dt * (1. - gamma)) + &a_next * (dt * gamma);
+
+        history.displacements.push(u_next);
+        history.velocities.push(v_next);
+        history.accelerations.push(a_next);
+    }
+
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Response of `m = 1`, `k = 1` to a suddenly applied unit force, which is `1 − cos t`
+    fn step_response(scheme: IntegrationScheme) -> IntegrationHistory {
+        let one = DMatrix::<f64>::identity(1, 1);
+        let zero = DMatrix::<f64>::zeros(1, 1);
+        let forces = vec![DVector::<f64>::from_element(1, 1.); 315];
+        integrate_equations_of_motion(&one, &one, &zero, &forces, 0.01, scheme).unwrap()
+    }
+
+    #[test]
+    fn average_acceleration_matches_step_response() {
+        let history = step_response(IntegrationScheme::average_acceleration());
+        for (step, u) in history.displacements.iter().enumerate() {
+            let time = step as f64 * 0.01;
+            assert_abs_diff_eq!(u[0], 1. - time.cos(), epsilon = 1e-4);
+        }
+        // The peak of 2 is reached at t = π
+        assert_abs_diff_eq!(history.accelerations[0][0], 1.);
+        assert_abs_diff_eq!(history.displacements[314][0], 2., epsilon = 1e-4);
+    }
+
+    #[test]
+    fn hilber_hughes_taylor_matches_step_response() {
+        let history = step_response(IntegrationScheme::HilberHughesTaylor { alpha: -0.1 });
+        assert_abs_diff_eq!(history.displacements[314][0], 2., epsilon = 1e-3);
+    }
+
+    #[test]
+    fn rayleigh_coefficients_give_requested_damping() {
+        let damping = RayleighDamping::from_damping_ratios(2., 0.05, 20., 0.05);
+        let ratio = |omega: f64| {
+            damping.mass_coefficient / (2. * omega) + damping.stiffness_coefficient * omega / 2.
+        };
+        assert_relative_eq!(ratio(2.), 0.05, max_relative = 1e-12);
+        assert_relative_eq!(ratio(20.), 0.05, max_relative = 1e-12);
+    }
+
+    #[test]
+    fn records_parse_with_and_without_times() {
+        let timed =
+            TimeSeries::parse("# time, acceleration\n0, 0\n0.5, 1.0\n\n1.0, 0\n", None).unwrap();
+        assert_relative_eq!(timed.value_at(0.25), 0.5);
+        assert_relative_eq!(timed.value_at(0.75), 0.5);
+        assert_relative_eq!(timed.value_at(2.), 0.);
+
+        let spaced = TimeSeries::parse("0.0\n2.0\n4.0", Some(0.1)).unwrap();
+        assert_relative_eq!(spaced.value_at(0.15), 3.);
+        assert!(TimeSeries::parse("0.0\n2.0", None).is_err());
+        assert!(TimeSeries::parse("0 1\n0 2", None).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::models::cable::CableElement;
+use na::{Matrix2, Matrix3, Point3, Vector2, Vector3};
+
+/// Chords whose horizontal projection is below this fraction of their length are treated as
+/// vertical, where the horizontal tension of a catenary vanishes and the cable hangs straight.
+const VERTICAL_CHORD_TOLERANCE: f64 = 1e-6;
+
+/// The catenary is found once the gap between its ends and the nodes falls below this fraction
+/// of the chord length.
+const CATENARY_TOLERANCE: f64 = 1e-12;
+
+const CATENARY_MAX_ITERATIONS: usize = 100;
+
+/// The forces and tangent stiffness of a cable between two positions of its nodes.
+pub struct CableState {
+    /// World force exerted by the cable on its start node
+    pub start_force: Vector3<f64>,
+    /// World force exerted by the cable on its end node
+    pub end_force: Vector3<f64>,
+    pub start_tension: f64,
+    pub end_tension: f64,
+    /// World tangent stiffness `k` of the end node relative to the start node, so that the
+    /// translational stiffness of the element is `[k, -k; -k, k]`
+    pub stiffness: Matrix3<f64>,
+    /// True if the cable carries no tension
+    pub slack: bool,
+}
+
+/// The equilibrium shape of a cable.
+enum CableShape {
+    /// A weightless or vertical cable, stretched straight between its ends
+    Straight { tension: f64 },
+    /// An elastic catenary with the constant horizontal tension `H` along the unit horizontal
+    /// `axis` and the vertical tension `V₀` at the start, which grows by the weight towards the
+    /// end
+    Catenary {
+        horizontal: f64,
+        vertical: f64,
+        axis: Vector3<f64>,
+    },
+}
+
+/**
+ * Returns the end forces and tangent stiffness of a cable hanging between the given positions
+ * of its nodes.
+ *
+ * A cable with weight hangs as an elastic catenary, found by Newton's method on its horizontal
+ * and vertical tension, and its in-plane stiffness is the inverse of the flexibility of the
+ * catenary ends. A weightless or vertical cable is a straight tension-only bar carrying any
+ * weight half at each end.
+ *
+ * # Arguments
+ *
+ * `cable` - the cable
+ * `unstressed_length` - the unstressed length of the cable
+ * `start` - world position of the start node
+ * `end` - world position of the end node
+ */
+#[allow(non_snake_case)]
+pub fn cable_element_state(
+    cable: &CableElement,
+    unstressed_length: f64,
+    start: &Point3<f64>,
+    end: &Point3<f64>,
+) -> Result<CableState, String> {
+    let (EA, w, L0) = (
+        cable.material.E * cable.area,
+        cable.weight,
+        unstressed_length,
+    );
+    let chord = end - start;
+    let vertical = Vector3::z();
+
+    match cable_shape(cable, L0, &chord)? {
+        CableShape::Straight { tension } => {
+            let length = chord.norm();
+            let direction = chord / length;
+            let along = direction * direction.transpose();
+            let stiffness = if tension > 0. {
+                EA / L0 * along + tension / length * (Matrix3::identity() - along)
+            } else {
+                Matrix3::zeros()
+            };
+            let hanging = vertical * (w * L0 / 2.);
+            Ok(CableState {
+                start_force: direction * tension - hanging,
+                end_force: -direction * tension - hanging,
+                start_tension: tension,
+                end_tension: tension,
+                stiffness,
+                slack: tension == 0.,
+            })
+        }
+        CableShape::Catenary {
+            horizontal,
+            vertical: start_vertical,
+            axis,
+        } => {
+            let end_vertical = start_vertical + w * L0;
+            let flexibility = catenary_flexibility(EA, w, L0, horizontal, start_vertical);
+            let in_plane = flexibility.try_inverse().ok_or(format!(
+                "Failed to invert the flexibility of cable {}",
+                cable.id
+            ))?;
+
+            // Out of the vertical plane the horizontal tension acts as a string
+            let across = vertical.cross(&axis);
+            let horizontal_length = Vector2::new(chord.x, chord.y).norm();
+            let stiffness = axis * in_plane[(0, 0)] * axis.transpose()
+                + axis * in_plane[(0, 1)] * vertical.transpose()
+                + vertical * in_plane[(1, 0)] * axis.transpose()
+                + vertical * in_plane[(1, 1)] * vertical.transpose()
+                + across * (horizontal / horizontal_length) * across.transpose();
+
+            Ok(CableState {
+                start_force: axis * horizontal + vertical * start_vertical,
+                end_force: -(axis * horizontal + vertical * end_vertical),
+                start_tension: horizontal.hypot(start_vertical),
+                end_tension: horizontal.hypot(end_vertical),
+                stiffness,
+                slack: false,
+            })
+        }
+    }
+}
+
+/**
+ * Returns world positions along a cable hanging between the given positions of its nodes, at
+ * equal intervals of unstressed length from the start to the end.
+ *
+ * # Arguments
+ *
+ * `cable` - the cable
+ * `unstressed_length` - the unstressed length of the cable
+ * `start` - world position of the start node
+ * `end` - world position of the end node
+ * `station_count` - the number of positions including both ends, at least 2
+ */
+#[allow(non_snake_case)]
+pub fn cable_profile(
+    cable: &CableElement,
+    unstressed_length: f64,
+    start: &Point3<f64>,
+    end: &Point3<f64>,
+    station_count: usize,
+) -> Result<Vec<Point3<f64>>, String> {
+    if station_count < 2 {
+        return Err("A cable profile needs at least two stations".to_string());
+    }
+    let (EA, w, L0) = (
+        cable.material.E * cable.area,
+        cable.weight,
+        unstressed_length,
+    );
+    let chord = end - start;
+    let shape = cable_shape(cable, L0, &chord)?;
+
+    let profile = (0..station_count)
+        .map(|i| {
+            let s = L0 * i as f64 / (station_count - 1) as f64;
+            match shape {
+                CableShape::Straight { .. } => start + chord * (s / L0),
+                CableShape::Catenary {
+                    horizontal: H,
+                    vertical: V0,
+                    axis,
+                } => {
+                    let V = V0 + w * s;
+                    let along = H * s / EA + H / w * ((V / H).asinh() - (V0 / H).asinh());
+                    let up = (V0 * s + w * s * s / 2.) / EA + (H.hypot(V) - H.hypot(V0)) / w;
+                    start + axis * along + Vector3::z() * up
+                }
+            }
+        })
+        .collect();
+    Ok(profile)
+}
+
+#[allow(non_snake_case)]
+fn cable_shape(
+    cable: &CableElement,
+    unstressed_length: f64,
+    chord: &Vector3<f64>,
+) -> Result<CableShape, String> {
+    let (EA, w, L0) = (
+        cable.material.E * cable.area,
+        cable.weight,
+        unstressed_length,
+    );
+    let length = chord.norm();
+    let horizontal_length = Vector2::new(chord.x, chord.y).norm();
+
+    if w == 0. || horizontal_length <= VERTICAL_CHORD_TOLERANCE * length {
+        return Ok(CableShape::Straight {
+            tension: (EA * (length - L0) / L0).max(0.),
+        });
+    }
+
+    // Start from the estimate of Jayaraman and Knudson (1981)
+    let (lh, lz) = (horizontal_length, chord.z);
+    let lambda = if L0 > length {
+        (3. * ((L0 * L0 - lz * lz) / (lh * lh) - 1.)).sqrt()
+    } else {
+        0.2
+    };
+    let mut tension = Vector2::new(w * lh / (2. * lambda), w / 2. * (lz / lambda.tanh() - L0));
+
+    let target = Vector2::new(lh, lz);
+    for _ in 0..CATENARY_MAX_ITERATIONS {
+        let (H, V0) = (tension[0], tension[1]);
+        let gap = catenary_span(EA, w, L0, H, V0) - target;
+        if gap.norm() <= CATENARY_TOLERANCE * length {
+            return Ok(CableShape::Catenary {
+                horizontal: H,
+                vertical: V0,
+                axis: Vector3::new(chord.x, chord.y, 0.) / lh,
+            });
+        }
+        let flexibility = catenary_flexibility(EA, w, L0, H, V0);
+        let mut step = match flexibility.try_inverse() {
+            Some(x) => -(x * gap),
+            None => break,
+        };
+        // The horizontal tension of a catenary stays positive
+        while tension[0] + step[0] <= 0. {
+            step /= 2.;
+        }
+        tension += step;
+    }
+    Err(format!("Failed to find the catenary of cable {}", cable.id))
+}
+
+/// Returns the horizontal and vertical span `[lh, lz]` of an elastic catenary with the
+/// horizontal tension `H` and the vertical tension `V0` at its start.
+#[allow(non_snake_case)]
+fn catenary_span(EA: f64, w: f64, L0: f64, H: f64, V0: f64) -> Vector2<f64> {
+    let V1 = V0 + w * L0;
+    Vector2::new(
+        H * L0 / EA + H / w * ((V1 / H).asinh() - (V0 / H).asinh()),
+        (V0 * L0 + w * L0 * L0 / 2.) / EA + (H.hypot(V1) - H.hypot(V0)) / w,
+    )
+}
+
+/// Returns the derivatives of the span of an elastic catenary with respect to its horizontal
+/// tension and vertical tension at the start.
+#[allow(non_snake_case)]
+fn catenary_flexibility(EA: f64, w: f64, L0: f64, H: f64, V0: f64) -> Matrix2<f64> {
+    let V1 = V0 + w * L0;
+    let (T0, T1) = (H.hypot(V0), H.hypot(V1));
+    let slopes = V1 / T1 - V0 / T0;
+    let coupling = H / w * (1. / T1 - 1. / T0);
+    Matrix2::new(
+        L0 / EA + ((V1 / H).asinh() - (V0 / H).asinh()) / w - slopes / w,
+        coupling,
+        coupling,
+        L0 / EA + slopes / w,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::cable::CableInitialState;
+    use crate::models::material::IsotropicMaterial;
+
+    fn cable(weight: f64) -> CableElement {
+        CableElement {
+            id: "cable".to_string(),
+            start_node_id: "a".to_string(),
+            end_node_id: "b".to_string(),
+            area: 1.,
+            material: IsotropicMaterial::new(1e12, 0.3),
+            weight,
+            initial_state: CableInitialState::UnstressedLength(0.),
+        }
+    }
+
+    #[test]
+    fn inextensible_catenary_matches_closed_form_sag() {
+        // A level span of 10 under unit weight with a horizontal tension of 10
+        let (span, horizontal): (f64, f64) = (10., 10.);
+        let length = 2. * horizontal * (span / (2. * horizontal)).sinh();
+        let cable = cable(1.);
+        let (start, end) = (Point3::origin(), Point3::new(6., 8., 0.));
+
+        let state = cable_element_state(&cable, length, &start, &end).unwrap();
+        let end_tension = horizontal * (span / (2. * horizontal)).cosh();
+        assert_relative_eq!(state.start_tension, end_tension, max_relative = 1e-6);
+        assert_relative_eq!(state.end_tension, end_tension, max_relative = 1e-6);
+        assert_relative_eq!(
+            (state.start_force + state.end_force).z,
+            -length,
+            max_relative = 1e-9
+        );
+
+        let profile = cable_profile(&cable, length, &start, &end, 5).unwrap();
+        let sag = horizontal * ((span / (2. * horizontal)).cosh() - 1.);
+        assert_relative_eq!(profile[2].z, -sag, max_relative = 1e-6);
+        assert_abs_diff_eq!((profile[4] - end).norm(), 0., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn tangent_stiffness_matches_finite_differences() {
+        let mut cable = cable(0.5);
+        cable.material = IsotropicMaterial::new(1000., 0.3);
+        let (start, end) = (Point3::new(1., 0., 2.), Point3::new(5., 3., 4.));
+        let length = 5.5;
+
+        let state = cable_element_state(&cable, length, &start, &end).unwrap();
+        let step = 1e-6;
+        for j in 0..3 {
+            let mut moved = end;
+            moved[j] += step;
+            let perturbed = cable_element_state(&cable, length, &start, &moved).unwrap();
+            // The stiffness gives the change in the force the end node exerts on the cable
+            let change = -(perturbed.end_force - state.end_force) / step;
+            for i in 0..3 {
+                assert_abs_diff_eq!(change[i], state.stiffness[(i, j)], epsilon = 1e-4);
+            }
+        }
+
+        // Weightless cables are tension-only
+        cable.weight = 0.;
+        let slack = cable_element_state(&cable, 6., &start, &end).unwrap();
+        assert!(slack.slack);
+        assert_eq!(slack.stiffness, Matrix3::zeros());
+        let taut = cable_element_state(&cable, 5., &start, &end).unwrap();
+        let chord = (end - start).norm();
+        assert_relative_eq!(
+            taut.start_tension,
+            1000. * (chord - 5.) / 5.,
+            max_relative = 1e-12
+        );
+    }
+}
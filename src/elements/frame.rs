@@ -1,7 +1,17 @@
-use crate::models::frame::{FrameElement, FrameEndRelease, FrameEndReleases};
+use crate::models::frame::{
+    BeamTheory, CrossSection, FrameElement, FrameEndRelease, FrameEndReleases,
+};
 use crate::utils::transform::world_to_local_transform;
 use na::*;
 
+/// Fraction of its initial value below which the pivot of a released degree of freedom is taken
+/// as zero, as when an earlier release already removed its stiffness, e.g. shear released at
+/// both ends
+const RELEASED_PIVOT_TOLERANCE: f64 = 1e-10;
+
+/// Number of Simpson's rule intervals along a tapered frame, which must be even
+const TAPER_INTEGRATION_INTERVALS: usize = 64;
+
 pub fn transform_frame_local_to_world(
     frame: &FrameElement,
     m: &MatrixN<f64, U12>,
@@ -17,6 +27,27 @@ pub fn transform_frame_world_to_local(
     transform * m
 }
 
+/// Returns the stiffness matrix `Γᵀ k Γ` in world coordinates from the local stiffness matrix `k`
+pub fn transform_frame_stiffness_local_to_world(
+    frame: &FrameElement,
+    k: &MatrixN<f64, U12>,
+) -> MatrixN<f64, U12> {
+    let transform = world_to_local_transform(&frame.geometry.local_axes);
+    transform.transpose() * k * transform
+}
+
+/// Returns the local elastic stiffness matrix of a frame according to its beam theory and any
+/// taper.
+pub fn frame_element_local_stiffness_matrix(element: &FrameElement) -> MatrixN<f64, U12> {
+    if element.geometry.taper.is_some() {
+        return frame_element_tapered_stiffness_matrix(element);
+    }
+    match element.beam_theory {
+        BeamTheory::EulerBernoulli => frame_element_stiffness_matrix(element),
+        BeamTheory::Timoshenko => frame_element_with_shear_deformation_stiffness_matrix(element),
+    }
+}
+
 #[rustfmt::skip]
 #[allow(non_snake_case)]
 pub fn frame_element_stiffness_matrix(element: &FrameElement) -> MatrixN<f64, U12> {
@@ -24,7 +55,7 @@ pub fn frame_element_stiffness_matrix(element: &FrameElement) -> MatrixN<f64, U1
     let section = &element.geometry.cross_section;
     let material = &element.material;
 
-    let L = element.length_or_inf();
+    let L = element.flexible_length_or_inf();
     let L2 = L * L;
 
     let (A, Iy, Iz, J) = (section.A, section.Iy, section.Iz, section.J);
@@ -65,7 +96,9 @@ pub fn frame_element_stiffness_matrix(element: &FrameElement) -> MatrixN<f64, U1
     let axial_action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
     merge_single_action_into_complete_4by4(&axial_action, &mut m);
 
+    add_foundation_stiffness(&mut m, element, L);
     apply_end_releases(&mut m, element);    
+    apply_end_offsets(&mut m, element);
     
     m
 }
@@ -77,7 +110,7 @@ pub fn frame_element_with_shear_deformation_stiffness_matrix(element: &FrameElem
     let section = &element.geometry.cross_section;
     let material = &element.material;
 
-    let L = element.length_or_inf();
+    let L = element.flexible_length_or_inf();
     let L2 = L * L;
 
     let (A, Avy, Avz, Iy, Iz, J) = (section.A, section.Avy, section.Avz, section.Iy, section.Iz, section.J);
@@ -101,7 +134,7 @@ pub fn frame_element_with_shear_deformation_stiffness_matrix(element: &FrameElem
 
     // Bending About Z
     let index_map = Vector4::new(7, 11, 1, 5);
-    let eta = E * Iz / Avy / G;
+    let eta = shear_flexibility(E * Iz, G * Avy);
     let stiffness_matrix = E * Iz / L / (L2 / 12. + eta) * Matrix4::new(     1.,       -L / 2.,     -1.,       -L / 2.,
                                                                         -L / 2., L2 / 3. + eta,  L / 2., L2 / 6. - eta,
                                                                             -1.,        L / 2.,      1.,        L / 2.,
@@ -113,7 +146,7 @@ pub fn frame_element_with_shear_deformation_stiffness_matrix(element: &FrameElem
 
     // Bending About Y
     let index_map = Vector4::new(8, 10, 2, 4);
-    let eta = E * Iy / Avz / G;
+    let eta = shear_flexibility(E * Iy, G * Avz);
     let stiffness_matrix = E * Iy / L / (L2 / 12. + eta) * Matrix4::new(     1.,        L / 2.,     -1.,        L / 2.,
                                                                          L / 2., L2 / 3. + eta, -L / 2., L2 / 6. - eta,
                                                                             -1.,       -L / 2.,      1.,       -L / 2.,
@@ -122,11 +155,391 @@ pub fn frame_element_with_shear_deformation_stiffness_matrix(element: &FrameElem
     let axial_action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
     merge_single_action_into_complete_4by4(&axial_action, &mut m);
     
+    add_foundation_stiffness(&mut m, element, L);
     apply_end_releases(&mut m, element);
+    apply_end_offsets(&mut m, element);
     
     m
 }
 
+/// Returns the local stiffness matrix of a frame whose cross section varies along its length.
+///
+/// The flexibility of the member as a cantilever from its start is integrated numerically over
+/// the varying section, inverted, and expanded to both ends by equilibrium. Shear deformation is
+/// included for the Timoshenko beam theory. A prismatic member gives the same stiffness as
+/// `frame_element_stiffness_matrix` or `frame_element_with_shear_deformation_stiffness_matrix`.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn frame_element_tapered_stiffness_matrix(element: &FrameElement) -> MatrixN<f64, U12> {
+
+    let material = &element.material;
+    let (E, G) = (material.E, material.G);
+    let shear = element.beam_theory == BeamTheory::Timoshenko;
+
+    let L = element.flexible_length_or_inf();
+
+    // Integrals along the member of 1, (L - x) and (L - x)² over each sectional stiffness
+    let integrate = |stiffness: &dyn Fn(&CrossSection) -> f64, power: i32| {
+        integrate_along_frame(L, |x| {
+            let k = stiffness(&element.geometry.cross_section_at(x / L));
+            if k == 0. || !k.is_finite() { 0. } else { (L - x).powi(power) / k }
+        })
+    };
+
+    let mut m = MatrixN::<f64, U12>::zeros();
+
+    // Axial
+    let index_map = Vector2::new(0, 6);
+    let stiffness_matrix = 1. / integrate(&|x| E * x.A, 0) * Matrix2::new( 1., -1.,
+                                                                           -1.,  1.);
+    let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_2by2(&action, &mut m);
+
+    // Torsion
+    let index_map = Vector2::new(3, 9);
+    let stiffness_matrix = 1. / integrate(&|x| G * x.J, 0) * Matrix2::new( 1., -1.,
+                                                                          -1.,  1.);
+    let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_2by2(&action, &mut m);
+
+    // Bending About Z, from the end shear and moment with the start clamped
+    let shear_z = if shear { integrate(&|x| G * x.Avy, 0) } else { 0. };
+    let flexibility = Matrix2::new(integrate(&|x| E * x.Iz, 2) + shear_z, integrate(&|x| E * x.Iz, 1),
+                                   integrate(&|x| E * x.Iz, 1),           integrate(&|x| E * x.Iz, 0));
+    let equilibrium = Matrix4x2::new(-1.,  0.,
+                                      -L, -1.,
+                                      1.,  0.,
+                                      0.,  1.);
+    let index_map = Vector4::new(1, 5, 7, 11);
+    let stiffness_matrix = equilibrium * invert_flexibility(&flexibility) * equilibrium.transpose();
+    let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_4by4(&action, &mut m);
+
+    // Bending About Y, where the rotation is the negative slope
+    let shear_y = if shear { integrate(&|x| G * x.Avz, 0) } else { 0. };
+    let flexibility = Matrix2::new( integrate(&|x| E * x.Iy, 2) + shear_y, -integrate(&|x| E * x.Iy, 1),
+                                   -integrate(&|x| E * x.Iy, 1),            integrate(&|x| E * x.Iy, 0));
+    let equilibrium = Matrix4x2::new(-1.,  0.,
+                                       L, -1.,
+                                      1.,  0.,
+                                      0.,  1.);
+    let index_map = Vector4::new(2, 4, 8, 10);
+    let stiffness_matrix = equilibrium * invert_flexibility(&flexibility) * equilibrium.transpose();
+    let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_4by4(&action, &mut m);
+
+    add_foundation_stiffness(&mut m, element, L);
+    apply_end_releases(&mut m, element);
+    apply_end_offsets(&mut m, element);
+
+    m
+}
+
+/// Adds the consistent stiffness of any elastic foundation along the flexible length `L`, which
+/// is `∫ Nᵀ k N dx` over the cubic transverse displacement functions and so takes the form of
+/// the consistent mass matrix with `k L` in place of the mass.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+fn add_foundation_stiffness(m: &mut MatrixN<f64, U12>, element: &FrameElement, L: f64) {
+    let foundation = match &element.foundation {
+        Some(x) => x,
+        None => return,
+    };
+    let L2 = L * L;
+
+    // Transverse to the axis along y, bending about z
+    let index_map = Vector4::new(1, 5, 7, 11);
+    let stiffness_matrix = foundation.ky * L / 420. * Matrix4::new(     156.,   22. * L,       54.,  -13. * L,
+                                                                     22. * L,  4. * L2,   13. * L,  -3. * L2,
+                                                                         54.,   13. * L,      156.,  -22. * L,
+                                                                    -13. * L, -3. * L2,  -22. * L,   4. * L2);
+    add_single_action_into_complete_4by4(&SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix }, m);
+
+    // Transverse to the axis along z, bending about y
+    let index_map = Vector4::new(2, 4, 8, 10);
+    let stiffness_matrix = foundation.kz * L / 420. * Matrix4::new(     156.,  -22. * L,       54.,   13. * L,
+                                                                    -22. * L,  4. * L2,  -13. * L,  -3. * L2,
+                                                                         54.,  -13. * L,      156.,   22. * L,
+                                                                     13. * L, -3. * L2,   22. * L,   4. * L2);
+    add_single_action_into_complete_4by4(&SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix }, m);
+}
+
+/// Integrates `f(x)` from 0 to `length` by the composite Simpson's rule.
+pub fn integrate_along_frame<F: Fn(f64) -> f64>(length: f64, f: F) -> f64 {
+    let n = TAPER_INTEGRATION_INTERVALS;
+    let h = length / n as f64;
+    let sum: f64 = (0..=n)
+        .map(|i| {
+            let weight = if i == 0 || i == n {
+                1.
+            } else if i % 2 == 1 {
+                4.
+            } else {
+                2.
+            };
+            weight * f(i as f64 * h)
+        })
+        .sum();
+    sum * h / 3.
+}
+
+/// Returns the stiffness from a 2x2 flexibility, or zero stiffness if the member has no
+/// flexibility to invert, e.g. a section with no bending stiffness.
+fn invert_flexibility(flexibility: &Matrix2<f64>) -> Matrix2<f64> {
+    flexibility.try_inverse().unwrap_or_else(Matrix2::zeros)
+}
+
+/// Returns the ratio `EI / GAv` of bending to shear stiffness, taking a shear area of zero to
+/// mean shear deformation is ignored.
+fn shear_flexibility(bending_stiffness: f64, shear_stiffness: f64) -> f64 {
+    if shear_stiffness == 0. {
+        0.
+    } else {
+        bending_stiffness / shear_stiffness
+    }
+}
+
+/// Returns the local geometric stiffness matrix of a frame carrying the axial force `P`
+/// (tension positive) used for linear buckling and second-order analysis.
+///
+/// Where both end moments about an axis are released, bending about that axis reduces to the
+/// string stiffness `P / L` of a pin-ended member. Other releases follow the deflected shape of
+/// the condensed elastic stiffness, so a moment released at one end gives the geometric
+/// stiffness of a pinned-fixed member.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn frame_element_geometric_stiffness_matrix(element: &FrameElement, P: f64) -> MatrixN<f64, U12> {
+
+    let section = &element.geometry.average_cross_section();
+
+    let L = element.length_or_inf();
+    let L2 = L * L;
+
+    let (A, Iy, Iz) = (section.A, section.Iy, section.Iz);
+
+    let mut m = MatrixN::<f64, U12>::zeros();
+
+    // Torsion, from the polar radius of gyration
+    if element.start_releases.T == FrameEndRelease::Fixed && element.end_releases.T == FrameEndRelease::Fixed {
+        let index_map = Vector2::new(3, 9);
+        let stiffness_matrix = P * (Iy + Iz) / A / L * Matrix2::new( 1., -1.,
+                                                                    -1.,  1.);
+        let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+        merge_single_action_into_complete_2by2(&action, &mut m);
+    }
+
+    // Bending About Z
+    if is_bending_released_about_z(element) {
+        let index_map = Vector2::new(1, 7);
+        let stiffness_matrix = P / L * Matrix2::new( 1., -1.,
+                                                    -1.,  1.);
+        let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+        merge_single_action_into_complete_2by2(&action, &mut m);
+    } else {
+        let index_map = Vector4::new(1, 5, 7, 11);
+        let stiffness_matrix = P / L * Matrix4::new(  6. / 5.,       L / 10., -6. / 5.,       L / 10.,
+                                                       L / 10., 2. * L2 / 15., -L / 10.,      -L2 / 30.,
+                                                     -6. / 5.,      -L / 10.,  6. / 5.,      -L / 10.,
+                                                       L / 10.,     -L2 / 30., -L / 10., 2. * L2 / 15.);
+        let released = [&element.start_releases.Vy, &element.start_releases.Mz,
+                        &element.end_releases.Vy, &element.end_releases.Mz];
+        let transform = released_bending_transform(&bending_shape_stiffness(L, 1.), &released);
+        let stiffness_matrix = transform.transpose() * stiffness_matrix * transform;
+        let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+        merge_single_action_into_complete_4by4(&action, &mut m);
+    }
+
+    // Bending About Y
+    if is_bending_released_about_y(element) {
+        let index_map = Vector2::new(2, 8);
+        let stiffness_matrix = P / L * Matrix2::new( 1., -1.,
+                                                    -1.,  1.);
+        let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+        merge_single_action_into_complete_2by2(&action, &mut m);
+    } else {
+        let index_map = Vector4::new(2, 4, 8, 10);
+        let stiffness_matrix = P / L * Matrix4::new(  6. / 5.,      -L / 10., -6. / 5.,      -L / 10.,
+                                                      -L / 10., 2. * L2 / 15.,  L / 10.,      -L2 / 30.,
+                                                     -6. / 5.,       L / 10.,  6. / 5.,       L / 10.,
+                                                      -L / 10.,     -L2 / 30.,  L / 10., 2. * L2 / 15.);
+        let released = [&element.start_releases.Vz, &element.start_releases.My,
+                        &element.end_releases.Vz, &element.end_releases.My];
+        let transform = released_bending_transform(&bending_shape_stiffness(L, -1.), &released);
+        let stiffness_matrix = transform.transpose() * stiffness_matrix * transform;
+        let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+        merge_single_action_into_complete_4by4(&action, &mut m);
+    }
+
+    m
+}
+
+/// Returns the Euler-Bernoulli bending stiffness of a frame of unit `EI` over the transverse
+/// translation and rotation of each end, where `sign` is 1 for bending about local z and -1
+/// for bending about local y.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+fn bending_shape_stiffness(L: f64, sign: f64) -> Matrix4<f64> {
+    let (L2, s) = (L * L, sign);
+    1. / L * Matrix4::new(    12. / L2,  s * 6. / L,    -12. / L2,  s * 6. / L,
+                           s * 6. / L ,  4.        , -s * 6. / L ,  2.        ,
+                             -12. / L2, -s * 6. / L,     12. / L2, -s * 6. / L,
+                           s * 6. / L ,  2.        , -s * 6. / L ,  4.        )
+}
+
+/// Returns the transformation `T` from the bending displacements of both ends to the same
+/// displacements with the released ones found from the condensed `stiffness`, so the released
+/// rows of `T` carry no force, and `Tᵀ K T` condenses any matrix `K` consistently.
+fn released_bending_transform(
+    stiffness: &Matrix4<f64>,
+    released: &[&FrameEndRelease; 4],
+) -> Matrix4<f64> {
+    let mut k = *stiffness;
+    let mut transform = Matrix4::<f64>::identity();
+    for (i, release) in released.iter().enumerate() {
+        if **release != FrameEndRelease::Free {
+            continue;
+        }
+        let mut step = Matrix4::<f64>::identity();
+        if k[(i, i)].abs() > RELEASED_PIVOT_TOLERANCE * stiffness[(i, i)].abs() {
+            for j in (0..4).filter(|x| *x != i) {
+                step[(i, j)] = -k[(i, j)] / k[(i, i)];
+            }
+        }
+        step[(i, i)] = 0.;
+        k = step.transpose() * k * step;
+        transform *= step;
+    }
+    transform
+}
+
+/// Returns the local geometric stiffness of a frame carrying the axial force `P` (tension
+/// positive) from the rotation of its chord alone, i.e. the P-Δ effect without the P-δ effect
+/// of member curvature.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn frame_element_string_stiffness_matrix(element: &FrameElement, P: f64) -> MatrixN<f64, U12> {
+
+    let L = element.length_or_inf();
+
+    let mut m = MatrixN::<f64, U12>::zeros();
+
+    // Transverse translations along local y then local z
+    for index_map in [Vector2::new(1, 7), Vector2::new(2, 8)].iter() {
+        let stiffness_matrix = P / L * Matrix2::new( 1., -1.,
+                                                    -1.,  1.);
+        let action = SingleActionStiffnessMatrix2by2 { index_map: *index_map, stiffness_matrix };
+        merge_single_action_into_complete_2by2(&action, &mut m);
+    }
+
+    m
+}
+
+/// Returns the local consistent mass matrix of a frame, derived from the same cubic
+/// displacement functions as the stiffness matrix. Rotary inertia of the cross section is
+/// included only for torsion, using the polar moment `Iy + Iz`.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn frame_element_consistent_mass_matrix(element: &FrameElement) -> MatrixN<f64, U12> {
+
+    let section = &element.geometry.average_cross_section();
+
+    let L = element.length_or_inf();
+    let L2 = L * L;
+
+    let (A, Iy, Iz) = (section.A, section.Iy, section.Iz);
+    let mass = element.material.rho * A * L;
+
+    let mut m = MatrixN::<f64, U12>::zeros();
+
+    // Axial
+    let index_map = Vector2::new(0, 6);
+    let stiffness_matrix = mass / 6. * Matrix2::new( 2., 1.,
+                                                     1., 2.);
+    let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_2by2(&action, &mut m);
+
+    // Torsion
+    let index_map = Vector2::new(3, 9);
+    let stiffness_matrix = mass * (Iy + Iz) / A / 6. * Matrix2::new( 2., 1.,
+                                                                     1., 2.);
+    let action = SingleActionStiffnessMatrix2by2 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_2by2(&action, &mut m);
+
+    // Bending About Z
+    let index_map = Vector4::new(1, 5, 7, 11);
+    let stiffness_matrix = mass / 420. * Matrix4::new(     156.,   22. * L,       54.,  -13. * L,
+                                                        22. * L,  4. * L2,   13. * L,  -3. * L2,
+                                                            54.,   13. * L,      156.,  -22. * L,
+                                                       -13. * L, -3. * L2,  -22. * L,   4. * L2);
+    let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_4by4(&action, &mut m);
+
+    // Bending About Y
+    let index_map = Vector4::new(2, 4, 8, 10);
+    let stiffness_matrix = mass / 420. * Matrix4::new(     156.,  -22. * L,       54.,   13. * L,
+                                                       -22. * L,  4. * L2,  -13. * L,  -3. * L2,
+                                                            54.,  -13. * L,      156.,   22. * L,
+                                                        13. * L, -3. * L2,   22. * L,   4. * L2);
+    let action = SingleActionStiffnessMatrix4by4 { index_map, stiffness_matrix };
+    merge_single_action_into_complete_4by4(&action, &mut m);
+
+    m
+}
+
+/// Returns the local lumped mass matrix of a frame, with half of the mass at each end in every
+/// translational direction and half of the torsional inertia at each end. Bending rotations
+/// carry no mass.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn frame_element_lumped_mass_matrix(element: &FrameElement) -> MatrixN<f64, U12> {
+
+    let section = &element.geometry.average_cross_section();
+
+    let L = element.length_or_inf();
+
+    let (A, Iy, Iz) = (section.A, section.Iy, section.Iz);
+    let mass = element.material.rho * A * L;
+
+    let mut m = MatrixN::<f64, U12>::zeros();
+
+    for i in [0, 1, 2, 6, 7, 8].iter() {
+        m[(*i, *i)] = mass / 2.;
+    }
+    for i in [3, 9].iter() {
+        m[(*i, *i)] = mass * (Iy + Iz) / A / 2.;
+    }
+
+    m
+}
+
+/// True if the end releases remove all bending stiffness about the local z axis
+fn is_bending_released_about_z(frame: &FrameElement) -> bool {
+    let start: &FrameEndReleases = &(frame.start_releases);
+    let end: &FrameEndReleases = &(frame.end_releases);
+
+    let (start_moment, end_moment) = (
+        start.Mz == FrameEndRelease::Free,
+        end.Mz == FrameEndRelease::Free,
+    );
+
+    (start_moment && (end_moment || start.Vy == FrameEndRelease::Free))
+        || (end_moment && end.Vy == FrameEndRelease::Free)
+}
+
+/// True if the end releases remove all bending stiffness about the local y axis
+fn is_bending_released_about_y(frame: &FrameElement) -> bool {
+    let start: &FrameEndReleases = &(frame.start_releases);
+    let end: &FrameEndReleases = &(frame.end_releases);
+
+    let (start_moment, end_moment) = (
+        start.My == FrameEndRelease::Free,
+        end.My == FrameEndRelease::Free,
+    );
+
+    (start_moment && (end_moment || start.Vz == FrameEndRelease::Free))
+        || (end_moment && end.Vz == FrameEndRelease::Free)
+}
+
 /// Applies end releases if they occur
 fn apply_end_releases(m: &mut MatrixN<f64, U12>, frame: &FrameElement) {
     let start: &FrameEndReleases = &(frame.start_releases);
@@ -146,12 +559,13 @@ fn apply_end_releases(m: &mut MatrixN<f64, U12>, frame: &FrameElement) {
         m[(9, 9)] = 0.;
     }
 
-    // TODO Figure out this logic... there's moment and shear interaction.
-    //      If it can't transmit shear through both ends, isn't that also effectively a release?
-    if (start.Mz == FrameEndRelease::Free && end.Mz == FrameEndRelease::Free)
-        || (start.Mz == FrameEndRelease::Free && start.Vy == FrameEndRelease::Free)
-        || (end.Mz == FrameEndRelease::Free && end.Vy == FrameEndRelease::Free)
-    {
+    // Releases leaving the member no bending stiffness would make the condensation singular,
+    // unless a foundation still supports the member
+    let (ky, kz) = match &frame.foundation {
+        Some(x) => (x.ky, x.kz),
+        None => (0., 0.),
+    };
+    if is_bending_released_about_z(frame) && ky == 0. {
         m[(1, 1)] = 0.;
         m[(1, 5)] = 0.;
         m[(1, 7)] = 0.;
@@ -171,22 +585,12 @@ fn apply_end_releases(m: &mut MatrixN<f64, U12>, frame: &FrameElement) {
         m[(11, 5)] = 0.;
         m[(11, 7)] = 0.;
         m[(11, 11)] = 0.;
-    } else if start.Mz == FrameEndRelease::Free {
-        unimplemented!();
-    } else if end.Mz == FrameEndRelease::Free {
-        unimplemented!();
-    } else if start.Vy == FrameEndRelease::Free {
-        unimplemented!();
-    } else if end.Vy == FrameEndRelease::Free {
-        unimplemented!();
+    } else {
+        let released = [(&start.Vy, 1), (&start.Mz, 5), (&end.Vy, 7), (&end.Mz, 11)];
+        condense_released_dofs(m, &released);
     }
 
-    // TODO Figure out this logic... there's moment and shear interaction.
-    //      If it can't transmit shear through both ends, isn't that also effectively a release?
-    if (start.My == FrameEndRelease::Free && end.My == FrameEndRelease::Free)
-        || (start.My == FrameEndRelease::Free && start.Vz == FrameEndRelease::Free)
-        || (end.My == FrameEndRelease::Free && end.Vz == FrameEndRelease::Free)
-    {
+    if is_bending_released_about_y(frame) && kz == 0. {
         m[(2, 2)] = 0.;
         m[(2, 8)] = 0.;
         m[(8, 2)] = 0.;
@@ -206,15 +610,110 @@ fn apply_end_releases(m: &mut MatrixN<f64, U12>, frame: &FrameElement) {
         m[(4, 10)] = 0.;
         m[(10, 4)] = 0.;
         m[(10, 10)] = 0.;
-    } else if start.My == FrameEndRelease::Free {
-        unimplemented!();
-    } else if end.My == FrameEndRelease::Free {
-        unimplemented!();
-    } else if start.Vz == FrameEndRelease::Free {
-        unimplemented!();
-    } else if end.Vz == FrameEndRelease::Free {
-        unimplemented!();
+    } else {
+        let released = [(&start.Vz, 2), (&start.My, 4), (&end.Vz, 8), (&end.My, 10)];
+        condense_released_dofs(m, &released);
+    }
+}
+
+/// Statically condenses each released degree of freedom out of the stiffness in turn, so the
+/// member carries no force in the released directions whatever the beam theory.
+fn condense_released_dofs(m: &mut MatrixN<f64, U12>, released: &[(&FrameEndRelease, usize)]) {
+    let initial = m.diagonal();
+    for (release, i) in released.iter() {
+        if **release != FrameEndRelease::Free {
+            continue;
+        }
+        let i = *i;
+        let pivot = m[(i, i)];
+        if pivot.abs() > RELEASED_PIVOT_TOLERANCE * initial[i].abs() {
+            for r in (0..12).filter(|x| *x != i) {
+                for c in (0..12).filter(|x| *x != i) {
+                    m[(r, c)] -= m[(r, i)] * m[(i, c)] / pivot;
+                }
+            }
+        }
+        for j in 0..12 {
+            m[(i, j)] = 0.;
+            m[(j, i)] = 0.;
+        }
+    }
+}
+
+/// Returns the transformation `T` from local displacements at the nodes to local displacements
+/// at the ends of the flexible member, moving each end rigidly through its offset.
+pub fn frame_end_offset_transform(element: &FrameElement) -> MatrixN<f64, U12> {
+    let offsets = &element.end_offsets;
+    let mut transform = MatrixN::<f64, U12>::identity();
+
+    for (i, offset) in [offsets.start, offsets.end].iter().enumerate() {
+        let a = local_offset(element, offset) * offsets.rigid_zone_factor;
+        let n = 6 * i;
+        transform[(n, n + 4)] = a.z;
+        transform[(n, n + 5)] = -a.y;
+        transform[(n + 1, n + 3)] = -a.z;
+        transform[(n + 1, n + 5)] = a.x;
+        transform[(n + 2, n + 3)] = a.y;
+        transform[(n + 2, n + 4)] = -a.x;
+    }
+
+    transform
+}
+
+/// Returns the local end forces acting at the faces of the supports given those acting at the
+/// nodes, moving each end moment through the full offset.
+pub fn frame_end_forces_at_faces(
+    element: &FrameElement,
+    node_forces: &VectorN<f64, U12>,
+) -> VectorN<f64, U12> {
+    move_end_forces(element, node_forces, 1.)
+}
+
+/// Returns the local end forces acting at the ends of the flexible member given those acting at
+/// the nodes, moving each end moment through the rigid part of the offset.
+pub fn frame_end_forces_at_flexible_ends(
+    element: &FrameElement,
+    node_forces: &VectorN<f64, U12>,
+) -> VectorN<f64, U12> {
+    move_end_forces(element, node_forces, element.end_offsets.rigid_zone_factor)
+}
+
+/// Moves the end forces from the nodes through the given fraction of each offset
+fn move_end_forces(
+    element: &FrameElement,
+    node_forces: &VectorN<f64, U12>,
+    fraction: f64,
+) -> VectorN<f64, U12> {
+    let offsets = &element.end_offsets;
+    let mut forces = *node_forces;
+
+    for (i, offset) in [offsets.start, offsets.end].iter().enumerate() {
+        let e = local_offset(element, offset) * fraction;
+        let n = 6 * i;
+        let force = Vector3::new(forces[n], forces[n + 1], forces[n + 2]);
+        let moment = e.cross(&force);
+        for j in 0..3 {
+            forces[n + 3 + j] -= moment[j];
+        }
     }
+
+    forces
+}
+
+/// Returns the components of a world offset along the local axes of the frame
+fn local_offset(element: &FrameElement, offset: &Vector3<f64>) -> Vector3<f64> {
+    let axes = &element.geometry.local_axes;
+    Vector3::from_iterator((0..3).map(|i| axes.column(i).normalize().dot(offset)))
+}
+
+/// Moves the stiffness of the flexible member to the nodes as `Tᵀ m T` if the frame has rigid
+/// end zones
+fn apply_end_offsets(m: &mut MatrixN<f64, U12>, frame: &FrameElement) {
+    if frame.end_offsets.is_none() {
+        return;
+    }
+    let transform = frame_end_offset_transform(frame);
+    *m = transform.transpose() * *m * transform;
 }
 
 fn merge_single_action_into_complete_2by2(
@@ -247,6 +746,19 @@ fn merge_single_action_into_complete_4by4(
     }
 }
 
+fn add_single_action_into_complete_4by4(
+    single: &SingleActionStiffnessMatrix4by4,
+    complete: &mut MatrixN<f64, U12>,
+) {
+    let map = single.index_map;
+    let source = single.stiffness_matrix;
+    for i in 0..4 {
+        for j in 0..4 {
+            complete[(map[i], map[j])] += source[(i, j)];
+        }
+    }
+}
+
 struct SingleActionStiffnessMatrix2by2 {
     index_map: Vector2<usize>,
     stiffness_matrix: Matrix2<f64>,
@@ -290,6 +802,247 @@ mod tests {
         }
     }
 
+    #[test]
+    fn consistent_and_lumped_mass_conserve_total_mass() {
+        let frame = FrameElement {
+            id: "mass".to_string(),
+            start_node_id: "mass_a".to_string(),
+            end_node_id: "mass_b".to_string(),
+            start_releases: FrameEndReleases::fully_fixed(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry {
+                cross_section: CrossSection {
+                    A: 2.,
+                    Avy: 0.,
+                    Avz: 0.,
+                    J: 1.,
+                    Iy: 1.,
+                    Iz: 1.,
+                },
+                local_axes: Matrix3::identity(),
+                taper: None,
+            },
+            material: IsotropicMaterial::new(200., 0.3).with_density(3.),
+            end_offsets: FrameEndOffsets::none(),
+            beam_theory: BeamTheory::EulerBernoulli,
+            foundation: None,
+        };
+        add_node(Node {
+            id: "mass_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "mass_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(5., 0., 0.),
+            local_axes: None,
+        });
+
+        let consistent = frame_element_consistent_mass_matrix(&frame);
+        let lumped = frame_element_lumped_mass_matrix(&frame);
+        assert_matrix_symmetric_12by12(&consistent);
+
+        // A rigid translation along each local axis mobilizes the full mass rho A L
+        for direction in 0..3 {
+            let mut rigid = VectorN::<f64, U12>::zeros();
+            rigid[direction] = 1.;
+            rigid[direction + 6] = 1.;
+            let expected = 3. * 2. * 5.;
+            assert_relative_eq!(
+                (rigid.transpose() * consistent * rigid)[(0, 0)],
+                expected,
+                max_relative = 1e-12
+            );
+            assert_relative_eq!(
+                (rigid.transpose() * lumped * rigid)[(0, 0)],
+                expected,
+                max_relative = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn rigid_end_zone_shortens_cantilever_and_moves_moment_to_face() {
+        let local_axes = Matrix3::identity();
+        let frame = FrameElement {
+            id: "offset".to_string(),
+            start_node_id: "offset_a".to_string(),
+            end_node_id: "offset_b".to_string(),
+            start_releases: FrameEndReleases::fully_fixed(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry {
+                cross_section: CrossSection {
+                    A: 2.,
+                    Avy: 0.,
+                    Avz: 0.,
+                    J: 1.,
+                    Iy: 1.,
+                    Iz: 1.,
+                },
+                local_axes,
+                taper: None,
+            },
+            material: IsotropicMaterial::new(200., 0.3),
+            end_offsets: FrameEndOffsets::along_axis(&local_axes, 2., 0., 1.),
+            beam_theory: BeamTheory::EulerBernoulli,
+            foundation: None,
+        };
+        add_node(Node {
+            id: "offset_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "offset_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(10., 0., 0.),
+            local_axes: None,
+        });
+
+        let k = frame_element_stiffness_matrix(&frame);
+        assert_matrix_symmetric_12by12(&k);
+
+        // With the start fixed, a unit tip load deflects the tip as a cantilever 8 long
+        let tip = Matrix6::<f64>::from_fn(|i, j| k[(i + 6, j + 6)]);
+        let load = Vector6::new(0., 1., 0., 0., 0., 0.);
+        let deflection = tip.try_inverse().unwrap() * load;
+        assert_relative_eq!(
+            deflection[1],
+            8_f64.powi(3) / 3. / 200.,
+            max_relative = 1e-9
+        );
+
+        let mut displacements = VectorN::<f64, U12>::zeros();
+        for i in 0..6 {
+            displacements[i + 6] = deflection[i];
+        }
+        let node_forces = k * displacements;
+        let face_forces = frame_end_forces_at_faces(&frame, &node_forces);
+        assert_relative_eq!(node_forces[5], -10., max_relative = 1e-9);
+        assert_relative_eq!(face_forces[5], -8., max_relative = 1e-9);
+        assert_relative_eq!(face_forces[11], 0., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn moment_release_condenses_both_beam_theories() {
+        add_node(Node {
+            id: "release_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "release_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(4., 0., 0.),
+            local_axes: None,
+        });
+        let mut start_releases = FrameEndReleases::fully_fixed();
+        start_releases.Mz = FrameEndRelease::Free;
+        let frame = |shear_area: f64, beam_theory: BeamTheory| FrameElement {
+            id: "release".to_string(),
+            start_node_id: "release_a".to_string(),
+            end_node_id: "release_b".to_string(),
+            start_releases: start_releases.clone(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry {
+                cross_section: CrossSection {
+                    A: 2.,
+                    Avy: shear_area,
+                    Avz: 0.,
+                    J: 1.,
+                    Iy: 1.,
+                    Iz: 3.,
+                },
+                local_axes: Matrix3::identity(),
+                taper: None,
+            },
+            material: IsotropicMaterial::new(200., 0.25),
+            end_offsets: FrameEndOffsets::none(),
+            beam_theory,
+            foundation: None,
+        };
+
+        // A pinned start leaves the end rotational stiffness 3EI/L, less shear flexibility
+        let euler = frame_element_local_stiffness_matrix(&frame(0., BeamTheory::EulerBernoulli));
+        let unsheared = frame_element_local_stiffness_matrix(&frame(0., BeamTheory::Timoshenko));
+        let sheared = frame_element_local_stiffness_matrix(&frame(1.5, BeamTheory::Timoshenko));
+        assert_matrix_symmetric_12by12(&sheared);
+        assert_matrrices_equal_12by12(&euler, &unsheared);
+
+        let (ei, ga, l) = (200. * 3., 80. * 1.5, 4.);
+        assert_relative_eq!(euler[(11, 11)], 3. * ei / l, max_relative = 1e-9);
+        assert_relative_eq!(
+            sheared[(11, 11)],
+            3. * ei / l / (1. + 3. * ei / ga / l / l),
+            max_relative = 1e-9
+        );
+        for i in 0..12 {
+            assert_abs_diff_eq!(sheared[(5, i)], 0.);
+        }
+    }
+
+    #[test]
+    fn tapered_stiffness_matches_prismatic_and_closed_form_axial() {
+        add_node(Node {
+            id: "taper_a".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            coordinate: Point3::new(0., 0., 0.),
+            local_axes: None,
+        });
+        add_node(Node {
+            id: "taper_b".to_string(),
+            degrees_of_freedom: Vector6::from_iterator(6..12),
+            coordinate: Point3::new(0., 5., 0.),
+            local_axes: None,
+        });
+        let section = |area: f64| CrossSection {
+            A: area,
+            Avy: 0.,
+            Avz: 0.,
+            J: 1.,
+            Iy: 2.,
+            Iz: 3.,
+        };
+        let frame = |end_area: f64| FrameElement {
+            id: "taper".to_string(),
+            start_node_id: "taper_a".to_string(),
+            end_node_id: "taper_b".to_string(),
+            start_releases: FrameEndReleases::fully_fixed(),
+            end_releases: FrameEndReleases::fully_fixed(),
+            geometry: FrameGeometry {
+                cross_section: section(2.),
+                local_axes: Matrix3::from_columns(&[Vector3::y(), -Vector3::x(), Vector3::z()]),
+                taper: Some(FrameTaper {
+                    end_cross_section: section(end_area),
+                    variation: TaperVariation::Linear,
+                }),
+            },
+            material: IsotropicMaterial::new(200., 0.3),
+            end_offsets: FrameEndOffsets::none(),
+            beam_theory: BeamTheory::EulerBernoulli,
+            foundation: None,
+        };
+
+        let prismatic = frame(2.);
+        let tapered = frame_element_tapered_stiffness_matrix(&prismatic);
+        let expected = frame_element_stiffness_matrix(&prismatic);
+        for i in 0..12 {
+            for j in 0..12 {
+                assert_abs_diff_eq!(tapered[(i, j)], expected[(i, j)], epsilon = 1e-9);
+            }
+        }
+
+        // EA varying linearly from A0 to A1 gives k = E (A1 - A0) / (L ln(A1 / A0))
+        let tapered = frame_element_tapered_stiffness_matrix(&frame(6.));
+        let axial = 200. * (6. - 2.) / (5. * 3_f64.ln());
+        assert_relative_eq!(tapered[(0, 0)], axial, max_relative = 1e-6);
+        assert_relative_eq!(tapered[(0, 6)], -axial, max_relative = 1e-6);
+    }
+
     mod mcguire_matrix_structural_analysis_2nd_edition {
         use super::*;
 
@@ -299,16 +1052,19 @@ mod tests {
                 id: "a".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(0..6),
                 coordinate: Point3::new(0., 0., 0.),
+                local_axes: None,
             });
             add_node(Node {
                 id: "b".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(6..12),
                 coordinate: Point3::new(8e3, 0., 0.),
+                local_axes: None,
             });
             add_node(Node {
                 id: "c".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(12..18),
                 coordinate: Point3::new(13e3, 0., 0.),
+                local_axes: None,
             });
             add_frame_element(FrameElement {
                 id: "ab".to_string(),
@@ -326,8 +1082,12 @@ mod tests {
                         Iz: 200e6,
                     },
                     local_axes: Matrix3::identity(),
+                    taper: None,
                 },
                 material: material.clone(),
+                end_offsets: FrameEndOffsets::none(),
+                beam_theory: BeamTheory::EulerBernoulli,
+                foundation: None,
             });
             add_frame_element(FrameElement {
                 id: "bc".to_string(),
@@ -345,8 +1105,12 @@ mod tests {
                         Iz: 50e6,
                     },
                     local_axes: Matrix3::identity(),
+                    taper: None,
                 },
                 material: material.clone(),
+                end_offsets: FrameEndOffsets::none(),
+                beam_theory: BeamTheory::EulerBernoulli,
+                foundation: None,
             });
         }
 
@@ -619,16 +1383,19 @@ mod tests {
                 id: "a".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(0..6),
                 coordinate: Point3::new(0., 0., 0.),
+                local_axes: None,
             });
             add_node(Node {
                 id: "b".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(6..12),
                 coordinate: Point3::new(8e3, 0., 0.),
+                local_axes: None,
             });
             add_node(Node {
                 id: "c".to_string(),
                 degrees_of_freedom: Vector6::from_iterator(12..18),
                 coordinate: Point3::new(16e3, 0., 0.),
+                local_axes: None,
             });
             add_frame_element(FrameElement {
                 id: "ab".to_string(),
@@ -646,8 +1413,12 @@ mod tests {
                         Iz: 200e6,
                     },
                     local_axes: Matrix3::identity(),
+                    taper: None,
                 },
                 material: material.clone(),
+                end_offsets: FrameEndOffsets::none(),
+                beam_theory: BeamTheory::EulerBernoulli,
+                foundation: None,
             });
             add_frame_element(FrameElement {
                 id: "bc".to_string(),
@@ -665,8 +1436,12 @@ mod tests {
                         Iz: 200e6,
                     },
                     local_axes: Matrix3::identity(),
+                    taper: None,
                 },
                 material: material.clone(),
+                end_offsets: FrameEndOffsets::none(),
+                beam_theory: BeamTheory::EulerBernoulli,
+                foundation: None,
             });
             let member_ab = match get_frame_element_by_id("ab") {
                 Some(x) => x,
@@ -0,0 +1,524 @@
+use crate::models::material::IsotropicMaterial;
+use crate::models::membrane::MembraneElement;
+use na::{DMatrix, DVector, Matrix2, Matrix3, Point2, Point3, Vector2, Vector3};
+
+/// Area coordinates `(ξ, η)` and weights of the single-point rule for constant-strain triangles,
+/// whose weights sum to the area `1/2` of the natural triangle
+const CONSTANT_STRAIN_GAUSS_POINTS: [(f64, f64, f64); 1] = [(1. / 3., 1. / 3., 1. / 2.)];
+
+/// Area coordinates `(ξ, η)` and weights of the three-point rule, which integrates the stiffness
+/// of a straight-sided linear-strain triangle exactly
+const LINEAR_STRAIN_GAUSS_POINTS: [(f64, f64, f64); 3] = [
+    (1. / 6., 1. / 6., 1. / 6.),
+    (2. / 3., 1. / 6., 1. / 6.),
+    (1. / 6., 2. / 3., 1. / 6.),
+];
+
+/// Area coordinates `(ξ, η)` of the corners and then the midside nodes
+const NODE_NATURAL_COORDINATES: [(f64, f64); 6] = [
+    (0., 0.),
+    (1., 0.),
+    (0., 1.),
+    (0.5, 0.),
+    (0.5, 0.5),
+    (0., 0.5),
+];
+
+/// Strains and stresses at a point of a membrane element.
+pub struct MembraneSample {
+    /// World position of the point
+    pub location: Point3<f64>,
+    /// Strains `[εx, εy, γxy]` along the local axes
+    pub strain: Vector3<f64>,
+    /// Stresses `[σx, σy, τxy]` along the local axes
+    pub stress: Vector3<f64>,
+}
+
+/// In-plane strains and stresses of a membrane element.
+pub struct MembraneStress {
+    /// Local axes of the element in the columns, with x along the edge from the first to the
+    /// second node and z normal to its plane
+    pub local_axes: Matrix3<f64>,
+    /// Strains `[εx, εy, γxy]` along the local axes at the centroid
+    pub strain: Vector3<f64>,
+    /// Stresses `[σx, σy, τxy]` along the local axes at the centroid
+    pub stress: Vector3<f64>,
+    /// Samples at the integration points of the stiffness, which are the most accurate
+    pub gauss_points: Vec<MembraneSample>,
+    /// Samples at the nodes in the order of `node_ids`, where a constant-strain triangle repeats
+    /// its single value
+    pub nodes: Vec<MembraneSample>,
+}
+
+/// Returns the local axes of a membrane in the columns, with x along the edge from the first to
+/// the second corner and z normal to the plane of the first three corners.
+pub fn membrane_local_axes(corners: &[Point3<f64>]) -> Matrix3<f64> {
+    let (first, second) = (corners[1] - corners[0], corners[2] - corners[0]);
+    let x = first.normalize();
+    let z = first.cross(&second).normalize();
+    Matrix3::from_columns(&[x, z.cross(&x), z])
+}
+
+/// Returns the coordinates of the nodes along the local x and y axes, measured from the first
+/// node.
+pub fn membrane_local_coordinates(
+    coordinates: &[Point3<f64>],
+    local_axes: &Matrix3<f64>,
+) -> Vec<Point2<f64>> {
+    coordinates
+        .iter()
+        .map(|x| {
+            let offset = x - coordinates[0];
+            Point2::new(
+                offset.dot(&local_axes.column(0)),
+                offset.dot(&local_axes.column(1)),
+            )
+        })
+        .collect()
+}
+
+/// Returns the matrix `D` relating the stresses `[σx, σy, τxy]` to the strains `[εx, εy, γxy]`
+/// in plane stress.
+#[rustfmt::skip]
+#[allow(non_snake_case)]
+pub fn plane_stress_matrix(material: &IsotropicMaterial) -> Matrix3<f64> {
+    let (E, nu) = (material.E, material.nu);
+    E / (1. - nu * nu) * Matrix3::new(1., nu, 0.,
+                                      nu, 1., 0.,
+                                      0., 0., (1. - nu) / 2.)
+}
+
+/**
+ * Returns the Jacobian determinant and the strain-displacement matrix `B` of a membrane at the
+ * area coordinates `(ξ, η)`, where `B` gives the strains `[εx, εy, γxy]` from the local
+ * displacements `[u1, v1, u2, v2, ...]`. The determinant is twice the area of a constant-strain
+ * triangle.
+ *
+ * # Arguments
+ *
+ * `local` - local coordinates of the 3 corners, followed for a linear-strain triangle by the
+ * midside nodes of the edges 1-2, 2-3 and 3-1
+ * `xi` - area coordinate of the second corner
+ * `eta` - area coordinate of the third corner
+ */
+pub fn membrane_strain_matrix(local: &[Point2<f64>], xi: f64, eta: f64) -> (f64, DMatrix<f64>) {
+    let (_, natural) = shape_functions(local.len(), xi, eta);
+    let mut jacobian = Matrix2::<f64>::zeros();
+    for (i, x) in local.iter().enumerate() {
+        for row in 0..2 {
+            jacobian[(row, 0)] += natural[(row, i)] * x.x;
+            jacobian[(row, 1)] += natural[(row, i)] * x.y;
+        }
+    }
+    let determinant = jacobian.determinant();
+    let inverse = jacobian.try_inverse().unwrap_or_else(Matrix2::zeros);
+
+    let mut b = DMatrix::<f64>::zeros(3, 2 * local.len());
+    for i in 0..local.len() {
+        let derivative = inverse * Vector2::new(natural[(0, i)], natural[(1, i)]);
+        let (dx, dy) = (derivative.x, derivative.y);
+        b[(0, 2 * i)] = dx;
+        b[(1, 2 * i + 1)] = dy;
+        b[(2, 2 * i)] = dy;
+        b[(2, 2 * i + 1)] = dx;
+    }
+    (determinant, b)
+}
+
+/// Returns the stiffness matrix `∫ t Bᵀ D B dA` of a membrane over the local displacements
+/// `[u1, v1, u2, v2, ...]` of its nodes.
+pub fn membrane_local_stiffness_matrix(
+    element: &MembraneElement,
+    coordinates: &[Point3<f64>],
+) -> DMatrix<f64> {
+    let local = membrane_local_coordinates(coordinates, &membrane_local_axes(coordinates));
+    let d = plane_stress_matrix(&element.material);
+    let d = DMatrix::from_iterator(3, 3, d.iter().cloned());
+
+    let size = 2 * coordinates.len();
+    let mut stiffness = DMatrix::<f64>::zeros(size, size);
+    for (xi, eta, weight) in gauss_points(coordinates.len()).iter() {
+        let (determinant, b) = membrane_strain_matrix(&local, *xi, *eta);
+        stiffness += b.transpose() * &d * b * (element.thickness * determinant * weight);
+    }
+    stiffness
+}
+
+/// Returns the stiffness matrix of a membrane over the world translations
+/// `[Ux1, Uy1, Uz1, Ux2, ...]` of its nodes.
+pub fn membrane_world_stiffness_matrix(
+    element: &MembraneElement,
+    coordinates: &[Point3<f64>],
+) -> DMatrix<f64> {
+    let transform =
+        world_to_membrane_transform(&membrane_local_axes(coordinates), coordinates.len());
+    let local = membrane_local_stiffness_matrix(element, coordinates);
+    transform.transpose() * local * transform
+}
+
+/**
+ * Returns the strains and stresses of a membrane given the displacements of its nodes, at the
+ * centroid, the integration points and the nodes.
+ *
+ * # Arguments
+ *
+ * `element` - the membrane
+ * `coordinates` - world coordinates of the nodes, in the order of `element.node_ids`
+ * `displacements` - world translations `[Ux, Uy, Uz]` of the nodes in the same order
+ */
+pub fn membrane_stress(
+    element: &MembraneElement,
+    coordinates: &[Point3<f64>],
+    displacements: &[Vector3<f64>],
+) -> MembraneStress {
+    let local_axes = membrane_local_axes(coordinates);
+    let local = membrane_local_coordinates(coordinates, &local_axes);
+    let d = plane_stress_matrix(&element.material);
+
+    let world = DVector::from_iterator(
+        3 * displacements.len(),
+        displacements.iter().flat_map(|x| x.iter().cloned()),
+    );
+    let local_displacements = world_to_membrane_transform(&local_axes, coordinates.len()) * world;
+
+    let sample = |xi: f64, eta: f64| {
+        let (_, b) = membrane_strain_matrix(&local, xi, eta);
+        let strain = &b * &local_displacements;
+        let strain = Vector3::new(strain[0], strain[1], strain[2]);
+        let (values, _) = shape_functions(coordinates.len(), xi, eta);
+        let location = coordinates
+            .iter()
+            .zip(values.iter())
+            .fold(Point3::origin(), |sum, (x, n)| sum + x.coords * *n);
+        MembraneSample {
+            location,
+            strain,
+            stress: d * strain,
+        }
+    };
+
+    let centroid = sample(1. / 3., 1. / 3.);
+    MembraneStress {
+        local_axes,
+        strain: centroid.strain,
+        stress: centroid.stress,
+        gauss_points: gauss_points(coordinates.len())
+            .iter()
+            .map(|(xi, eta, _)| sample(*xi, *eta))
+            .collect(),
+        nodes: NODE_NATURAL_COORDINATES[..coordinates.len()]
+            .iter()
+            .map(|(xi, eta)| sample(*xi, *eta))
+            .collect(),
+    }
+}
+
+/**
+ * Returns the world forces on the nodes of a membrane that are equivalent to a uniform load
+ * along one of its edges, shared in proportion to the displacement functions of the edge. A
+ * straight edge of a linear-strain triangle takes 1/6, 2/3 and 1/6 of the load at its corner,
+ * midside and corner nodes.
+ *
+ * # Arguments
+ *
+ * `coordinates` - world coordinates of the nodes, in the order of the element's node ids
+ * `edge` - the edge from corner `edge` to the next corner counterclockwise, from 0 to 2
+ * `load` - world force per unit length `[Fx, Fy, Fz]` along the edge
+ */
+pub fn membrane_edge_forces(
+    coordinates: &[Point3<f64>],
+    edge: usize,
+    load: &Vector3<f64>,
+) -> Vec<(usize, Vector3<f64>)> {
+    let nodes = if coordinates.len() == 6 {
+        vec![edge, (edge + 1) % 3, edge + 3]
+    } else {
+        vec![edge, (edge + 1) % 3]
+    };
+
+    // Three-point Gauss-Legendre rule along the edge parameter s from -1 to 1
+    let root = (3. / 5f64).sqrt();
+    let rule = [(-root, 5. / 9.), (0., 8. / 9.), (root, 5. / 9.)];
+    let mut forces = vec![0.; nodes.len()];
+    for (s, weight) in rule.iter() {
+        let (values, slopes) = if nodes.len() == 3 {
+            (
+                vec![s * (s - 1.) / 2., s * (s + 1.) / 2., 1. - s * s],
+                vec![s - 0.5, s + 0.5, -2. * s],
+            )
+        } else {
+            (vec![(1. - s) / 2., (1. + s) / 2.], vec![-0.5, 0.5])
+        };
+        let tangent = nodes
+            .iter()
+            .zip(slopes.iter())
+            .fold(Vector3::zeros(), |sum, (i, slope)| {
+                sum + coordinates[*i].coords * *slope
+            });
+        for (force, value) in forces.iter_mut().zip(values.iter()) {
+            *force += value * tangent.norm() * weight;
+        }
+    }
+    nodes
+        .into_iter()
+        .zip(forces)
+        .map(|(i, share)| (i, load * share))
+        .collect()
+}
+
+/// Returns the integration rule for the stiffness of a membrane with the given number of nodes
+fn gauss_points(node_count: usize) -> &'static [(f64, f64, f64)] {
+    if node_count == 6 {
+        &LINEAR_STRAIN_GAUSS_POINTS
+    } else {
+        &CONSTANT_STRAIN_GAUSS_POINTS
+    }
+}
+
+/// Returns the shape functions of a triangle with 3 or 6 nodes at the area coordinates `(ξ, η)`,
+/// along with their derivatives with respect to `ξ` and `η` in the two rows of a matrix
+#[rustfmt::skip]
+fn shape_functions(node_count: usize, xi: f64, eta: f64) -> (DVector<f64>, DMatrix<f64>) {
+    let zeta = 1. - xi - eta;
+    if node_count == 6 {
+        (
+            DVector::from_vec(vec![zeta * (2. * zeta - 1.), xi * (2. * xi - 1.),
+                                   eta * (2. * eta - 1.), 4. * zeta * xi, 4. * xi * eta,
+                                   4. * eta * zeta]),
+            DMatrix::from_row_slice(2, 6, &[
+                1. - 4. * zeta, 4. * xi - 1., 0.,            4. * (zeta - xi), 4. * eta, -4. * eta,
+                1. - 4. * zeta, 0.,           4. * eta - 1., -4. * xi,         4. * xi,  4. * (zeta - eta),
+            ]),
+        )
+    } else {
+        (
+            DVector::from_vec(vec![zeta, xi, eta]),
+            DMatrix::from_row_slice(2, 3, &[
+                -1., 1., 0.,
+                -1., 0., 1.,
+            ]),
+        )
+    }
+}
+
+/// Returns the transformation from the world translations of the nodes to their displacements
+/// along the local x and y axes
+fn world_to_membrane_transform(local_axes: &Matrix3<f64>, node_count: usize) -> DMatrix<f64> {
+    let mut transform = DMatrix::<f64>::zeros(2 * node_count, 3 * node_count);
+    for node in 0..node_count {
+        for axis in 0..2 {
+            for j in 0..3 {
+                transform[(2 * node + axis, 3 * node + j)] = local_axes[(j, axis)];
+            }
+        }
+    }
+    transform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_strain_triangle_in_tilted_plane_recovers_uniform_stress() {
+        let element = MembraneElement {
+            id: "cst".to_string(),
+            node_ids: Vec::new(),
+            thickness: 0.5,
+            material: IsotropicMaterial::new(200., 0.25),
+        };
+        // A right triangle with legs 3 and 2 lying in a plane tilted about the world x axis
+        let (c, s) = (0.6, 0.8);
+        let axes = Matrix3::new(1., 0., 0., 0., c, -s, 0., s, c);
+        let origin = Point3::new(1., 2., 3.);
+        let coordinates: Vec<Point3<f64>> = [(0., 0.), (3., 0.), (0., 2.)]
+            .iter()
+            .map(|(x, y)| origin + axes * Vector3::new(*x, *y, 0.))
+            .collect();
+        let computed_axes = membrane_local_axes(&coordinates);
+        for i in 0..9 {
+            assert_abs_diff_eq!(computed_axes[i], axes[i], epsilon = 1e-12);
+        }
+
+        // Uniform strain plus a rigid rotation about the normal and a translation
+        let (ex, ey, gxy) = (1e-3, -2e-3, 4e-3);
+        let displacements: Vec<Vector3<f64>> = coordinates
+            .iter()
+            .map(|p| {
+                let local = axes.transpose() * (p - origin);
+                let (x, y) = (local.x, local.y);
+                let u = ex * x + gxy / 2. * y - 0.01 * y + 0.3;
+                let v = ey * y + gxy / 2. * x + 0.01 * x;
+                axes * Vector3::new(u, v, 0.) + Vector3::new(0.1, 0.2, 0.3)
+            })
+            .collect();
+        let result = membrane_stress(&element, &coordinates, &displacements);
+        let expected = [ex, ey, gxy];
+        for i in 0..3 {
+            assert_relative_eq!(result.strain[i], expected[i], max_relative = 1e-9);
+        }
+        assert_relative_eq!(
+            result.stress[0],
+            200. / (1. - 0.0625) * (ex + 0.25 * ey),
+            max_relative = 1e-9
+        );
+
+        // Rigid motions, including rotation out of the plane, need no nodal forces
+        let k = membrane_world_stiffness_matrix(&element, &coordinates);
+        let rotation = Vector3::new(0.3, -0.2, 0.5);
+        let rigid = DVector::from_iterator(
+            9,
+            coordinates.iter().flat_map(|p| {
+                let motion = rotation.cross(&(p - origin)) + Vector3::new(1., 2., 3.);
+                vec![motion.x, motion.y, motion.z]
+            }),
+        );
+        assert_abs_diff_eq!((k * rigid).amax(), 0., epsilon = 1e-10);
+    }
+
+    #[test]
+    fn linear_strain_triangle_passes_patch_test_and_recovers_linear_strain() {
+        let element = MembraneElement {
+            id: "lst".to_string(),
+            node_ids: Vec::new(),
+            thickness: 1.,
+            material: IsotropicMaterial::new(100., 0.3),
+        };
+        // Curved edges from midside nodes moved off the chords keep the linear patch exact
+        let distorted: Vec<Point3<f64>> = [
+            (0., 0.),
+            (4., 0.),
+            (1., 3.),
+            (2.1, 0.1),
+            (2.7, 1.9),
+            (0.4, 1.4),
+        ]
+        .iter()
+        .map(|(x, y)| Point3::new(*x, *y, 0.))
+        .collect();
+        let linear: Vec<Vector3<f64>> = distorted
+            .iter()
+            .map(|p| Vector3::new(2e-3 * p.x + 1e-3 * p.y + 0.1, -1e-3 * p.y, 0.))
+            .collect();
+        let result = membrane_stress(&element, &distorted, &linear);
+        for sample in result.gauss_points.iter().chain(result.nodes.iter()) {
+            let expected = [2e-3, -1e-3, 1e-3];
+            for i in 0..3 {
+                assert_abs_diff_eq!(sample.strain[i], expected[i], epsilon = 1e-12);
+            }
+        }
+
+        // A straight-sided element represents bending strains that vary linearly
+        let mut straight = distorted.clone();
+        for (mid, (a, b)) in [(3, (0, 1)), (4, (1, 2)), (5, (2, 0))].iter() {
+            straight[*mid] = na::center(&straight[*a], &straight[*b]);
+        }
+        let bending: Vec<Vector3<f64>> = straight
+            .iter()
+            .map(|p| Vector3::new(1e-3 * p.x * p.x, 0., 0.))
+            .collect();
+        let result = membrane_stress(&element, &straight, &bending);
+        assert_eq!(result.nodes.len(), 6);
+        assert_eq!(result.gauss_points.len(), 3);
+        for (sample, x) in result.nodes.iter().zip(straight.iter()) {
+            assert_abs_diff_eq!((sample.location - x).norm(), 0., epsilon = 1e-12);
+        }
+        for sample in result.gauss_points.iter().chain(result.nodes.iter()) {
+            assert_abs_diff_eq!(sample.strain[0], 2e-3 * sample.location.x, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn linear_strain_cantilever_matches_beam_theory_where_constant_strain_is_stiff() {
+        // A wall 10 long and 2 deep, clamped at x = 0 with a unit shear on its free end
+        let (L, h, nu, E) = (10., 2., 0.25, 1000.);
+        let element = MembraneElement {
+            id: "wall".to_string(),
+            node_ids: Vec::new(),
+            thickness: 1.,
+            material: IsotropicMaterial::new(E, nu),
+        };
+        let bending = L * L * L / (3. * E * h * h * h / 12.);
+        let shear = L / (5. / 6. * E / (2. * (1. + nu)) * h);
+
+        let tip_deflection = |node_count: usize, (columns, rows): (usize, usize)| {
+            // Corner nodes of the cells, with midside nodes between them for quadratic elements
+            let step = if node_count == 6 { 2 } else { 1 };
+            let (nx, ny) = (step * columns + 1, step * rows + 1);
+            let index = |i: usize, j: usize| j * nx + i;
+            let position = |i: usize, j: usize| {
+                Point3::new(
+                    L * i as f64 / (nx - 1) as f64,
+                    h * j as f64 / (ny - 1) as f64,
+                    0.,
+                )
+            };
+
+            let mut triangles = Vec::<Vec<usize>>::new();
+            for column in 0..columns {
+                for row in 0..rows {
+                    let (i, j) = (step * column, step * row);
+                    let (a, b, c, d) = ((i, j), (i + step, j), (i + step, j + step), (i, j + step));
+                    let mid = |p: (usize, usize), q: (usize, usize)| {
+                        index((p.0 + q.0) / 2, (p.1 + q.1) / 2)
+                    };
+                    for (p, q, r) in [(a, b, c), (a, c, d)].iter() {
+                        let mut nodes = vec![index(p.0, p.1), index(q.0, q.1), index(r.0, r.1)];
+                        if node_count == 6 {
+                            nodes.extend(vec![mid(*p, *q), mid(*q, *r), mid(*r, *p)]);
+                        }
+                        triangles.push(nodes);
+                    }
+                }
+            }
+
+            // In-plane degrees of freedom [u, v] of every node
+            let size = 2 * nx * ny;
+            let mut stiffness = DMatrix::<f64>::zeros(size, size);
+            let mut forces = DVector::<f64>::zeros(size);
+            for nodes in triangles.iter() {
+                let coordinates: Vec<Point3<f64>> =
+                    nodes.iter().map(|n| position(n % nx, n / nx)).collect();
+                let k = membrane_world_stiffness_matrix(&element, &coordinates);
+                for (a, row) in nodes.iter().enumerate() {
+                    for (b, column) in nodes.iter().enumerate() {
+                        for i in 0..2 {
+                            for j in 0..2 {
+                                stiffness[(2 * row + i, 2 * column + j)] +=
+                                    k[(3 * a + i, 3 * b + j)];
+                            }
+                        }
+                    }
+                }
+                // The lower triangle of each cell owns its right edge
+                if coordinates[1].x == L && coordinates[2].x == L {
+                    let load = Vector3::new(0., -1. / h, 0.);
+                    for (a, force) in membrane_edge_forces(&coordinates, 1, &load) {
+                        forces[2 * nodes[a] + 1] += force.y;
+                    }
+                }
+            }
+            assert_relative_eq!(forces.sum(), -1., max_relative = 1e-12);
+
+            let free: Vec<usize> = (0..size).filter(|x| (x / 2) % nx != 0).collect();
+            let reduced =
+                DMatrix::from_fn(free.len(), free.len(), |i, j| stiffness[(free[i], free[j])]);
+            let load = DVector::from_fn(free.len(), |i, _| forces[free[i]]);
+            let solution = reduced.lu().solve(&load).unwrap();
+            let tip: Vec<f64> = (0..ny)
+                .map(|j| {
+                    let dof = 2 * index(nx - 1, j) + 1;
+                    solution[free.iter().position(|x| *x == dof).unwrap()]
+                })
+                .collect();
+            -tip.iter().sum::<f64>() / tip.len() as f64
+        };
+
+        let expected = bending + shear;
+        let quadratic = tip_deflection(6, (4, 1));
+        assert_relative_eq!(quadratic, expected, max_relative = 0.03);
+        let linear = tip_deflection(3, (4, 1));
+        assert!(linear < 0.5 * expected);
+    }
+}
@@ -1 +1,4 @@
+pub mod cable;
 pub mod frame;
+pub mod membrane;
+pub mod shell;
@@ -0,0 +1,555 @@
+use crate::elements::membrane::plane_stress_matrix;
+use crate::models::shell::ShellElement;
+use na::{DMatrix, DVector, Matrix2, Matrix3, Point2, Point3, Vector2, Vector3, Vector6};
+
+/// Rotations about the normal are resisted by a fictitious stiffness of this fraction of
+/// `G t A`, which only acts between the nodes of a shell and so leaves its rigid rotation free.
+const DRILLING_STIFFNESS_FACTOR: f64 = 1e-3;
+
+/// Shear correction factor of a homogeneous plate
+const SHEAR_CORRECTION_FACTOR: f64 = 5. / 6.;
+
+/// Natural coordinates `(ξ, η)` of the corners, counterclockwise
+const CORNERS: [(f64, f64); 4] = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)];
+
+/// Natural coordinates of the 2x2 Gauss points, each with unit weight
+const GAUSS_POINT: f64 = 0.577_350_269_189_625_8;
+
+/// Local degrees of freedom `[u, v, w, θx, θy, θz]` of each node
+const NODE_DOFS: usize = 6;
+
+/// Force and moment resultants per unit length at the centroid of a shell, along its local
+/// axes.
+pub struct ShellResultants {
+    /// Local axes of the element in the columns, with x along the edge from the first to the
+    /// second node and z normal to its mean plane
+    pub local_axes: Matrix3<f64>,
+    /// Membrane forces `[Nxx, Nyy, Nxy]`
+    pub forces: Vector3<f64>,
+    /// Bending and twisting moments `[Mxx, Myy, Mxy]`, where `Mxx` is the moment of the `σx`
+    /// stresses and is positive with tension on the +z face
+    pub moments: Vector3<f64>,
+    /// Transverse shears `[Qx, Qy]` on the faces normal to x and y, positive along +z
+    pub shears: Vector2<f64>,
+}
+
+/// The rigidities relating the resultants of a shell to its strains
+struct ShellRigidities {
+    /// Relates `[Nxx, Nyy, Nxy]` to the membrane strains
+    membrane: DMatrix<f64>,
+    /// Relates `[Mxx, Myy, Mxy]` to the curvatures
+    bending: DMatrix<f64>,
+    /// Relates `[Qx, Qy]` to the transverse shear strains
+    shear: DMatrix<f64>,
+}
+
+impl ShellRigidities {
+    fn new(element: &ShellElement) -> ShellRigidities {
+        let t = element.thickness;
+        let plane_stress = plane_stress_matrix(&element.material);
+        let plane_stress = DMatrix::from_iterator(3, 3, plane_stress.iter().cloned());
+        ShellRigidities {
+            membrane: &plane_stress * t,
+            bending: &plane_stress * (t * t * t / 12.),
+            shear: DMatrix::identity(2, 2) * (SHEAR_CORRECTION_FACTOR * element.material.G * t),
+        }
+    }
+}
+
+/// The local axes of a shell and the coordinates of its corners projected onto its mean plane,
+/// measured from their average
+struct ShellGeometry {
+    local_axes: Matrix3<f64>,
+    corners: Vec<Point2<f64>>,
+    /// Heights of the nodes of a warped shell above their projected corners
+    heights: Vec<f64>,
+}
+
+impl ShellGeometry {
+    fn new(coordinates: &[Point3<f64>]) -> ShellGeometry {
+        let local_axes = shell_local_axes(coordinates);
+        let center = coordinates
+            .iter()
+            .fold(Vector3::zeros(), |sum, x| sum + x.coords)
+            / 4.;
+        let offsets: Vec<Vector3<f64>> = coordinates.iter().map(|x| x.coords - center).collect();
+        ShellGeometry {
+            local_axes,
+            corners: offsets
+                .iter()
+                .map(|x| Point2::new(x.dot(&local_axes.column(0)), x.dot(&local_axes.column(1))))
+                .collect(),
+            heights: offsets
+                .iter()
+                .map(|x| x.dot(&local_axes.column(2)))
+                .collect(),
+        }
+    }
+
+    /// Returns the transformation from the local displacements of the nodes to those of the
+    /// projected corners, which are rigidly offset from the nodes of a warped shell so that the
+    /// element stays in equilibrium
+    fn warping_transform(&self) -> DMatrix<f64> {
+        let mut transform = DMatrix::<f64>::identity(4 * NODE_DOFS, 4 * NODE_DOFS);
+        for (i, height) in self.heights.iter().enumerate() {
+            transform[(NODE_DOFS * i, NODE_DOFS * i + 4)] = -height;
+            transform[(NODE_DOFS * i + 1, NODE_DOFS * i + 3)] = *height;
+        }
+        transform
+    }
+
+    /// Returns the shape functions and their derivatives with respect to `ξ` and `η` in the rows
+    /// of a matrix
+    fn shape_functions(xi: f64, eta: f64) -> ([f64; 4], DMatrix<f64>) {
+        let mut values = [0.; 4];
+        let mut natural = DMatrix::<f64>::zeros(2, 4);
+        for (i, (a, b)) in CORNERS.iter().enumerate() {
+            values[i] = (1. + a * xi) * (1. + b * eta) / 4.;
+            natural[(0, i)] = a * (1. + b * eta) / 4.;
+            natural[(1, i)] = b * (1. + a * xi) / 4.;
+        }
+        (values, natural)
+    }
+
+    /// Returns the Jacobian `[x,ξ y,ξ; x,η y,η]` at the natural coordinates
+    fn jacobian(&self, xi: f64, eta: f64) -> Matrix2<f64> {
+        let (_, natural) = ShellGeometry::shape_functions(xi, eta);
+        let mut jacobian = Matrix2::<f64>::zeros();
+        for (i, x) in self.corners.iter().enumerate() {
+            for row in 0..2 {
+                jacobian[(row, 0)] += natural[(row, i)] * x.x;
+                jacobian[(row, 1)] += natural[(row, i)] * x.y;
+            }
+        }
+        jacobian
+    }
+
+    /// Returns the derivatives of the shape functions with respect to x and y in the rows of a
+    /// matrix
+    fn cartesian_derivatives(&self, xi: f64, eta: f64) -> DMatrix<f64> {
+        let (_, natural) = ShellGeometry::shape_functions(xi, eta);
+        let inverse = self
+            .jacobian(xi, eta)
+            .try_inverse()
+            .unwrap_or_else(Matrix2::zeros);
+        let inverse = DMatrix::from_iterator(2, 2, inverse.iter().cloned());
+        inverse * natural
+    }
+
+    /// Returns the matrix giving the membrane strains `[εx, εy, γxy]` from the local
+    /// displacements
+    fn membrane_strain_matrix(&self, xi: f64, eta: f64) -> DMatrix<f64> {
+        let derivatives = self.cartesian_derivatives(xi, eta);
+        let mut b = DMatrix::<f64>::zeros(3, 4 * NODE_DOFS);
+        for i in 0..4 {
+            let (dx, dy) = (derivatives[(0, i)], derivatives[(1, i)]);
+            b[(0, NODE_DOFS * i)] = dx;
+            b[(1, NODE_DOFS * i + 1)] = dy;
+            b[(2, NODE_DOFS * i)] = dy;
+            b[(2, NODE_DOFS * i + 1)] = dx;
+        }
+        b
+    }
+
+    /**
+     * Returns the matrix giving the membrane strains from the amplitudes `[u₁, v₁, u₂, v₂]` of
+     * the incompatible modes `1 − ξ²` and `1 − η²`, which let the element bend in its plane.
+     * Following Taylor, Beresford and Wilson (1976) the derivatives are taken with the Jacobian
+     * at the centroid and scaled by the ratio of the Jacobian determinants, so that distorted
+     * elements still pass the patch test.
+     */
+    fn incompatible_strain_matrix(&self, xi: f64, eta: f64) -> DMatrix<f64> {
+        let center = self.jacobian(0., 0.);
+        let scale = center.determinant() / self.jacobian(xi, eta).determinant();
+        let inverse = center.try_inverse().unwrap_or_else(Matrix2::zeros);
+        let modes = [
+            inverse * Vector2::new(-2. * xi, 0.),
+            inverse * Vector2::new(0., -2. * eta),
+        ];
+        let mut b = DMatrix::<f64>::zeros(3, 4);
+        for (i, derivative) in modes.iter().enumerate() {
+            let (dx, dy) = (derivative.x * scale, derivative.y * scale);
+            b[(0, 2 * i)] = dx;
+            b[(1, 2 * i + 1)] = dy;
+            b[(2, 2 * i)] = dy;
+            b[(2, 2 * i + 1)] = dx;
+        }
+        b
+    }
+
+    /// Returns the matrix giving the curvatures `[κx, κy, κxy]` from the local displacements,
+    /// where the rotations turn the normal so that `u = z θy` and `v = −z θx`
+    fn bending_strain_matrix(&self, xi: f64, eta: f64) -> DMatrix<f64> {
+        let derivatives = self.cartesian_derivatives(xi, eta);
+        let mut b = DMatrix::<f64>::zeros(3, 4 * NODE_DOFS);
+        for i in 0..4 {
+            let (dx, dy) = (derivatives[(0, i)], derivatives[(1, i)]);
+            b[(0, NODE_DOFS * i + 4)] = dx;
+            b[(1, NODE_DOFS * i + 3)] = -dy;
+            b[(2, NODE_DOFS * i + 3)] = -dx;
+            b[(2, NODE_DOFS * i + 4)] = dy;
+        }
+        b
+    }
+
+    /// Returns the row giving the covariant transverse shear strain along `ξ` (`direction` 0)
+    /// or `η` (`direction` 1) at the natural coordinates
+    fn covariant_shear_row(&self, xi: f64, eta: f64, direction: usize) -> DMatrix<f64> {
+        let (values, natural) = ShellGeometry::shape_functions(xi, eta);
+        let jacobian = self.jacobian(xi, eta);
+        let (dx, dy) = (jacobian[(direction, 0)], jacobian[(direction, 1)]);
+        let mut row = DMatrix::<f64>::zeros(1, 4 * NODE_DOFS);
+        for i in 0..4 {
+            row[(0, NODE_DOFS * i + 2)] = natural[(direction, i)];
+            row[(0, NODE_DOFS * i + 3)] = -values[i] * dy;
+            row[(0, NODE_DOFS * i + 4)] = values[i] * dx;
+        }
+        row
+    }
+
+    /// Returns the matrix giving the transverse shear strains `[γxz, γyz]` from the local
+    /// displacements. As in the MITC4 element of Bathe and Dvorkin (1985) the covariant strains
+    /// are interpolated from the midpoints of the edges, which keeps thin plates from locking.
+    fn shear_strain_matrix(&self, xi: f64, eta: f64) -> DMatrix<f64> {
+        let along_xi = self.covariant_shear_row(0., -1., 0) * ((1. - eta) / 2.)
+            + self.covariant_shear_row(0., 1., 0) * ((1. + eta) / 2.);
+        let along_eta = self.covariant_shear_row(-1., 0., 1) * ((1. - xi) / 2.)
+            + self.covariant_shear_row(1., 0., 1) * ((1. + xi) / 2.);
+        let mut covariant = DMatrix::<f64>::zeros(2, 4 * NODE_DOFS);
+        covariant.set_row(0, &along_xi.row(0));
+        covariant.set_row(1, &along_eta.row(0));
+
+        let inverse = self
+            .jacobian(xi, eta)
+            .try_inverse()
+            .unwrap_or_else(Matrix2::zeros);
+        DMatrix::from_iterator(2, 2, inverse.iter().cloned()) * covariant
+    }
+
+    /// Returns the area of the flat element
+    fn area(&self) -> f64 {
+        let c = &self.corners;
+        ((c[2] - c[0]).perp(&(c[3] - c[1]))) / 2.
+    }
+}
+
+/// Returns the local axes of a shell in the columns, with z normal to the plane of its
+/// diagonals and x along the projection of the edge from the first to the second corner.
+pub fn shell_local_axes(corners: &[Point3<f64>]) -> Matrix3<f64> {
+    let normal = (corners[2] - corners[0])
+        .cross(&(corners[3] - corners[1]))
+        .normalize();
+    let edge = corners[1] - corners[0];
+    let x = (edge - normal * edge.dot(&normal)).normalize();
+    Matrix3::from_columns(&[x, normal.cross(&x), normal])
+}
+
+/// Returns the Jacobian determinant of a shell at each of its corners, which are all positive
+/// for a convex element numbered counterclockwise about its normal.
+pub fn shell_corner_jacobians(coordinates: &[Point3<f64>]) -> Vec<f64> {
+    let geometry = ShellGeometry::new(coordinates);
+    CORNERS
+        .iter()
+        .map(|(xi, eta)| geometry.jacobian(*xi, *eta).determinant())
+        .collect()
+}
+
+/**
+ * Returns the stiffness matrix of a shell over the local displacements `[u, v, w, θx, θy, θz]`
+ * of its nodes, along the local axes of the element.
+ *
+ * The membrane is a bilinear quadrilateral enhanced with condensed incompatible modes, and the
+ * plate is an MITC4 Reissner-Mindlin element, both integrated with 2x2 Gauss points. A warped
+ * element is projected onto its mean plane, with its corners linked rigidly to its nodes.
+ *
+ * # Arguments
+ *
+ * `element` - the shell
+ * `coordinates` - world coordinates of the nodes, in the order of `element.node_ids`
+ */
+pub fn shell_local_stiffness_matrix(
+    element: &ShellElement,
+    coordinates: &[Point3<f64>],
+) -> DMatrix<f64> {
+    let geometry = ShellGeometry::new(coordinates);
+    let rigidities = ShellRigidities::new(element);
+    let size = 4 * NODE_DOFS;
+    let mut stiffness = DMatrix::<f64>::zeros(size, size);
+    let mut coupling = DMatrix::<f64>::zeros(size, 4);
+    let mut incompatible = DMatrix::<f64>::zeros(4, 4);
+
+    for xi in [-GAUSS_POINT, GAUSS_POINT].iter() {
+        for eta in [-GAUSS_POINT, GAUSS_POINT].iter() {
+            let determinant = geometry.jacobian(*xi, *eta).determinant();
+            let membrane = geometry.membrane_strain_matrix(*xi, *eta);
+            let modes = geometry.incompatible_strain_matrix(*xi, *eta);
+            let bending = geometry.bending_strain_matrix(*xi, *eta);
+            let shear = geometry.shear_strain_matrix(*xi, *eta);
+
+            stiffness += (membrane.transpose() * &rigidities.membrane * &membrane
+                + bending.transpose() * &rigidities.bending * bending
+                + shear.transpose() * &rigidities.shear * shear)
+                * determinant;
+            coupling += membrane.transpose() * &rigidities.membrane * &modes * determinant;
+            incompatible += modes.transpose() * &rigidities.membrane * &modes * determinant;
+        }
+    }
+    if let Some(inverse) = incompatible.try_inverse() {
+        stiffness -= &coupling * inverse * coupling.transpose();
+    }
+
+    let drilling =
+        DRILLING_STIFFNESS_FACTOR * element.material.G * element.thickness * geometry.area();
+    for i in 0..4 {
+        for j in 0..4 {
+            let share = if i == j { 0.75 } else { -0.25 };
+            stiffness[(NODE_DOFS * i + 5, NODE_DOFS * j + 5)] += drilling * share;
+        }
+    }
+    let warping = geometry.warping_transform();
+    warping.transpose() * stiffness * warping
+}
+
+/// Returns the stiffness matrix of a shell over the world displacements
+/// `[Ux, Uy, Uz, Rx, Ry, Rz]` of its nodes.
+pub fn shell_world_stiffness_matrix(
+    element: &ShellElement,
+    coordinates: &[Point3<f64>],
+) -> DMatrix<f64> {
+    let transform = world_to_shell_transform(&shell_local_axes(coordinates));
+    let local = shell_local_stiffness_matrix(element, coordinates);
+    transform.transpose() * local * transform
+}
+
+/**
+ * Returns the force and moment resultants per unit length at the centroid of a shell given the
+ * displacements of its nodes.
+ *
+ * # Arguments
+ *
+ * `element` - the shell
+ * `coordinates` - world coordinates of the nodes, in the order of `element.node_ids`
+ * `displacements` - world displacements `[Ux, Uy, Uz, Rx, Ry, Rz]` of the nodes in the same
+ * order
+ */
+pub fn shell_resultants(
+    element: &ShellElement,
+    coordinates: &[Point3<f64>],
+    displacements: &[Vector6<f64>],
+) -> ShellResultants {
+    let geometry = ShellGeometry::new(coordinates);
+    let rigidities = ShellRigidities::new(element);
+    let world = DVector::from_iterator(
+        4 * NODE_DOFS,
+        displacements.iter().flat_map(|x| x.iter().cloned()),
+    );
+    let local =
+        geometry.warping_transform() * world_to_shell_transform(&geometry.local_axes) * world;
+
+    // The incompatible modes have no strain at the centroid
+    let forces = &rigidities.membrane * geometry.membrane_strain_matrix(0., 0.) * &local;
+    let moments = &rigidities.bending * geometry.bending_strain_matrix(0., 0.) * &local;
+    let shears = &rigidities.shear * geometry.shear_strain_matrix(0., 0.) * &local;
+    ShellResultants {
+        local_axes: geometry.local_axes,
+        forces: Vector3::new(forces[0], forces[1], forces[2]),
+        moments: Vector3::new(moments[0], moments[1], moments[2]),
+        shears: Vector2::new(shears[0], shears[1]),
+    }
+}
+
+/// Returns the transformation from the world displacements of the nodes to their displacements
+/// along the local axes of the shell
+fn world_to_shell_transform(local_axes: &Matrix3<f64>) -> DMatrix<f64> {
+    let mut transform = DMatrix::<f64>::zeros(4 * NODE_DOFS, 4 * NODE_DOFS);
+    for block in 0..8 {
+        for i in 0..3 {
+            for j in 0..3 {
+                transform[(3 * block + i, 3 * block + j)] = local_axes[(j, i)];
+            }
+        }
+    }
+    transform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::material::IsotropicMaterial;
+
+    /// Returns the world displacements of every node of a mesh of shells, solved with dense
+    /// matrices for the degrees of freedom that are not restrained
+    fn solve_mesh(
+        element: &ShellElement,
+        positions: &[Point3<f64>],
+        quads: &[[usize; 4]],
+        restrained: impl Fn(usize) -> bool,
+        forces: &DVector<f64>,
+    ) -> DVector<f64> {
+        let size = NODE_DOFS * positions.len();
+        let mut stiffness = DMatrix::<f64>::zeros(size, size);
+        for quad in quads.iter() {
+            let coordinates: Vec<Point3<f64>> = quad.iter().map(|x| positions[*x]).collect();
+            let k = shell_world_stiffness_matrix(element, &coordinates);
+            for (a, row) in quad.iter().enumerate() {
+                for (b, column) in quad.iter().enumerate() {
+                    for i in 0..NODE_DOFS {
+                        for j in 0..NODE_DOFS {
+                            stiffness[(NODE_DOFS * row + i, NODE_DOFS * column + j)] +=
+                                k[(NODE_DOFS * a + i, NODE_DOFS * b + j)];
+                        }
+                    }
+                }
+            }
+        }
+
+        let free: Vec<usize> = (0..size).filter(|x| !restrained(*x)).collect();
+        let reduced =
+            DMatrix::from_fn(free.len(), free.len(), |i, j| stiffness[(free[i], free[j])]);
+        let load = DVector::from_fn(free.len(), |i, _| forces[free[i]]);
+        let solution = reduced.lu().solve(&load).unwrap();
+        let mut displacements = DVector::<f64>::zeros(size);
+        for (i, dof) in free.iter().enumerate() {
+            displacements[*dof] = solution[i];
+        }
+        displacements
+    }
+
+    /// Returns the nodes of a grid of `columns` by `rows` quadrilaterals, each counterclockwise
+    /// about the normal `first × second`
+    fn grid(
+        (columns, rows): (usize, usize),
+        position: impl Fn(f64, f64) -> Point3<f64>,
+    ) -> (Vec<Point3<f64>>, Vec<[usize; 4]>) {
+        let index = |i: usize, j: usize| j * (columns + 1) + i;
+        let positions = (0..=rows)
+            .flat_map(|j| (0..=columns).map(move |i| (i, j)))
+            .map(|(i, j)| position(i as f64 / columns as f64, j as f64 / rows as f64))
+            .collect();
+        let quads = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                [
+                    index(i, j),
+                    index(i + 1, j),
+                    index(i + 1, j + 1),
+                    index(i, j + 1),
+                ]
+            })
+            .collect();
+        (positions, quads)
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn thin_simply_supported_plate_matches_kirchhoff_without_locking() {
+        // A unit square plate with a span to thickness ratio of 100 under unit pressure
+        let (a, t, E, nu, q) = (1., 0.01, 1e6, 0.3, 1.);
+        let element = ShellElement {
+            id: "plate".to_string(),
+            node_ids: Vec::new(),
+            thickness: t,
+            material: IsotropicMaterial::new(E, nu),
+        };
+        let D = E * t * t * t / (12. * (1. - nu * nu));
+        let divisions = 8;
+        let (positions, quads) = grid((divisions, divisions), |x, y| Point3::new(x, y, 0.));
+
+        let on_edge = |p: &Point3<f64>| p.x == 0. || p.y == 0. || p.x == a || p.y == a;
+        let restrained = |dof: usize| {
+            let direction = dof % NODE_DOFS;
+            matches!(direction, 0 | 1 | 5) || (direction == 2 && on_edge(&positions[dof / 6]))
+        };
+        let spacing = a / divisions as f64;
+        let forces = DVector::from_fn(NODE_DOFS * positions.len(), |dof, _| {
+            if dof % NODE_DOFS != 2 {
+                return 0.;
+            }
+            let p = positions[dof / NODE_DOFS];
+            let share = |x: f64| if x == 0. || x == a { 0.5 } else { 1. };
+            q * spacing * spacing * share(p.x) * share(p.y)
+        });
+        let displacements = solve_mesh(&element, &positions, &quads, restrained, &forces);
+
+        // Navier's series for the center deflection and moments
+        let center = (divisions / 2) * (divisions + 1) + divisions / 2;
+        assert_relative_eq!(
+            displacements[NODE_DOFS * center + 2],
+            0.00406 * q * a.powi(4) / D,
+            max_relative = 0.01
+        );
+
+        let quad = quads[(divisions / 2) * divisions + divisions / 2];
+        let coordinates: Vec<Point3<f64>> = quad.iter().map(|x| positions[*x]).collect();
+        let nodal: Vec<Vector6<f64>> = quad
+            .iter()
+            .map(|x| {
+                Vector6::from_iterator(displacements.rows(NODE_DOFS * x, NODE_DOFS).iter().cloned())
+            })
+            .collect();
+        let result = shell_resultants(&element, &coordinates, &nodal);
+        assert_relative_eq!(result.moments[0], 0.0479 * q * a * a, max_relative = 0.05);
+        assert_relative_eq!(result.moments[1], result.moments[0], max_relative = 1e-9);
+        assert_abs_diff_eq!(result.forces.amax(), 0., epsilon = 1e-12);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn vertical_shear_wall_bends_in_plane_like_a_beam() {
+        // A wall in the world XZ plane 10 long and 2 deep, clamped at x = 0 under a unit shear
+        let (L, h, E, nu) = (10., 2., 1000., 0.25);
+        let element = ShellElement {
+            id: "wall".to_string(),
+            node_ids: Vec::new(),
+            thickness: 1.,
+            material: IsotropicMaterial::new(E, nu),
+        };
+        let (positions, quads) = grid((4, 1), |x, z| Point3::new(L * x, 0., h * z));
+
+        // Out of the plane the wall is held, leaving the drilling rotation Ry free
+        let restrained =
+            |dof: usize| positions[dof / NODE_DOFS].x == 0. || matches!(dof % NODE_DOFS, 1 | 3 | 5);
+        let forces = DVector::from_fn(NODE_DOFS * positions.len(), |dof, _| {
+            if dof % NODE_DOFS == 2 && positions[dof / NODE_DOFS].x == L {
+                -0.5
+            } else {
+                0.
+            }
+        });
+        let displacements = solve_mesh(&element, &positions, &quads, restrained, &forces);
+
+        let bending = L * L * L / (3. * E * h * h * h / 12.);
+        let shear = L / (5. / 6. * E / (2. * (1. + nu)) * h);
+        let tip = -(displacements[NODE_DOFS * 4 + 2] + displacements[NODE_DOFS * 9 + 2]) / 2.;
+        assert_relative_eq!(tip, bending + shear, max_relative = 0.05);
+
+        // Rigid motions of a distorted, warped and tilted shell need no nodal forces
+        let axes = shell_local_axes(&[
+            Point3::origin(),
+            Point3::new(1., 0., 0.),
+            Point3::new(1., 0.6, 0.8),
+            Point3::new(0., 0.6, 0.8),
+        ]);
+        let coordinates: Vec<Point3<f64>> = [
+            (0., 0., 0.),
+            (3., -0.5, 0.2),
+            (2.5, 2., 0.),
+            (-0.5, 1.5, 0.),
+        ]
+        .iter()
+        .map(|(x, y, z)| Point3::new(1., 2., 3.) + axes * Vector3::new(*x, *y, *z))
+        .collect();
+        let k = shell_world_stiffness_matrix(&element, &coordinates);
+        let rotation = Vector3::new(0.3, -0.2, 0.5);
+        let rigid = DVector::from_iterator(
+            4 * NODE_DOFS,
+            coordinates.iter().flat_map(|p| {
+                let motion = rotation.cross(&p.coords) + Vector3::new(1., 2., 3.);
+                vec![
+                    motion.x, motion.y, motion.z, rotation.x, rotation.y, rotation.z,
+                ]
+            }),
+        );
+        assert_abs_diff_eq!((k * rigid).amax(), 0., epsilon = 1e-9);
+    }
+}
@@ -12,8 +12,16 @@ pub mod elements;
 pub mod models;
 pub mod utils;
 
+use crate::analysis::renumber::RenumberingMethod;
+use crate::elements::membrane::membrane_edge_forces;
+use crate::models::cable::CableElement;
+use crate::models::constraint::{Constraint, Diaphragm, DiaphragmCenter, DiaphragmNodes};
 use crate::models::frame::{FrameElement, FrameStiffness};
+use crate::models::mass::NodalMass;
+use crate::models::membrane::MembraneElement;
 use crate::models::node::Node;
+use crate::models::shell::ShellElement;
+use crate::models::support::{Support, SupportCondition};
 use std::collections::HashMap;
 use std::sync::RwLock;
 
@@ -26,7 +34,7 @@ use wasm_bindgen::prelude::*;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 #[wasm_bindgen]
-extern {
+extern "C" {
     pub fn alert(s: &str);
 }
 
@@ -44,6 +52,8 @@ pub struct AnalysisData {
     pub applied_forces: Vec<f64>,
     pub frame_stiffnesses: HashMap<String, FrameStiffness>,
     pub world_stiffness: HashMap<(usize, usize), f64>,
+    /// Reordering applied to the free degrees of freedom before solving, if any
+    pub renumbering: Option<RenumberingMethod>,
 }
 
 impl AnalysisData {
@@ -52,24 +62,51 @@ impl AnalysisData {
             applied_forces: Vec::new(),
             frame_stiffnesses: HashMap::<String, FrameStiffness>::new(),
             world_stiffness: HashMap::<(usize, usize), f64>::new(),
+            renumbering: Some(RenumberingMethod::ReverseCuthillMckee),
         }
     }
 }
 
 pub struct ElementData {
     pub frames: HashMap<String, FrameElement>,
+    pub cables: HashMap<String, CableElement>,
+    pub membranes: HashMap<String, MembraneElement>,
+    pub shells: HashMap<String, ShellElement>,
     pub nodes: HashMap<String, Node>,
+    pub supports: HashMap<String, Support>,
+    pub nodal_masses: HashMap<String, NodalMass>,
+    pub constraints: HashMap<String, Constraint>,
 }
 
 impl ElementData {
     fn new() -> Self {
         ElementData {
             frames: HashMap::<String, FrameElement>::new(),
+            cables: HashMap::<String, CableElement>::new(),
+            membranes: HashMap::<String, MembraneElement>::new(),
+            shells: HashMap::<String, ShellElement>::new(),
             nodes: HashMap::<String, Node>::new(),
+            supports: HashMap::<String, Support>::new(),
+            nodal_masses: HashMap::<String, NodalMass>::new(),
+            constraints: HashMap::<String, Constraint>::new(),
         }
     }
 }
 
+/// Removes every node, element, support, constraint and load, and restores the default analysis
+/// settings, so that a new model can be built.
+pub fn clear_model() {
+    *ELEMENT_DATA.write().unwrap() = ElementData::new();
+    *ANALYSIS_DATA.write().unwrap() = AnalysisData::new();
+}
+
+/// Sets the reordering of the free degrees of freedom applied before solving, or turns it off
+/// with `None`. Reverse Cuthill–McKee is used by default. The nodes keep their own degrees of
+/// freedom either way.
+pub fn set_renumbering_method(method: Option<RenumberingMethod>) {
+    ANALYSIS_DATA.write().unwrap().renumbering = method;
+}
+
 pub fn add_node(node: Node) {
     ELEMENT_DATA
         .write()
@@ -89,6 +126,171 @@ pub fn get_node_by_id(id: &str) -> Option<Node> {
     }
 }
 
+/// Adds the support, replacing any support previously defined at the same node.
+pub fn add_support(support: Support) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .supports
+        .insert(support.node_id.clone(), support);
+}
+
+pub fn get_support_by_node_id(node_id: &str) -> Option<Support> {
+    match ELEMENT_DATA.read() {
+        Ok(data) => {
+            let support: &Support = data.supports.get(node_id)?;
+            Some((*support).clone())
+        }
+        Err(_) => None,
+    }
+}
+
+/// Adds the mass, replacing any nodal mass previously defined at the same node.
+pub fn add_nodal_mass(mass: NodalMass) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .nodal_masses
+        .insert(mass.node_id.clone(), mass);
+}
+
+/// Adds the constraint, replacing any constraint previously defined with the same id.
+pub fn add_constraint(id: &str, constraint: Constraint) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .constraints
+        .insert(id.to_string(), constraint);
+}
+
+/// Adds a diaphragm tying the selected nodes in the XY plane to a new master node at their
+/// center, and returns the master node. The master node takes the id of the diaphragm and is
+/// restrained in `Uz`, `Rx` and `Ry`, leaving its in-plane degrees of freedom to the diaphragm.
+/// It takes the six degree of freedom numbers after the highest of the existing nodes, which
+/// nodes added later must not reuse.
+pub fn add_diaphragm(
+    id: &str,
+    selection: DiaphragmNodes,
+    center: DiaphragmCenter,
+) -> Result<Node, String> {
+    let mut data = ELEMENT_DATA.write().unwrap();
+    if data.nodes.contains_key(id) {
+        return Err(format!("Node id = {} already exists", id));
+    }
+
+    let slave_node_ids = Diaphragm::gather_nodes(&selection, &data.nodes)?;
+    let coordinate = Diaphragm::center(
+        center,
+        &slave_node_ids,
+        &data.nodes,
+        &data.nodal_masses,
+        &data.frames,
+    )?;
+    let first_dof = data
+        .nodes
+        .values()
+        .flat_map(|x| x.degrees_of_freedom.iter().cloned())
+        .max()
+        .map_or(0, |x| x + 1);
+    let master = Node {
+        id: id.to_string(),
+        coordinate,
+        degrees_of_freedom: na::Vector6::from_iterator(first_dof..first_dof + 6),
+        local_axes: None,
+    };
+
+    data.nodes.insert(master.id.clone(), master.clone());
+    data.supports.insert(
+        master.id.clone(),
+        Support {
+            node_id: master.id.clone(),
+            Ux: SupportCondition::Free,
+            Uy: SupportCondition::Free,
+            Uz: SupportCondition::Fixed,
+            Rx: SupportCondition::Fixed,
+            Ry: SupportCondition::Fixed,
+            Rz: SupportCondition::Free,
+            local_axes: None,
+        },
+    );
+    data.constraints.insert(
+        id.to_string(),
+        Constraint::Diaphragm(Diaphragm {
+            master_node_id: master.id.clone(),
+            slave_node_ids,
+        }),
+    );
+    Ok(master)
+}
+
+/// Adds the world forces and moments `[Fx, Fy, Fz, Mx, My, Mz]` acting at the node
+/// to the applied forces.
+pub fn add_nodal_load(node_id: &str, load: na::Vector6<f64>) -> Result<(), String> {
+    let node = match get_node_by_id(node_id) {
+        Some(x) => x,
+        None => return Err(format!("Failed to find node id = {}", node_id)),
+    };
+
+    add_load_at_node_dofs(&node, node.from_world(&load));
+    Ok(())
+}
+
+/// Adds forces and moments `[Fx, Fy, Fz, Mx, My, Mz]` acting along the node's local axes
+/// to the applied forces.
+pub fn add_local_nodal_load(node_id: &str, load: na::Vector6<f64>) -> Result<(), String> {
+    let node = match get_node_by_id(node_id) {
+        Some(x) => x,
+        None => return Err(format!("Failed to find node id = {}", node_id)),
+    };
+    if node.local_axes.is_none() {
+        return Err(format!("Node {} has no local axes", node_id));
+    }
+
+    add_load_at_node_dofs(&node, load);
+    Ok(())
+}
+
+/// Adds a uniform world force per unit length `[Fx, Fy, Fz]` along an edge of a membrane to
+/// the applied forces, shared among the nodes of the edge consistently with the element. Edge
+/// `i` runs from corner `i` to the next corner, counting from 0.
+pub fn add_membrane_edge_load(
+    membrane_id: &str,
+    edge: usize,
+    load: na::Vector3<f64>,
+) -> Result<(), String> {
+    let membrane = match ELEMENT_DATA.read().unwrap().membranes.get(membrane_id) {
+        Some(x) => x.clone(),
+        None => return Err(format!("Failed to find membrane id = {}", membrane_id)),
+    };
+    if edge > 2 {
+        return Err(format!(
+            "Membrane {} has edges 0 to 2 rather than {}",
+            membrane_id, edge
+        ));
+    }
+    let nodes = membrane.nodes().ok_or(format!(
+        "Failed to find the nodes of membrane {}",
+        membrane_id
+    ))?;
+
+    let coordinates: Vec<_> = nodes.iter().map(|x| x.coordinate).collect();
+    for (i, force) in membrane_edge_forces(&coordinates, edge, &load) {
+        let world = na::Vector6::new(force.x, force.y, force.z, 0., 0., 0.);
+        add_load_at_node_dofs(&nodes[i], nodes[i].from_world(&world));
+    }
+    Ok(())
+}
+
+fn add_load_at_node_dofs(node: &Node, load: na::Vector6<f64>) {
+    let mut analysis = ANALYSIS_DATA.write().unwrap();
+    for (i, dof) in node.degrees_of_freedom.iter().enumerate() {
+        if *dof >= analysis.applied_forces.len() {
+            analysis.applied_forces.resize(*dof + 1, 0.);
+        }
+        analysis.applied_forces[*dof] += load[i];
+    }
+}
+
 pub fn add_frame_element(frame: FrameElement) {
     ELEMENT_DATA
         .write()
@@ -97,6 +299,30 @@ pub fn add_frame_element(frame: FrameElement) {
         .insert(frame.id.clone(), frame);
 }
 
+pub fn add_cable_element(cable: CableElement) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .cables
+        .insert(cable.id.clone(), cable);
+}
+
+pub fn add_membrane_element(membrane: MembraneElement) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .membranes
+        .insert(membrane.id.clone(), membrane);
+}
+
+pub fn add_shell_element(shell: ShellElement) {
+    ELEMENT_DATA
+        .write()
+        .unwrap()
+        .shells
+        .insert(shell.id.clone(), shell);
+}
+
 pub fn update_frame_element_stiffness(
     frame: &FrameElement,
     local: na::MatrixN<f64, na::U12>,
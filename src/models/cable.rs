@@ -0,0 +1,45 @@
+use crate::models::material::IsotropicMaterial;
+use crate::models::node::Node;
+use crate::*;
+
+/// A tension-only cable between two nodes, hanging as an elastic catenary under its own weight.
+/// Cables connect to the translational degrees of freedom of their nodes only.
+#[derive(Clone)]
+pub struct CableElement {
+    pub id: String,
+    pub start_node_id: String,
+    pub end_node_id: String,
+    /// Area of the cross section
+    pub area: f64,
+    pub material: IsotropicMaterial,
+    /// Weight per unit unstressed length, acting in the world -Z direction
+    pub weight: f64,
+    pub initial_state: CableInitialState,
+}
+
+impl CableElement {
+    /// Returns the unstressed length of the cable given the length of the chord between its
+    /// undeformed nodes.
+    pub fn unstressed_length(&self, chord: f64) -> f64 {
+        match self.initial_state {
+            CableInitialState::Pretension(x) => chord / (1. + x / (self.material.E * self.area)),
+            CableInitialState::UnstressedLength(x) => x,
+        }
+    }
+    pub fn start_node(&self) -> Option<Node> {
+        get_node_by_id(&self.start_node_id)
+    }
+    pub fn end_node(&self) -> Option<Node> {
+        get_node_by_id(&self.end_node_id)
+    }
+}
+
+/// The stress-free state of a cable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CableInitialState {
+    /// Tension of the cable stretched straight between its undeformed nodes, which makes the
+    /// unstressed length the chord shortened by the strain `T / EA`
+    Pretension(f64),
+    /// Length of the cable free of stress
+    UnstressedLength(f64),
+}
@@ -0,0 +1,343 @@
+use crate::models::frame::FrameElement;
+use crate::models::mass::NodalMass;
+use crate::models::node::{Direction, Node};
+use na::{Matrix6, Point3, Vector3};
+use std::collections::HashMap;
+
+/// Coefficients at or below this magnitude are left out of the equations of a rigid link.
+const NEGLIGIBLE_COEFFICIENT: f64 = 1e-14;
+
+/// The displacement of a node in one of its degrees of freedom, scaled by a coefficient.
+#[derive(Clone, Debug)]
+pub struct ConstraintTerm {
+    pub node_id: String,
+    pub direction: Direction,
+    pub coefficient: f64,
+}
+
+/// A slave degree of freedom following a linear combination of master degrees of freedom,
+/// `u_slave = Σ cᵢ u_masterᵢ`. Directions are those of each node's degrees of freedom, so they
+/// follow the node's local axes where it has them.
+#[derive(Clone, Debug)]
+pub struct MultiPointConstraint {
+    pub slave_node_id: String,
+    pub slave_direction: Direction,
+    pub masters: Vec<ConstraintTerm>,
+}
+
+/// A rigid connection making the slave node translate and rotate with the master node, as for
+/// an eccentric connection or a rigid floor offset.
+#[derive(Clone, Debug)]
+pub struct RigidLink {
+    pub master_node_id: String,
+    pub slave_node_id: String,
+}
+
+/// A floor rigid in the world XY plane, making every slave node translate in plane and rotate
+/// about z with the master node while leaving it free to move out of plane.
+#[derive(Clone, Debug)]
+pub struct Diaphragm {
+    pub master_node_id: String,
+    pub slave_node_ids: Vec<String>,
+}
+
+/// The nodes tied together by a diaphragm.
+#[derive(Clone, Debug)]
+pub enum DiaphragmNodes {
+    /// Every node whose z coordinate lies within `tolerance` of `z`
+    Elevation {
+        z: f64,
+        tolerance: f64,
+    },
+    List(Vec<String>),
+}
+
+/// Where the master node of a diaphragm is placed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiaphragmCenter {
+    /// The center of the nodal masses and of the frame masses lumped at the diaphragm nodes
+    Mass,
+    /// The centroid of the diaphragm nodes
+    Geometry,
+}
+
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    RigidLink(RigidLink),
+    MultiPoint(MultiPointConstraint),
+    Diaphragm(Diaphragm),
+}
+
+impl Constraint {
+    /// Returns the ids of every node the constraint references, starting with the slaves.
+    pub fn node_ids(&self) -> Vec<&str> {
+        match self {
+            Constraint::RigidLink(x) => vec![&x.slave_node_id, &x.master_node_id],
+            Constraint::Diaphragm(x) => x
+                .slave_node_ids
+                .iter()
+                .chain(std::iter::once(&x.master_node_id))
+                .map(|y| y.as_str())
+                .collect(),
+            Constraint::MultiPoint(x) => std::iter::once(x.slave_node_id.as_str())
+                .chain(x.masters.iter().map(|y| y.node_id.as_str()))
+                .collect(),
+        }
+    }
+
+    /// Returns the constraint as equations each giving one slave degree of freedom.
+    ///
+    /// A rigid link gives `u_s = u_m + θ_m × r` and `θ_s = θ_m` in world coordinates, where `r`
+    /// runs from the master to the slave node, rotated into the axes of either node as needed.
+    /// A diaphragm gives the in-plane components `Ux`, `Uy` and `Rz` of the same equations for
+    /// each slave, with `r` projected onto the XY plane.
+    pub fn equations(
+        &self,
+        nodes: &HashMap<String, Node>,
+    ) -> Result<Vec<MultiPointConstraint>, String> {
+        let find = |id: &str| match nodes.get(id) {
+            Some(x) => Ok(x),
+            None => Err(format!("Failed to find node id = {}", id)),
+        };
+        match self {
+            Constraint::MultiPoint(x) => Ok(vec![x.clone()]),
+            Constraint::RigidLink(x) => {
+                let master = find(&x.master_node_id)?;
+                let slave = find(&x.slave_node_id)?;
+                let r = slave.coordinate - master.coordinate;
+                Ok(rigid_equations(master, slave, r, &Direction::ALL))
+            }
+            Constraint::Diaphragm(x) => {
+                let master = find(&x.master_node_id)?;
+                let mut equations = Vec::<MultiPointConstraint>::new();
+                for id in x.slave_node_ids.iter() {
+                    let slave = find(id)?;
+                    let r = slave.coordinate - master.coordinate;
+                    equations.extend(rigid_equations(
+                        master,
+                        slave,
+                        Vector3::new(r.x, r.y, 0.),
+                        &[Direction::Ux, Direction::Uy, Direction::Rz],
+                    ));
+                }
+                Ok(equations)
+            }
+        }
+    }
+}
+
+impl Diaphragm {
+    /// Returns the sorted ids of the nodes selected for a diaphragm.
+    pub fn gather_nodes(
+        selection: &DiaphragmNodes,
+        nodes: &HashMap<String, Node>,
+    ) -> Result<Vec<String>, String> {
+        let mut ids: Vec<String> = match selection {
+            DiaphragmNodes::Elevation { z, tolerance } => nodes
+                .values()
+                .filter(|x| (x.coordinate.z - z).abs() <= *tolerance)
+                .map(|x| x.id.clone())
+                .collect(),
+            DiaphragmNodes::List(x) => {
+                if let Some(missing) = x.iter().find(|y| !nodes.contains_key(*y)) {
+                    return Err(format!("Failed to find node id = {}", missing));
+                }
+                x.clone()
+            }
+        };
+        ids.sort();
+        ids.dedup();
+
+        if ids.len() < 2 {
+            return Err("A diaphragm needs at least two nodes".to_string());
+        }
+        Ok(ids)
+    }
+
+    /// Returns the point at which to place the master node of a diaphragm tying the given nodes.
+    pub fn center(
+        center: DiaphragmCenter,
+        node_ids: &[String],
+        nodes: &HashMap<String, Node>,
+        nodal_masses: &HashMap<String, NodalMass>,
+        frames: &HashMap<String, FrameElement>,
+    ) -> Result<Point3<f64>, String> {
+        let mut weights: HashMap<&str, f64> = node_ids
+            .iter()
+            .map(|x| {
+                let weight = match center {
+                    DiaphragmCenter::Geometry => 1.,
+                    DiaphragmCenter::Mass => nodal_masses.get(x).map_or(0., |y| y.mass[0]),
+                };
+                (x.as_str(), weight)
+            })
+            .collect();
+        if center == DiaphragmCenter::Mass {
+            for frame in frames.values() {
+                let (start, end) = match (
+                    nodes.get(&frame.start_node_id),
+                    nodes.get(&frame.end_node_id),
+                ) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => continue,
+                };
+                let mass = frame.material.rho
+                    * frame.geometry.average_cross_section().A
+                    * na::distance(&start.coordinate, &end.coordinate);
+                for node in [start, end].iter() {
+                    if let Some(weight) = weights.get_mut(node.id.as_str()) {
+                        *weight += mass / 2.;
+                    }
+                }
+            }
+        }
+
+        let total: f64 = weights.values().sum();
+        if total <= 0. {
+            return Err("The diaphragm nodes have no mass".to_string());
+        }
+        let mut sum = Vector3::<f64>::zeros();
+        for (id, weight) in weights.iter() {
+            let node = match nodes.get(*id) {
+                Some(x) => x,
+                None => return Err(format!("Failed to find node id = {}", id)),
+            };
+            sum += node.coordinate.coords * *weight;
+        }
+        Ok(Point3::from(sum / total))
+    }
+}
+
+/// Returns the equations of the given slave directions of a node rigidly connected to a master,
+/// where `r` is the lever arm from the master to the slave.
+fn rigid_equations(
+    master: &Node,
+    slave: &Node,
+    r: Vector3<f64>,
+    directions: &[Direction],
+) -> Vec<MultiPointConstraint> {
+    #[rustfmt::skip]
+    let world = Matrix6::new(
+        1., 0., 0.,  0.,   r.z, -r.y,
+        0., 1., 0., -r.z,  0.,   r.x,
+        0., 0., 1.,  r.y, -r.x,  0.,
+        0., 0., 0.,  1.,   0.,   0.,
+        0., 0., 0.,  0.,   1.,   0.,
+        0., 0., 0.,  0.,   0.,   1.,
+    );
+    let nodal =
+        slave.world_to_nodal_transform() * world * master.world_to_nodal_transform().transpose();
+
+    directions
+        .iter()
+        .map(|slave_direction| MultiPointConstraint {
+            slave_node_id: slave.id.clone(),
+            slave_direction: *slave_direction,
+            masters: Direction::ALL
+                .iter()
+                .filter_map(|direction| {
+                    let coefficient = nodal[(slave_direction.index(), direction.index())];
+                    if coefficient.abs() <= NEGLIGIBLE_COEFFICIENT {
+                        return None;
+                    }
+                    Some(ConstraintTerm {
+                        node_id: master.id.clone(),
+                        direction: *direction,
+                        coefficient,
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::{Point3, Vector6};
+
+    fn node(id: &str, dofs: usize, coordinate: Point3<f64>) -> Node {
+        Node {
+            id: id.to_string(),
+            coordinate,
+            degrees_of_freedom: Vector6::from_iterator(dofs..dofs + 6),
+            local_axes: None,
+        }
+    }
+
+    #[test]
+    fn rigid_link_translates_slave_with_master_rotation() {
+        let nodes: HashMap<String, Node> = vec![
+            node("link_master", 0, Point3::new(1., 2., 3.)),
+            node("link_slave", 6, Point3::new(3., 2., 3.)),
+        ]
+        .into_iter()
+        .map(|x| (x.id.clone(), x))
+        .collect();
+        let link = Constraint::RigidLink(RigidLink {
+            master_node_id: "link_master".to_string(),
+            slave_node_id: "link_slave".to_string(),
+        });
+
+        // Rotation θz of the master moves a slave 2 along x by 2θz in y
+        let equations = link.equations(&nodes).unwrap();
+        let uy = &equations[Direction::Uy.index()];
+        assert_eq!(uy.masters.len(), 2);
+        assert_eq!(uy.masters[1].direction, Direction::Rz);
+        assert_relative_eq!(uy.masters[1].coefficient, 2., max_relative = 1e-12);
+        assert_eq!(equations[Direction::Ux.index()].masters.len(), 1);
+    }
+
+    #[test]
+    fn diaphragm_centers_master_on_mass_and_ties_in_plane_directions() {
+        let mut nodes: HashMap<String, Node> = vec![
+            node("floor_a", 0, Point3::new(0., 0., 3.)),
+            node("floor_b", 6, Point3::new(4., 0., 3.)),
+            node("floor_c", 12, Point3::new(4., 4., 3.)),
+            node("ground", 18, Point3::new(0., 0., 0.)),
+        ]
+        .into_iter()
+        .map(|x| (x.id.clone(), x))
+        .collect();
+        let masses: HashMap<String, NodalMass> = vec![
+            NodalMass::translational("floor_a", 1.),
+            NodalMass::translational("floor_c", 3.),
+        ]
+        .into_iter()
+        .map(|x| (x.node_id.clone(), x))
+        .collect();
+
+        let selection = DiaphragmNodes::Elevation {
+            z: 3.,
+            tolerance: 1e-6,
+        };
+        let ids = Diaphragm::gather_nodes(&selection, &nodes).unwrap();
+        assert_eq!(ids, vec!["floor_a", "floor_b", "floor_c"]);
+        let center = Diaphragm::center(
+            DiaphragmCenter::Mass,
+            &ids,
+            &nodes,
+            &masses,
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_relative_eq!(center.x, 3., max_relative = 1e-12);
+        assert_relative_eq!(center.y, 3., max_relative = 1e-12);
+
+        nodes.insert("master".to_string(), node("master", 24, center));
+        let diaphragm = Constraint::Diaphragm(Diaphragm {
+            master_node_id: "master".to_string(),
+            slave_node_ids: ids,
+        });
+
+        // Each slave gets Ux, Uy and Rz, with Ux = u_m + 3 θz for floor_a, 3 below the master
+        let equations = diaphragm.equations(&nodes).unwrap();
+        assert_eq!(equations.len(), 9);
+        let ux = &equations[0];
+        assert_eq!(ux.slave_node_id, "floor_a");
+        assert_eq!(ux.masters.len(), 2);
+        assert_eq!(ux.masters[1].direction, Direction::Rz);
+        assert_relative_eq!(ux.masters[1].coefficient, 3., max_relative = 1e-12);
+        assert_eq!(equations[2].slave_direction, Direction::Rz);
+    }
+}
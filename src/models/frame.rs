@@ -1,7 +1,7 @@
 use crate::models::material::*;
 use crate::models::node::*;
 use crate::*;
-use na::{Matrix3, MatrixN, Point3, U12};
+use na::{Matrix3, MatrixN, Point3, Vector3, U12};
 
 #[derive(Clone)]
 pub struct FrameElement {
@@ -12,6 +12,9 @@ pub struct FrameElement {
     pub end_releases: FrameEndReleases,
     pub geometry: FrameGeometry,
     pub material: IsotropicMaterial,
+    pub end_offsets: FrameEndOffsets,
+    pub beam_theory: BeamTheory,
+    pub foundation: Option<ElasticFoundation>,
 }
 
 impl FrameElement {
@@ -29,6 +32,15 @@ impl FrameElement {
         let end: Point3<f64> = self.end_node()?.coordinate;
         Some(na::distance(&start, &end))
     }
+    /// Returns the length between the ends of the rigid zones, or positive infinity if it cannot
+    /// be determined from the start and end nodes.
+    pub fn flexible_length_or_inf(&self) -> f64 {
+        let (start, end) = match (self.start_node(), self.end_node()) {
+            (Some(x), Some(y)) => self.end_offsets.flexible_ends(&x.coordinate, &y.coordinate),
+            _ => return f64::INFINITY,
+        };
+        na::distance(&start, &end)
+    }
     pub fn start_node(&self) -> Option<Node> {
         get_node_by_id(&self.start_node_id)
     }
@@ -37,10 +49,88 @@ impl FrameElement {
     }
 }
 
+/// The beam theory giving the bending stiffness of a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BeamTheory {
+    /// Plane sections remain normal to the axis, so shear deformation is ignored
+    EulerBernoulli,
+    /// Shear deformation follows from the shear areas `Avy` and `Avz`, where an area of zero
+    /// ignores shear deformation in that direction
+    Timoshenko,
+}
+
+/// Continuous elastic (Winkler) support along the flexible length of a frame, such as the
+/// subgrade under a grade beam or the ballast under a rail.
+#[derive(Clone)]
+pub struct ElasticFoundation {
+    /// Foundation modulus along the local y axis, as force per unit length of the frame per unit
+    /// displacement
+    pub ky: f64,
+    /// Foundation modulus along the local z axis
+    pub kz: f64,
+}
+
+/// Rigid zones at the ends of a frame, e.g. where a beam frames into a wide column. Only the
+/// elastic stiffness accounts for them; the geometric stiffness and mass use the full length
+/// between the nodes.
+#[derive(Clone)]
+pub struct FrameEndOffsets {
+    /// World vector from the start node to the face of the support at the start
+    pub start: Vector3<f64>,
+    /// World vector from the end node to the face of the support at the end
+    pub end: Vector3<f64>,
+    /// Fraction of each offset which is rigid, from 0 for a fully flexible member between the
+    /// nodes to 1 for a member flexible only between the faces
+    pub rigid_zone_factor: f64,
+}
+
+impl FrameEndOffsets {
+    pub fn none() -> FrameEndOffsets {
+        FrameEndOffsets {
+            start: Vector3::zeros(),
+            end: Vector3::zeros(),
+            rigid_zone_factor: 0.,
+        }
+    }
+    /// Returns offsets of the given lengths along the local x axis, running inwards from each
+    /// node.
+    pub fn along_axis(
+        local_axes: &Matrix3<f64>,
+        start_length: f64,
+        end_length: f64,
+        rigid_zone_factor: f64,
+    ) -> FrameEndOffsets {
+        let axis: Vector3<f64> = local_axes.column(0).normalize();
+        FrameEndOffsets {
+            start: axis * start_length,
+            end: -axis * end_length,
+            rigid_zone_factor,
+        }
+    }
+    /// Returns true if the offsets leave the flexible member running between the nodes.
+    pub fn is_none(&self) -> bool {
+        self.rigid_zone_factor == 0.
+            || (self.start == Vector3::zeros() && self.end == Vector3::zeros())
+    }
+    /// Returns the ends of the flexible member given the start and end node coordinates.
+    pub fn flexible_ends(
+        &self,
+        start: &Point3<f64>,
+        end: &Point3<f64>,
+    ) -> (Point3<f64>, Point3<f64>) {
+        (
+            start + self.start * self.rigid_zone_factor,
+            end + self.end * self.rigid_zone_factor,
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct FrameGeometry {
     pub local_axes: Matrix3<f64>,
+    /// The cross section of a prismatic frame, or the section at the start of a tapered one
     pub cross_section: CrossSection,
+    pub taper: Option<FrameTaper>,
 }
 
 // TODO change this function accept endpoints and a relative rotation about longitudinal axis
@@ -49,8 +139,54 @@ impl FrameGeometry {
         FrameGeometry {
             local_axes,
             cross_section,
+            taper: None,
         }
     }
+    /// Returns the cross section at the fraction `xi` of the flexible length from the start.
+    pub fn cross_section_at(&self, xi: f64) -> CrossSection {
+        let taper = match &self.taper {
+            Some(x) => x,
+            None => return self.cross_section.clone(),
+        };
+        let weight = match taper.variation {
+            TaperVariation::Linear => xi,
+            TaperVariation::Parabolic => xi * xi,
+        };
+        self.cross_section
+            .interpolate(&taper.end_cross_section, weight)
+    }
+    /// Returns the cross section with each property averaged along the length, as used for the
+    /// mass and geometric stiffness of tapered frames.
+    pub fn average_cross_section(&self) -> CrossSection {
+        let taper = match &self.taper {
+            Some(x) => x,
+            None => return self.cross_section.clone(),
+        };
+        let weight = match taper.variation {
+            TaperVariation::Linear => 1. / 2.,
+            TaperVariation::Parabolic => 1. / 3.,
+        };
+        self.cross_section
+            .interpolate(&taper.end_cross_section, weight)
+    }
+}
+
+/// Variation of the cross section along a tapered frame, where each property runs from its
+/// start value `p0` to its end value `p1`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaperVariation {
+    /// `p0 + (p1 - p0) ξ`
+    Linear,
+    /// `p0 + (p1 - p0) ξ²`, as for a haunch which is flat at the start
+    Parabolic,
+}
+
+/// A cross section varying from the geometry's `cross_section` at the start of the frame to
+/// `end_cross_section` at the end.
+#[derive(Clone)]
+pub struct FrameTaper {
+    pub end_cross_section: CrossSection,
+    pub variation: TaperVariation,
 }
 
 #[derive(Clone)]
@@ -105,6 +241,22 @@ pub struct CrossSection {
     pub Iz: f64,
 }
 
+impl CrossSection {
+    /// Returns the section with every property a fraction `weight` of the way to `other`.
+    pub fn interpolate(&self, other: &CrossSection, weight: f64) -> CrossSection {
+        // Equal values are kept as they are so that infinite shear areas stay infinite
+        let between = |a: f64, b: f64| if a == b { a } else { a + (b - a) * weight };
+        CrossSection {
+            A: between(self.A, other.A),
+            Avy: between(self.Avy, other.Avy),
+            Avz: between(self.Avz, other.Avz),
+            J: between(self.J, other.J),
+            Iy: between(self.Iy, other.Iy),
+            Iz: between(self.Iz, other.Iz),
+        }
+    }
+}
+
 pub struct FrameStiffness {
     pub local: MatrixN<f64, U12>,
     pub world: MatrixN<f64, U12>,
@@ -0,0 +1,21 @@
+use na::Vector6;
+
+/// Mass lumped at a node in addition to the mass of the elements, e.g. from non-structural
+/// finishes or equipment.
+#[derive(Clone)]
+pub struct NodalMass {
+    pub node_id: String,
+    /// Masses along `[Ux, Uy, Uz]` followed by mass moments of inertia about `[Rx, Ry, Rz]`,
+    /// all in world directions
+    pub mass: Vector6<f64>,
+}
+
+impl NodalMass {
+    /// Returns the same mass in every translational direction with no rotational inertia
+    pub fn translational(node_id: &str, mass: f64) -> NodalMass {
+        NodalMass {
+            node_id: node_id.to_string(),
+            mass: Vector6::new(mass, mass, mass, 0., 0., 0.),
+        }
+    }
+}
@@ -4,12 +4,18 @@ pub struct IsotropicMaterial {
     pub E: f64,
     pub G: f64,
     pub nu: f64,
+    /// Mass per unit volume
+    pub rho: f64,
 }
 
 #[allow(non_snake_case)]
 impl IsotropicMaterial {
     pub fn new(E: f64, nu: f64) -> IsotropicMaterial {
         let G = E / (2. * (1. + nu));
-        IsotropicMaterial { E, G, nu }
+        IsotropicMaterial { E, G, nu, rho: 0. }
+    }
+    /// Returns the material with the given mass per unit volume
+    pub fn with_density(self, rho: f64) -> IsotropicMaterial {
+        IsotropicMaterial { rho, ..self }
     }
 }
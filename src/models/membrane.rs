@@ -0,0 +1,24 @@
+use crate::models::material::IsotropicMaterial;
+use crate::models::node::Node;
+use crate::*;
+
+/// A flat plane-stress element carrying forces in its own plane only. Membranes connect to the
+/// translational degrees of freedom of their nodes, so nodes joined only by membranes need their
+/// rotations restrained.
+#[derive(Clone)]
+pub struct MembraneElement {
+    pub id: String,
+    /// The three corner nodes of a constant-strain triangle, counterclockwise about the normal,
+    /// or those of a linear-strain triangle followed by the midside nodes of the edges 1-2, 2-3
+    /// and 3-1
+    pub node_ids: Vec<String>,
+    pub thickness: f64,
+    pub material: IsotropicMaterial,
+}
+
+impl MembraneElement {
+    /// Returns the nodes of the element if they all exist.
+    pub fn nodes(&self) -> Option<Vec<Node>> {
+        self.node_ids.iter().map(|x| get_node_by_id(x)).collect()
+    }
+}
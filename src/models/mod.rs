@@ -1,3 +1,10 @@
+pub mod cable;
+pub mod constraint;
 pub mod frame;
+pub mod mass;
 pub mod material;
+pub mod membrane;
 pub mod node;
+pub mod shell;
+pub mod support;
+pub mod validation;
@@ -1,8 +1,110 @@
-use na::{Point3, Vector6};
+use crate::utils::transform::world_to_nodal_transform;
+use na::{Matrix3, Matrix6, Point3, Vector6};
+use std::fmt;
 
 #[derive(Clone)]
 pub struct Node {
     pub id: String,
     pub coordinate: Point3<f64>,
     pub degrees_of_freedom: Vector6<usize>,
+    /// Axes of the node's local coordinate system, with the local x, y and z axes in the columns
+    /// in world coordinates. When set, the degrees of freedom, support conditions and loads of
+    /// the node act along these axes rather than the world axes.
+    pub local_axes: Option<Matrix3<f64>>,
+}
+
+impl Node {
+    /// Returns the transformation from world components `[Ux, Uy, Uz, Rx, Ry, Rz]` to components
+    /// along the node's degrees of freedom.
+    pub fn world_to_nodal_transform(&self) -> Matrix6<f64> {
+        match &self.local_axes {
+            Some(x) => world_to_nodal_transform(x),
+            None => Matrix6::identity(),
+        }
+    }
+    /// Returns world components of a vector given along the node's degrees of freedom.
+    pub fn to_world(&self, nodal: &Vector6<f64>) -> Vector6<f64> {
+        match &self.local_axes {
+            Some(x) => world_to_nodal_transform(x).transpose() * nodal,
+            None => *nodal,
+        }
+    }
+    /// Returns the components of a world vector along the node's degrees of freedom.
+    pub fn from_world(&self, world: &Vector6<f64>) -> Vector6<f64> {
+        match &self.local_axes {
+            Some(x) => world_to_nodal_transform(x) * world,
+            None => *world,
+        }
+    }
+}
+
+/// The six nodal directions, in the order they appear in `Node::degrees_of_freedom`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Ux,
+    Uy,
+    Uz,
+    Rx,
+    Ry,
+    Rz,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::Ux,
+        Direction::Uy,
+        Direction::Uz,
+        Direction::Rx,
+        Direction::Ry,
+        Direction::Rz,
+    ];
+
+    /// Returns the position of this direction within `Node::degrees_of_freedom`.
+    pub fn index(self) -> usize {
+        match self {
+            Direction::Ux => 0,
+            Direction::Uy => 1,
+            Direction::Uz => 2,
+            Direction::Rx => 3,
+            Direction::Ry => 4,
+            Direction::Rz => 5,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_axes_rotate_translations_and_rotations_alike() {
+        // Local x along world y, local y along world -x
+        #[rustfmt::skip]
+        let axes = Matrix3::new(
+            0., -1., 0.,
+            1.,  0., 0.,
+            0.,  0., 1.,
+        );
+        let node = Node {
+            id: "rotated".to_string(),
+            coordinate: Point3::origin(),
+            degrees_of_freedom: Vector6::from_iterator(0..6),
+            local_axes: Some(axes),
+        };
+
+        let world = Vector6::new(0., 2., 3., 4., 0., 5.);
+        let nodal = node.from_world(&world);
+        let expected = Vector6::new(2., 0., 3., 0., -4., 5.);
+        let round_trip = node.to_world(&nodal);
+        for i in 0..6 {
+            assert_abs_diff_eq!(nodal[i], expected[i], epsilon = 1e-12);
+            assert_abs_diff_eq!(round_trip[i], world[i], epsilon = 1e-12);
+        }
+    }
 }
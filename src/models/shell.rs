@@ -0,0 +1,22 @@
+use crate::models::material::IsotropicMaterial;
+use crate::models::node::Node;
+use crate::*;
+
+/// A flat four-node shell carrying membrane forces in its plane along with bending and
+/// transverse shear. Shells connect to all six degrees of freedom of their nodes, including a
+/// small stiffness against rotation about their normal so that they join frames.
+#[derive(Clone)]
+pub struct ShellElement {
+    pub id: String,
+    /// The four corner nodes, counterclockwise about the normal
+    pub node_ids: Vec<String>,
+    pub thickness: f64,
+    pub material: IsotropicMaterial,
+}
+
+impl ShellElement {
+    /// Returns the nodes of the element if they all exist.
+    pub fn nodes(&self) -> Option<Vec<Node>> {
+        self.node_ids.iter().map(|x| get_node_by_id(x)).collect()
+    }
+}
@@ -0,0 +1,243 @@
+use crate::models::node::Direction;
+use na::{Matrix3, Matrix6, Vector6};
+
+#[derive(Clone)]
+#[allow(non_snake_case)]
+pub struct Support {
+    pub node_id: String,
+    pub Ux: SupportCondition,
+    pub Uy: SupportCondition,
+    pub Uz: SupportCondition,
+    pub Rx: SupportCondition,
+    pub Ry: SupportCondition,
+    pub Rz: SupportCondition,
+    /// Axes of skewed springs, with the local x, y and z axes in the columns in world
+    /// coordinates. When `None` the conditions act along the node's degrees of freedom, which
+    /// follow the node's local axes where it has them and the world axes otherwise.
+    pub local_axes: Option<Matrix3<f64>>,
+}
+
+impl Support {
+    pub fn fully_fixed(node_id: &str) -> Support {
+        Support {
+            node_id: node_id.to_string(),
+            Ux: SupportCondition::Fixed,
+            Uy: SupportCondition::Fixed,
+            Uz: SupportCondition::Fixed,
+            Rx: SupportCondition::Fixed,
+            Ry: SupportCondition::Fixed,
+            Rz: SupportCondition::Fixed,
+            local_axes: None,
+        }
+    }
+    pub fn pinned(node_id: &str) -> Support {
+        Support {
+            node_id: node_id.to_string(),
+            Ux: SupportCondition::Fixed,
+            Uy: SupportCondition::Fixed,
+            Uz: SupportCondition::Fixed,
+            Rx: SupportCondition::Free,
+            Ry: SupportCondition::Free,
+            Rz: SupportCondition::Free,
+            local_axes: None,
+        }
+    }
+    /// Returns a support with linear springs of the given stiffnesses `[Ux, Uy, Uz, Rx, Ry, Rz]`,
+    /// where a stiffness of zero leaves the direction free.
+    pub fn springs(node_id: &str, stiffness: Vector6<f64>) -> Support {
+        let condition = |k: f64| {
+            if k == 0. {
+                SupportCondition::Free
+            } else {
+                SupportCondition::Spring(k)
+            }
+        };
+        Support {
+            node_id: node_id.to_string(),
+            Ux: condition(stiffness[0]),
+            Uy: condition(stiffness[1]),
+            Uz: condition(stiffness[2]),
+            Rx: condition(stiffness[3]),
+            Ry: condition(stiffness[4]),
+            Rz: condition(stiffness[5]),
+            local_axes: None,
+        }
+    }
+    /// Returns the condition in the given direction.
+    pub fn condition(&self, direction: Direction) -> &SupportCondition {
+        match direction {
+            Direction::Ux => &self.Ux,
+            Direction::Uy => &self.Uy,
+            Direction::Uz => &self.Uz,
+            Direction::Rx => &self.Rx,
+            Direction::Ry => &self.Ry,
+            Direction::Rz => &self.Rz,
+        }
+    }
+    /// Returns true if the given direction is restrained, at zero or a prescribed displacement.
+    pub fn is_fixed(&self, direction: Direction) -> bool {
+        matches!(
+            self.condition(direction),
+            SupportCondition::Fixed | SupportCondition::Prescribed(_)
+        )
+    }
+    /// Returns true if a spring acts in any direction.
+    pub fn has_springs(&self) -> bool {
+        Direction::ALL
+            .iter()
+            .any(|x| self.condition(*x).is_spring())
+    }
+    /// Returns true if a compression-only or multilinear spring acts in any direction.
+    pub fn has_nonlinear_springs(&self) -> bool {
+        Direction::ALL
+            .iter()
+            .any(|x| self.condition(*x).is_nonlinear())
+    }
+    /// Returns the stiffness matrix of the springs over the node's six degrees of freedom, in
+    /// world coordinates when the springs are skewed. Nonlinear springs contribute their initial
+    /// stiffness.
+    pub fn spring_stiffness(&self) -> Matrix6<f64> {
+        let mut local = Matrix6::<f64>::zeros();
+        for direction in Direction::ALL.iter() {
+            let k = self.condition(*direction).initial_stiffness();
+            local[(direction.index(), direction.index())] = k;
+        }
+        let axes = match self.local_axes {
+            Some(x) => x,
+            None => return local,
+        };
+
+        let mut rotation = Matrix6::<f64>::zeros();
+        for i in 0..3 {
+            let axis = axes.column(i).normalize();
+            for j in 0..3 {
+                rotation[(j, i)] = axis[j];
+                rotation[(j + 3, i + 3)] = axis[j];
+            }
+        }
+        rotation * local * rotation.transpose()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum SupportCondition {
+    Fixed,
+    Free,
+    /// Restrained at the given displacement along the node's degree of freedom, such as the
+    /// settlement of a foundation. Static analyses impose the displacement, while dynamic
+    /// analyses hold the direction fixed.
+    Prescribed(f64),
+    /// Linear spring with the given stiffness
+    Spring(f64),
+    /// Spring with the given stiffness which only resists displacement in the negative
+    /// direction, such as soil beneath a footing that is free to lift off
+    CompressionOnly(f64),
+    /// Spring following the force-displacement curve through the given `(displacement, force)`
+    /// points, in order of increasing displacement. The curve is linear between the points and
+    /// is extended beyond them along the first and last segments.
+    Multilinear(Vec<(f64, f64)>),
+}
+
+impl SupportCondition {
+    /// Returns true for linear and nonlinear springs.
+    pub fn is_spring(&self) -> bool {
+        matches!(
+            self,
+            SupportCondition::Spring(_)
+                | SupportCondition::CompressionOnly(_)
+                | SupportCondition::Multilinear(_)
+        )
+    }
+    /// Returns true for springs whose force is not proportional to the displacement.
+    pub fn is_nonlinear(&self) -> bool {
+        matches!(
+            self,
+            SupportCondition::CompressionOnly(_) | SupportCondition::Multilinear(_)
+        )
+    }
+    /// Returns the stiffness of a spring at zero displacement, or zero for other conditions.
+    pub fn initial_stiffness(&self) -> f64 {
+        match self {
+            SupportCondition::Spring(k) | SupportCondition::CompressionOnly(k) => *k,
+            SupportCondition::Multilinear(points) => multilinear_segment(points, 0.).0,
+            _ => 0.,
+        }
+    }
+    /// Returns the force carried by a spring at the given displacement. The spring acts on the
+    /// node in the opposite sense.
+    pub fn spring_force(&self, displacement: f64) -> f64 {
+        match self {
+            SupportCondition::Spring(k) => k * displacement,
+            SupportCondition::CompressionOnly(k) => k * displacement.min(0.),
+            SupportCondition::Multilinear(points) => {
+                let (slope, intercept) = multilinear_segment(points, displacement);
+                intercept + slope * displacement
+            }
+            _ => 0.,
+        }
+    }
+    /// Returns the secant stiffness `F(δ) / δ` of a spring, or the initial stiffness at zero
+    /// displacement.
+    pub fn secant_stiffness(&self, displacement: f64) -> f64 {
+        if displacement == 0. {
+            self.initial_stiffness()
+        } else {
+            self.spring_force(displacement) / displacement
+        }
+    }
+}
+
+/// Returns the slope and intercept of the segment of a multilinear curve governing the given
+/// displacement
+fn multilinear_segment(points: &[(f64, f64)], displacement: f64) -> (f64, f64) {
+    if points.len() < 2 {
+        return (0., 0.);
+    }
+    let end = points
+        .iter()
+        .position(|x| x.0 > displacement)
+        .unwrap_or(points.len() - 1)
+        .max(1);
+    let ((d0, f0), (d1, f1)) = (points[end - 1], points[end]);
+    let slope = (f1 - f0) / (d1 - d0);
+    (slope, f0 - slope * d0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skewed_spring_couples_world_directions() {
+        let mut support = Support::springs("skewed_spring", Vector6::new(2., 0., 0., 0., 0., 0.));
+        #[rustfmt::skip]
+        let axes = Matrix3::new(
+            1., -1., 0.,
+            1.,  1., 0.,
+            0.,  0., 1.,
+        );
+        support.local_axes = Some(axes);
+
+        let k = support.spring_stiffness();
+        assert_relative_eq!(k[(0, 0)], 1., max_relative = 1e-12);
+        assert_relative_eq!(k[(1, 1)], 1., max_relative = 1e-12);
+        assert_relative_eq!(k[(0, 1)], 1., max_relative = 1e-12);
+        assert_relative_eq!(k[(1, 0)], 1., max_relative = 1e-12);
+        assert_eq!(k[(2, 2)], 0.);
+    }
+
+    #[test]
+    fn multilinear_spring_follows_curve_and_extrapolates() {
+        let spring = SupportCondition::Multilinear(vec![(-2., -10.), (0., 0.), (1., 2.), (3., 3.)]);
+        assert_relative_eq!(spring.initial_stiffness(), 2., max_relative = 1e-12);
+        assert_relative_eq!(spring.spring_force(-1.), -5., max_relative = 1e-12);
+        assert_relative_eq!(spring.spring_force(2.), 2.5, max_relative = 1e-12);
+        assert_relative_eq!(spring.spring_force(5.), 4., max_relative = 1e-12);
+        assert_relative_eq!(spring.spring_force(-4.), -20., max_relative = 1e-12);
+        assert_relative_eq!(spring.secant_stiffness(2.), 1.25, max_relative = 1e-12);
+
+        let compression = SupportCondition::CompressionOnly(3.);
+        assert_relative_eq!(compression.spring_force(-2.), -6., max_relative = 1e-12);
+        assert_eq!(compression.secant_stiffness(0.5), 0.);
+    }
+}
@@ -0,0 +1,950 @@
+use crate::elements::membrane::{
+    membrane_local_axes, membrane_local_coordinates, membrane_strain_matrix,
+};
+use crate::elements::shell::{shell_corner_jacobians, shell_local_axes};
+use crate::models::cable::{CableElement, CableInitialState};
+use crate::models::constraint::Constraint;
+use crate::models::frame::{CrossSection, FrameElement};
+use crate::models::material::IsotropicMaterial;
+use crate::models::membrane::MembraneElement;
+use crate::models::node::{Direction, Node};
+use crate::models::shell::ShellElement;
+use crate::models::support::{Support, SupportCondition};
+use crate::ELEMENT_DATA;
+use na::{Matrix, Matrix3};
+use std::collections::HashMap;
+
+/// Nodes closer than this fraction of the overall model size are considered coincident.
+const COINCIDENT_NODE_TOLERANCE: f64 = 1e-9;
+
+/// Local axes whose unit vectors have a dot product above this value are not orthogonal.
+const ORTHOGONALITY_TOLERANCE: f64 = 1e-6;
+
+/// Spring curves carrying more than this fraction of their largest force at zero displacement
+/// do not pass through the origin.
+const CURVE_ORIGIN_TOLERANCE: f64 = 1e-9;
+
+/// Shells whose corners lie further than this fraction of their mean diagonal from their mean
+/// plane are warped.
+const SHELL_WARPING_TOLERANCE: f64 = 1e-2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The model cannot be analyzed
+    Error,
+    /// The model can be analyzed but is probably not what was intended
+    Warning,
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub element_id: Option<String>,
+    pub node_ids: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|x| x.severity == Severity::Error)
+    }
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|x| x.severity == Severity::Warning)
+    }
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+    fn error(&mut self, element_id: Option<&str>, node_ids: &[&str], message: String) {
+        self.push(Severity::Error, element_id, node_ids, message);
+    }
+    fn warning(&mut self, element_id: Option<&str>, node_ids: &[&str], message: String) {
+        self.push(Severity::Warning, element_id, node_ids, message);
+    }
+    fn push(
+        &mut self,
+        severity: Severity,
+        element_id: Option<&str>,
+        node_ids: &[&str],
+        message: String,
+    ) {
+        self.issues.push(ValidationIssue {
+            severity,
+            element_id: element_id.map(|x| x.to_string()),
+            node_ids: node_ids.iter().map(|x| x.to_string()).collect(),
+            message,
+        });
+    }
+}
+
+/// Checks the model for geometry and property errors, returning every error and warning found.
+pub fn validate() -> ValidationReport {
+    let data = ELEMENT_DATA.read().unwrap();
+    let mut report = ValidationReport::default();
+
+    validate_nodes(&data.nodes, &mut report);
+
+    let mut frame_ids: Vec<&String> = data.frames.keys().collect();
+    frame_ids.sort();
+    for id in frame_ids {
+        validate_frame(&data.frames[id], &data.nodes, &mut report);
+    }
+
+    let mut cable_ids: Vec<&String> = data.cables.keys().collect();
+    cable_ids.sort();
+    for id in cable_ids {
+        validate_cable(&data.cables[id], &data.nodes, &mut report);
+    }
+
+    let mut membrane_ids: Vec<&String> = data.membranes.keys().collect();
+    membrane_ids.sort();
+    for id in membrane_ids {
+        validate_membrane(&data.membranes[id], &data.nodes, &mut report);
+    }
+
+    let mut shell_ids: Vec<&String> = data.shells.keys().collect();
+    shell_ids.sort();
+    for id in shell_ids {
+        validate_shell(&data.shells[id], &data.nodes, &mut report);
+    }
+
+    let mut support_ids: Vec<&String> = data.supports.keys().collect();
+    support_ids.sort();
+    for id in support_ids {
+        if !data.nodes.contains_key(id) {
+            report.error(
+                None,
+                &[id],
+                format!("Support references missing node id = {}", id),
+            );
+        }
+        validate_support(&data.supports[id], &mut report);
+    }
+
+    let mut mass_ids: Vec<&String> = data.nodal_masses.keys().collect();
+    mass_ids.sort();
+    for id in mass_ids {
+        if !data.nodes.contains_key(id) {
+            report.error(
+                None,
+                &[id],
+                format!("Nodal mass references missing node id = {}", id),
+            );
+        }
+        if data.nodal_masses[id]
+            .mass
+            .iter()
+            .any(|x| x.is_nan() || *x < 0.)
+        {
+            report.error(
+                None,
+                &[id],
+                format!("Nodal mass at node {} is negative", id),
+            );
+        }
+    }
+
+    validate_constraints(&data.constraints, &data.nodes, &data.supports, &mut report);
+
+    report
+}
+
+fn validate_constraints(
+    constraints: &HashMap<String, Constraint>,
+    nodes: &HashMap<String, Node>,
+    supports: &HashMap<String, Support>,
+    report: &mut ValidationReport,
+) {
+    let mut ids: Vec<&String> = constraints.keys().collect();
+    ids.sort();
+
+    // Master degrees of freedom of each slave, along with the constraint defining it
+    let mut slaves = HashMap::<(String, Direction), (&String, Vec<(String, Direction)>)>::new();
+    for id in ids {
+        let constraint = &constraints[id];
+        let missing: Vec<&str> = constraint
+            .node_ids()
+            .into_iter()
+            .filter(|x| !nodes.contains_key(*x))
+            .collect();
+        if !missing.is_empty() {
+            report.error(
+                Some(id),
+                &missing,
+                format!(
+                    "Constraint {} references missing node ids = {}",
+                    id,
+                    missing.join(", ")
+                ),
+            );
+            continue;
+        }
+        if let Constraint::RigidLink(x) = constraint {
+            if x.master_node_id == x.slave_node_id {
+                report.error(
+                    Some(id),
+                    &[&x.slave_node_id],
+                    format!(
+                        "Rigid link {} connects node {} to itself",
+                        id, x.slave_node_id
+                    ),
+                );
+                continue;
+            }
+        }
+
+        if let Constraint::Diaphragm(x) = constraint {
+            if x.slave_node_ids.contains(&x.master_node_id) {
+                report.error(
+                    Some(id),
+                    &[&x.master_node_id],
+                    format!(
+                        "Diaphragm {} ties its master node {} to itself",
+                        id, x.master_node_id
+                    ),
+                );
+                continue;
+            }
+            // In-plane directions of a node stay in plane only if its local z axis is vertical
+            for slave in x.slave_node_ids.iter() {
+                let tilted = match nodes[slave].local_axes {
+                    Some(axes) => {
+                        let z = Matrix::normalize(&axes.column(2));
+                        z.x.abs().max(z.y.abs()) > ORTHOGONALITY_TOLERANCE
+                    }
+                    None => false,
+                };
+                if tilted {
+                    report.error(
+                        Some(id),
+                        &[slave],
+                        format!(
+                            "Diaphragm {} ties node {}, whose local z axis is not vertical",
+                            id, slave
+                        ),
+                    );
+                }
+            }
+        }
+
+        let equations = constraint.equations(nodes).unwrap_or_default();
+        for equation in equations.iter() {
+            let slave = &equation.slave_node_id;
+            let direction = equation.slave_direction;
+            if equation.masters.iter().any(|x| !x.coefficient.is_finite()) {
+                report.error(
+                    Some(id),
+                    &[slave],
+                    format!("Constraint {} has a non-finite coefficient", id),
+                );
+            }
+            if matches!(supports.get(slave), Some(x) if x.is_fixed(direction)) {
+                report.error(
+                    Some(id),
+                    &[slave],
+                    format!(
+                        "Constraint {} slaves {} of node {}, which a support restrains",
+                        id, direction, slave
+                    ),
+                );
+            }
+            let masters = equation
+                .masters
+                .iter()
+                .map(|x| (x.node_id.clone(), x.direction))
+                .collect();
+            if let Some((other, _)) = slaves.insert((slave.clone(), direction), (id, masters)) {
+                report.error(
+                    Some(id),
+                    &[slave],
+                    format!(
+                        "Constraints {} and {} both slave {} of node {}",
+                        other, id, direction, slave
+                    ),
+                );
+            }
+        }
+    }
+
+    // A slave reached again by following its masters through other constraints is a cycle
+    let mut keys: Vec<&(String, Direction)> = slaves.keys().collect();
+    keys.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.index().cmp(&b.1.index())));
+    for key in keys {
+        let mut pending: Vec<&(String, Direction)> = slaves[key].1.iter().collect();
+        let mut visited = Vec::<&(String, Direction)>::new();
+        while let Some(next) = pending.pop() {
+            if next == key {
+                report.error(
+                    Some(slaves[key].0),
+                    &[&key.0],
+                    format!(
+                        "Constraint {} makes {} of node {} depend on itself",
+                        slaves[key].0, key.1, key.0
+                    ),
+                );
+                break;
+            }
+            if visited.contains(&next) {
+                continue;
+            }
+            visited.push(next);
+            if let Some((_, masters)) = slaves.get(next) {
+                pending.extend(masters.iter());
+            }
+        }
+    }
+}
+
+fn validate_nodes(nodes: &HashMap<String, Node>, report: &mut ValidationReport) {
+    let mut sorted: Vec<&Node> = nodes.values().collect();
+    sorted.sort_by(|a, b| {
+        a.coordinate
+            .x
+            .partial_cmp(&b.coordinate.x)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    for node in sorted.iter() {
+        let finite = node.coordinate.coords.iter().all(|x| x.is_finite());
+        if !finite {
+            report.error(
+                None,
+                &[&node.id],
+                format!("Node {} has a non-finite coordinate", node.id),
+            );
+        }
+        if let Some(local_axes) = node.local_axes {
+            for message in local_axes_errors(&local_axes) {
+                report.error(None, &[&node.id], format!("Node {} {}", node.id, message));
+            }
+        }
+    }
+
+    let mut owners = HashMap::<usize, &String>::new();
+    let mut by_id = sorted.clone();
+    by_id.sort_by(|a, b| a.id.cmp(&b.id));
+    for node in by_id.iter() {
+        for dof in node.degrees_of_freedom.iter() {
+            if let Some(owner) = owners.insert(*dof, &node.id) {
+                if owner != &node.id {
+                    report.error(
+                        None,
+                        &[owner, &node.id],
+                        format!(
+                            "Nodes {} and {} share degree of freedom {}",
+                            owner, node.id, dof
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let extent = sorted
+        .iter()
+        .flat_map(|node| node.coordinate.coords.iter())
+        .filter(|x| x.is_finite())
+        .fold(0., |acc: f64, x| acc.max(x.abs()));
+    let tolerance = COINCIDENT_NODE_TOLERANCE * extent.max(1.);
+
+    // Sweep along x so that only nodes with nearby x coordinates are compared
+    for (i, a) in sorted.iter().enumerate() {
+        for b in sorted[i + 1..].iter() {
+            if b.coordinate.x - a.coordinate.x > tolerance {
+                break;
+            }
+            if na::distance(&a.coordinate, &b.coordinate) <= tolerance {
+                report.warning(
+                    None,
+                    &[&a.id, &b.id],
+                    format!("Nodes {} and {} are coincident", a.id, b.id),
+                );
+            }
+        }
+    }
+}
+
+fn validate_frame(
+    frame: &FrameElement,
+    nodes: &HashMap<String, Node>,
+    report: &mut ValidationReport,
+) {
+    let id = Some(frame.id.as_str());
+
+    let start = nodes.get(&frame.start_node_id);
+    let end = nodes.get(&frame.end_node_id);
+    if start.is_none() {
+        report.error(
+            id,
+            &[&frame.start_node_id],
+            format!(
+                "Frame {} references missing start node id = {}",
+                frame.id, frame.start_node_id
+            ),
+        );
+    }
+    if end.is_none() {
+        report.error(
+            id,
+            &[&frame.end_node_id],
+            format!(
+                "Frame {} references missing end node id = {}",
+                frame.id, frame.end_node_id
+            ),
+        );
+    }
+    if let (Some(start), Some(end)) = (start, end) {
+        let length = na::distance(&start.coordinate, &end.coordinate);
+        if length <= COINCIDENT_NODE_TOLERANCE * start.coordinate.coords.norm().max(1.) {
+            report.error(
+                id,
+                &[&start.id, &end.id],
+                format!("Frame {} has zero length", frame.id),
+            );
+        } else {
+            let chord = (end.coordinate - start.coordinate) / length;
+            let x = frame.geometry.local_axes.column(0);
+            let norm = x.norm();
+            if norm.is_finite()
+                && norm > 0.
+                && (x.dot(&chord) <= 0. || x.cross(&chord).norm() > ORTHOGONALITY_TOLERANCE * norm)
+            {
+                report.error(
+                    id,
+                    &[&start.id, &end.id],
+                    format!(
+                        "Frame {} has a local x axis which does not run from its start node to \
+                         its end node",
+                        frame.id
+                    ),
+                );
+            }
+        }
+        let offsets = &frame.end_offsets;
+        let (flexible_start, flexible_end) =
+            offsets.flexible_ends(&start.coordinate, &end.coordinate);
+        // Measured along the member so that overlapping rigid zones count as no flexible length
+        let flexible_length =
+            (flexible_end - flexible_start).dot(&(end.coordinate - start.coordinate)) / length;
+        if !offsets
+            .start
+            .iter()
+            .chain(offsets.end.iter())
+            .all(|x| x.is_finite())
+            || !(0. ..=1.).contains(&offsets.rigid_zone_factor)
+        {
+            report.error(
+                id,
+                &[],
+                format!(
+                    "Frame {} has end offsets which are not finite or a rigid zone factor outside [0, 1]",
+                    frame.id
+                ),
+            );
+        } else if length > 0.
+            && flexible_length <= COINCIDENT_NODE_TOLERANCE * start.coordinate.coords.norm().max(1.)
+        {
+            report.error(
+                id,
+                &[&start.id, &end.id],
+                format!(
+                    "Frame {} has rigid end zones which leave no flexible length",
+                    frame.id
+                ),
+            );
+        }
+    }
+
+    for message in cross_section_errors(&frame.geometry.cross_section) {
+        report.error(id, &[], format!("Frame {} {}", frame.id, message));
+    }
+    if let Some(taper) = &frame.geometry.taper {
+        for message in cross_section_errors(&taper.end_cross_section) {
+            report.error(
+                id,
+                &[],
+                format!("Frame {} at its tapered end {}", frame.id, message),
+            );
+        }
+    }
+    if let Some(foundation) = &frame.foundation {
+        for (axis, k) in [("y", foundation.ky), ("z", foundation.kz)].iter() {
+            if !k.is_finite() || *k < 0. {
+                report.error(
+                    id,
+                    &[],
+                    format!(
+                        "Frame {} has invalid foundation modulus along local {} = {}",
+                        frame.id, axis, k
+                    ),
+                );
+            }
+        }
+    }
+    for message in material_errors(&frame.material) {
+        report.error(id, &[], format!("Frame {} {}", frame.id, message));
+    }
+    for message in local_axes_errors(&frame.geometry.local_axes) {
+        report.error(id, &[], format!("Frame {} {}", frame.id, message));
+    }
+}
+
+fn validate_cable(
+    cable: &CableElement,
+    nodes: &HashMap<String, Node>,
+    report: &mut ValidationReport,
+) {
+    let id = Some(cable.id.as_str());
+
+    for (end, node_id) in [("start", &cable.start_node_id), ("end", &cable.end_node_id)].iter() {
+        if !nodes.contains_key(*node_id) {
+            report.error(
+                id,
+                &[node_id],
+                format!(
+                    "Cable {} references missing {} node id = {}",
+                    cable.id, end, node_id
+                ),
+            );
+        }
+    }
+    if let (Some(start), Some(end)) = (
+        nodes.get(&cable.start_node_id),
+        nodes.get(&cable.end_node_id),
+    ) {
+        let length = na::distance(&start.coordinate, &end.coordinate);
+        if length <= COINCIDENT_NODE_TOLERANCE * start.coordinate.coords.norm().max(1.) {
+            report.error(
+                id,
+                &[&start.id, &end.id],
+                format!("Cable {} has zero length", cable.id),
+            );
+        }
+    }
+
+    if cable.area.is_nan() || cable.area <= 0. {
+        report.error(
+            id,
+            &[],
+            format!("Cable {} has non-positive area = {}", cable.id, cable.area),
+        );
+    }
+    if !cable.weight.is_finite() || cable.weight < 0. {
+        report.error(
+            id,
+            &[],
+            format!("Cable {} has invalid weight = {}", cable.id, cable.weight),
+        );
+    }
+    match cable.initial_state {
+        CableInitialState::Pretension(x) if !x.is_finite() || x < 0. => report.error(
+            id,
+            &[],
+            format!("Cable {} has invalid pretension = {}", cable.id, x),
+        ),
+        CableInitialState::UnstressedLength(x) if !x.is_finite() || x <= 0. => report.error(
+            id,
+            &[],
+            format!(
+                "Cable {} has non-positive unstressed length = {}",
+                cable.id, x
+            ),
+        ),
+        _ => (),
+    }
+    for message in material_errors(&cable.material) {
+        report.error(id, &[], format!("Cable {} {}", cable.id, message));
+    }
+}
+
+fn validate_membrane(
+    membrane: &MembraneElement,
+    nodes: &HashMap<String, Node>,
+    report: &mut ValidationReport,
+) {
+    let id = Some(membrane.id.as_str());
+
+    if membrane.node_ids.len() != 3 && membrane.node_ids.len() != 6 {
+        report.error(
+            id,
+            &[],
+            format!(
+                "Membrane {} has {} nodes rather than 3 or 6",
+                membrane.id,
+                membrane.node_ids.len()
+            ),
+        );
+        return;
+    }
+    let missing: Vec<&str> = membrane
+        .node_ids
+        .iter()
+        .map(|x| x.as_str())
+        .filter(|x| !nodes.contains_key(*x))
+        .collect();
+    if !missing.is_empty() {
+        report.error(
+            id,
+            &missing,
+            format!(
+                "Membrane {} references missing node ids = {}",
+                membrane.id,
+                missing.join(", ")
+            ),
+        );
+    } else {
+        let coordinates: Vec<_> = membrane
+            .node_ids
+            .iter()
+            .map(|x| nodes[x].coordinate)
+            .collect();
+        let node_ids: Vec<&str> = membrane.node_ids.iter().map(|x| x.as_str()).collect();
+        let (first, second) = (
+            coordinates[1] - coordinates[0],
+            coordinates[2] - coordinates[0],
+        );
+        let scale = first.norm().max(second.norm());
+        if first.cross(&second).norm() <= COINCIDENT_NODE_TOLERANCE * scale * scale {
+            report.error(
+                id,
+                &node_ids,
+                format!("Membrane {} has zero area", membrane.id),
+            );
+        } else if coordinates.len() == 6 {
+            // Midside nodes placed too far along their edges fold the element over itself
+            let local =
+                membrane_local_coordinates(&coordinates, &membrane_local_axes(&coordinates));
+            let folded = [(0., 0.), (1., 0.), (0., 1.), (1. / 3., 1. / 3.)]
+                .iter()
+                .any(|(xi, eta)| membrane_strain_matrix(&local, *xi, *eta).0 <= 0.);
+            if folded {
+                report.error(
+                    id,
+                    &node_ids,
+                    format!(
+                        "Membrane {} is distorted by its midside nodes so that it folds over",
+                        membrane.id
+                    ),
+                );
+            }
+        }
+    }
+
+    if membrane.thickness.is_nan() || membrane.thickness <= 0. {
+        report.error(
+            id,
+            &[],
+            format!(
+                "Membrane {} has non-positive thickness = {}",
+                membrane.id, membrane.thickness
+            ),
+        );
+    }
+    for message in material_errors(&membrane.material) {
+        report.error(id, &[], format!("Membrane {} {}", membrane.id, message));
+    }
+}
+
+fn validate_shell(
+    shell: &ShellElement,
+    nodes: &HashMap<String, Node>,
+    report: &mut ValidationReport,
+) {
+    let id = Some(shell.id.as_str());
+
+    if shell.node_ids.len() != 4 {
+        report.error(
+            id,
+            &[],
+            format!(
+                "Shell {} has {} nodes rather than 4",
+                shell.id,
+                shell.node_ids.len()
+            ),
+        );
+        return;
+    }
+    let missing: Vec<&str> = shell
+        .node_ids
+        .iter()
+        .map(|x| x.as_str())
+        .filter(|x| !nodes.contains_key(*x))
+        .collect();
+    if !missing.is_empty() {
+        report.error(
+            id,
+            &missing,
+            format!(
+                "Shell {} references missing node ids = {}",
+                shell.id,
+                missing.join(", ")
+            ),
+        );
+    } else {
+        let corners: Vec<_> = shell.node_ids.iter().map(|x| nodes[x].coordinate).collect();
+        let node_ids: Vec<&str> = shell.node_ids.iter().map(|x| x.as_str()).collect();
+        let (first, second) = (corners[2] - corners[0], corners[3] - corners[1]);
+        let diagonal = (first.norm() + second.norm()) / 2.;
+        if first.cross(&second).norm() <= COINCIDENT_NODE_TOLERANCE * diagonal * diagonal {
+            report.error(id, &node_ids, format!("Shell {} has zero area", shell.id));
+        } else if shell_corner_jacobians(&corners).iter().any(|x| *x <= 0.) {
+            report.error(
+                id,
+                &node_ids,
+                format!(
+                    "Shell {} is not convex with its nodes in order around its edges",
+                    shell.id
+                ),
+            );
+        } else {
+            // Each corner lies the same distance from the mean plane on alternating sides
+            let normal = shell_local_axes(&corners).column(2).into_owned();
+            let warping = ((corners[1] - corners[0]).dot(&normal)
+                - (corners[2] - corners[3]).dot(&normal))
+            .abs()
+                / 4.;
+            if warping > SHELL_WARPING_TOLERANCE * diagonal {
+                report.warning(
+                    id,
+                    &node_ids,
+                    format!(
+                        "Shell {} is warped by {} out of its mean plane, which the flat element \
+                         only approximates",
+                        shell.id, warping
+                    ),
+                );
+            }
+        }
+    }
+
+    if shell.thickness.is_nan() || shell.thickness <= 0. {
+        report.error(
+            id,
+            &[],
+            format!(
+                "Shell {} has non-positive thickness = {}",
+                shell.id, shell.thickness
+            ),
+        );
+    }
+    for message in material_errors(&shell.material) {
+        report.error(id, &[], format!("Shell {} {}", shell.id, message));
+    }
+}
+
+fn validate_support(support: &Support, report: &mut ValidationReport) {
+    let id = &support.node_id;
+    for direction in Direction::ALL.iter() {
+        match support.condition(*direction) {
+            SupportCondition::Spring(k) | SupportCondition::CompressionOnly(k)
+                if !k.is_finite() || *k < 0. =>
+            {
+                report.error(
+                    None,
+                    &[id],
+                    format!(
+                        "Support at node {} has invalid {} spring stiffness = {}",
+                        id, direction, k
+                    ),
+                );
+            }
+            SupportCondition::Multilinear(points) => {
+                for message in multilinear_curve_errors(points) {
+                    report.error(
+                        None,
+                        &[id],
+                        format!("Support at node {} {} spring {}", id, direction, message),
+                    );
+                }
+            }
+            SupportCondition::Prescribed(x) if !x.is_finite() => {
+                report.error(
+                    None,
+                    &[id],
+                    format!(
+                        "Support at node {} has non-finite {} prescribed displacement = {}",
+                        id, direction, x
+                    ),
+                );
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(local_axes) = support.local_axes {
+        for message in local_axes_errors(&local_axes) {
+            report.error(None, &[id], format!("Support at node {} {}", id, message));
+        }
+        if support.has_nonlinear_springs() {
+            report.error(
+                None,
+                &[id],
+                format!(
+                    "Support at node {} has nonlinear springs in skewed directions; give the \
+                     node local axes to align them",
+                    id
+                ),
+            );
+        }
+        if Direction::ALL.iter().any(|x| support.is_fixed(*x)) {
+            report.error(
+                None,
+                &[id],
+                format!(
+                    "Support at node {} is fixed in a skewed direction; give the node local axes \
+                     to restrain it along skewed directions",
+                    id
+                ),
+            );
+        }
+    }
+}
+
+/// Returns a description of every problem with the points of a multilinear spring curve
+pub fn multilinear_curve_errors(points: &[(f64, f64)]) -> Vec<String> {
+    if points.len() < 2 {
+        return vec!["has fewer than two points".to_string()];
+    }
+    if points.iter().any(|(d, f)| !d.is_finite() || !f.is_finite()) {
+        return vec!["has a non-finite point".to_string()];
+    }
+
+    let mut errors = Vec::<String>::new();
+    if points.windows(2).any(|x| x[1].0 <= x[0].0) {
+        errors.push("has displacements which are not strictly increasing".to_string());
+    } else {
+        let scale = points.iter().fold(0., |acc: f64, x| acc.max(x.1.abs()));
+        let force = SupportCondition::Multilinear(points.to_vec()).spring_force(0.);
+        if force.abs() > CURVE_ORIGIN_TOLERANCE * scale {
+            errors.push(format!("carries force = {} at zero displacement", force));
+        }
+    }
+    errors
+}
+
+/// Returns a description of every invalid cross section property
+pub fn cross_section_errors(section: &CrossSection) -> Vec<String> {
+    let mut errors = Vec::<String>::new();
+    let positive = [
+        ("A", section.A),
+        ("J", section.J),
+        ("Iy", section.Iy),
+        ("Iz", section.Iz),
+    ];
+    for (name, value) in positive.iter() {
+        if value.is_nan() || *value <= 0. {
+            errors.push(format!(
+                "has non-positive cross section {} = {}",
+                name, value
+            ));
+        }
+    }
+    // A shear area of zero means shear deformation is ignored
+    for (name, value) in [("Avy", section.Avy), ("Avz", section.Avz)].iter() {
+        if value.is_nan() || *value < 0. {
+            errors.push(format!("has negative cross section {} = {}", name, value));
+        }
+    }
+    errors
+}
+
+/// Returns a description of every invalid material property
+pub fn material_errors(material: &IsotropicMaterial) -> Vec<String> {
+    let mut errors = Vec::<String>::new();
+    if material.E.is_nan() || material.E <= 0. {
+        errors.push(format!("has non-positive modulus E = {}", material.E));
+    }
+    if material.G.is_nan() || material.G <= 0. {
+        errors.push(format!("has non-positive shear modulus G = {}", material.G));
+    }
+    if !(material.nu > -1. && material.nu < 0.5) {
+        errors.push(format!(
+            "has Poisson's ratio nu = {} outside of (-1, 0.5)",
+            material.nu
+        ));
+    }
+    if material.rho.is_nan() || material.rho < 0. {
+        errors.push(format!("has negative density rho = {}", material.rho));
+    }
+    errors
+}
+
+/// Returns a description of any degenerate, non-orthogonal or left-handed local axes
+pub fn local_axes_errors(local_axes: &Matrix3<f64>) -> Vec<String> {
+    let names = ["x", "y", "z"];
+    let mut errors = Vec::<String>::new();
+
+    for (i, name) in names.iter().enumerate() {
+        let norm = local_axes.column(i).norm();
+        if !norm.is_finite() || norm <= 0. {
+            errors.push(format!("has a degenerate local {} axis", name));
+        }
+    }
+    if !errors.is_empty() {
+        return errors;
+    }
+
+    for (i, j) in [(0, 1), (0, 2), (1, 2)].iter() {
+        let a = Matrix::normalize(&local_axes.column(*i));
+        let b = Matrix::normalize(&local_axes.column(*j));
+        if a.dot(&b).abs() > ORTHOGONALITY_TOLERANCE {
+            errors.push(format!(
+                "has local {} and {} axes which are not orthogonal",
+                names[*i], names[*j]
+            ));
+        }
+    }
+    if errors.is_empty() && local_axes.determinant() < 0. {
+        errors.push("has left-handed local axes".to_string());
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::Vector3;
+
+    #[test]
+    fn poissons_ratio_must_lie_within_physical_bounds() {
+        assert!(material_errors(&IsotropicMaterial::new(200., 0.3)).is_empty());
+        assert!(material_errors(&IsotropicMaterial::new(200., -0.99)).is_empty());
+        assert_eq!(material_errors(&IsotropicMaterial::new(200., 0.5)).len(), 1);
+        assert_eq!(
+            material_errors(&IsotropicMaterial::new(200., -1.5)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn local_axes_must_be_orthogonal_and_right_handed() {
+        assert!(local_axes_errors(&Matrix3::identity()).is_empty());
+
+        let skewed = Matrix3::from_columns(&[Vector3::x(), Vector3::new(1., 1., 0.), Vector3::z()]);
+        assert_eq!(local_axes_errors(&skewed).len(), 1);
+
+        let degenerate = Matrix3::from_columns(&[Vector3::x(), Vector3::zeros(), Vector3::z()]);
+        assert_eq!(local_axes_errors(&degenerate).len(), 1);
+
+        let mirrored = Matrix3::from_columns(&[Vector3::x(), -Vector3::y(), Vector3::z()]);
+        assert_eq!(
+            local_axes_errors(&mirrored),
+            vec!["has left-handed local axes".to_string()]
+        );
+    }
+
+    #[test]
+    fn multilinear_curves_must_pass_through_origin_in_order() {
+        assert!(multilinear_curve_errors(&[(-1., -2.), (0., 0.), (2., 3.)]).is_empty());
+        assert_eq!(multilinear_curve_errors(&[(0., 0.)]).len(), 1);
+        assert_eq!(multilinear_curve_errors(&[(0., 0.), (0., 1.)]).len(), 1);
+        assert_eq!(multilinear_curve_errors(&[(-1., 0.), (1., 2.)]).len(), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use na::{Matrix, Matrix3, MatrixN, Vector3, U12};
+use na::{Matrix, Matrix3, Matrix6, MatrixN, Vector3, U12};
 
 // pub fn frame_element_stiff_matrix(element: )
 
@@ -48,6 +48,35 @@ pub fn world_to_local_transform(local_axes: &Matrix3<f64>) -> MatrixN<f64, U12>
     transform
 }
 
+/**
+ * Returns the 6x6 transformation matrix to go from world to nodal local coordinates
+ *
+ * The transformation takes the form of
+ *
+ * γ 0
+ * 0 γ
+ *
+ * so that translations and rotations of a node are rotated alike.
+ *
+ * # Arguments
+ *
+ * `local_axes` - A 3x3 matrix with each of the nodal local x, y, and z axes as column vectors
+ *
+ */
+pub fn world_to_nodal_transform(local_axes: &Matrix3<f64>) -> Matrix6<f64> {
+    let rotation: Matrix3<f64> = world_to_local_rotation(local_axes);
+
+    let mut transform = Matrix6::<f64>::zeros();
+    for i in 0..3 {
+        for j in 0..3 {
+            transform[(i, j)] = rotation[(i, j)];
+            transform[(i + 3, j + 3)] = rotation[(i, j)];
+        }
+    }
+
+    transform
+}
+
 /**
  * Returns the rotation matrix __γ__ to go from world to local coordinate system
  * 
@@ -0,0 +1,92 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector6};
+use quick_fea::analysis::buckling::*;
+use quick_fea::analysis::linear_static::*;
+use quick_fea::models::frame::FrameEndRelease;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::{Support, SupportCondition};
+use quick_fea::*;
+
+#[test]
+fn pinned_frame_column_buckles_at_euler_load() {
+    let _model = new_model();
+    let (length, e, i) = (5., 1000., 0.1);
+    let count = 8;
+    for j in 0..=count {
+        let z = length * j as f64 / count as f64;
+        add_node(node(&format!("n{}", j), j, Point3::new(0., 0., z)));
+    }
+    for j in 0..count {
+        add_frame_element(frame(
+            &format!("f{}", j),
+            &format!("n{}", j),
+            &format!("n{}", j + 1),
+            along_z(),
+            section(1., i),
+            IsotropicMaterial::new(e, 0.3),
+        ));
+    }
+    // Pinned at the base with twisting restrained, and held laterally at the top
+    let mut base = Support::pinned("n0");
+    base.Rz = SupportCondition::Fixed;
+    add_support(base);
+    let mut top = Support::pinned(&format!("n{}", count));
+    top.Uz = SupportCondition::Free;
+    add_support(top);
+    add_nodal_load(
+        &format!("n{}", count),
+        Vector6::new(0., 0., -1., 0., 0., 0.),
+    )
+    .unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+    let buckling = run_linear_buckling_analysis(&results, 1).unwrap();
+    let euler = std::f64::consts::PI.powi(2) * e * i / (length * length);
+    assert_relative_eq!(buckling.modes[0].load_factor, euler, max_relative = 1e-3);
+
+    // The column bows sideways between its supports
+    let shape = &buckling.modes[0].shape;
+    let sway = shape[&format!("n{}", count / 2)];
+    assert!(sway[0].hypot(sway[1]) > 0.);
+    assert_relative_eq!(shape["n0"][0], 0.);
+}
+
+#[test]
+fn frame_released_at_its_base_buckles_as_a_pinned_fixed_element() {
+    let _model = new_model();
+    let (length, e, i) = (5., 1000., 0.1);
+    add_node(node("n0", 0, Point3::new(0., 0., 0.)));
+    add_node(node("n1", 1, Point3::new(0., 0., length)));
+    let mut column = frame(
+        "column",
+        "n0",
+        "n1",
+        along_z(),
+        section(1., i),
+        IsotropicMaterial::new(e, 0.3),
+    );
+    column.start_releases.My = FrameEndRelease::Free;
+    column.start_releases.Mz = FrameEndRelease::Free;
+    add_frame_element(column);
+    add_support(Support::fully_fixed("n0"));
+    let mut top = Support::pinned("n1");
+    top.Uz = SupportCondition::Free;
+    add_support(top);
+    add_nodal_load("n1", Vector6::new(0., 0., -1., 0., 0., 0.)).unwrap();
+
+    // The only bending freedom is the rotation of the top, which the pinned-fixed element resists
+    // with 3 E I / L elastically and P L / 5 geometrically
+    let results = run_linear_static_analysis().unwrap();
+    let buckling = run_linear_buckling_analysis(&results, 1).unwrap();
+    assert_relative_eq!(
+        buckling.modes[0].load_factor,
+        15. * e * i / (length * length),
+        max_relative = 1e-9
+    );
+}
@@ -0,0 +1,93 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector3, Vector6};
+use quick_fea::analysis::cable::*;
+use quick_fea::models::cable::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+
+#[test]
+fn guy_cable_stiffens_a_mast_against_a_lateral_load() {
+    let _model = new_model();
+    let height = 10.;
+    let (mast_e, mast_a, mast_i) = (1000., 100., 1.);
+    add_node(node("base", 0, Point3::new(0., 0., 0.)));
+    add_node(node("top", 1, Point3::new(0., 0., height)));
+    add_node(node("anchor", 2, Point3::new(-height, 0., 0.)));
+    add_frame_element(frame(
+        "mast",
+        "base",
+        "top",
+        along_z(),
+        section(mast_a, mast_i),
+        IsotropicMaterial::new(mast_e, 0.3),
+    ));
+    add_support(Support::fully_fixed("base"));
+    add_support(Support::fully_fixed("anchor"));
+
+    // A weightless guy at 45° from the mast top back to the anchor
+    let (cable_ea, pretension) = (1000., 1.);
+    add_cable_element(CableElement {
+        id: "guy".to_string(),
+        start_node_id: "top".to_string(),
+        end_node_id: "anchor".to_string(),
+        area: 0.01,
+        material: IsotropicMaterial::new(cable_ea / 0.01, 0.3),
+        weight: 0.,
+        initial_state: CableInitialState::Pretension(pretension),
+    });
+    let load = 10.;
+    add_nodal_load("top", Vector6::new(load, 0., 0., 0., 0., 0.)).unwrap();
+
+    let analysis = run_cable_analysis(&CableAnalysisSettings::default()).unwrap();
+    assert!(analysis.converged);
+    let guy = &analysis.cables["guy"];
+    let top = analysis.results.displacements["top"];
+    let sway = top[0];
+    assert!(!guy.slack);
+
+    // The guy stretches to the displaced top with the tension of its elongation
+    let chord = (Point3::new(-height, 0., 0.) - Point3::new(sway, top[1], height + top[2])).norm();
+    assert_relative_eq!(
+        guy.start_tension,
+        cable_ea * (chord / guy.unstressed_length - 1.),
+        max_relative = 1e-9
+    );
+    // The mast bends as a cantilever under the load less the pull of the guy
+    let mast_stiffness = 3. * mast_e * mast_i / height.powi(3);
+    assert_relative_eq!(
+        sway,
+        (load + guy.start_force.x) / mast_stiffness,
+        max_relative = 1e-6
+    );
+    assert_relative_eq!(
+        top[2],
+        guy.start_force.z * height / (mast_e * mast_a),
+        max_relative = 1e-6
+    );
+    assert_relative_eq!(
+        guy.start_force
+            .normalize()
+            .dot(&Vector3::new(-1., 0., -1.).normalize()),
+        1.,
+        epsilon = 1e-3
+    );
+
+    // Linearized about the pretensioned state, the guy adds EA / 2 L₀ and its pretension T / 2 L
+    // to the lateral stiffness of the mast
+    let length = height * 2f64.sqrt();
+    let guy_stiffness = cable_ea / (2. * guy.unstressed_length) + pretension / (2. * length);
+    let linear_sway = (load - pretension / 2f64.sqrt()) / (mast_stiffness + guy_stiffness);
+    assert_relative_eq!(sway, linear_sway, max_relative = 2e-2);
+    assert_relative_eq!(
+        guy.start_tension,
+        pretension + cable_ea / guy.unstressed_length * linear_sway / 2f64.sqrt(),
+        max_relative = 2e-2
+    );
+}
@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+
+use lazy_static::lazy_static;
+use na::{Matrix3, Point3, Vector3, Vector6};
+use quick_fea::models::frame::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::node::Node;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+use std::sync::{Mutex, MutexGuard};
+
+lazy_static! {
+    static ref MODEL: Mutex<()> = Mutex::new(());
+}
+
+/// Clears the model and keeps it to the calling test until the returned guard is dropped, as
+/// the tests of a file run in parallel against the one global model.
+pub fn new_model() -> MutexGuard<'static, ()> {
+    let guard = MODEL.lock().unwrap_or_else(|x| x.into_inner());
+    clear_model();
+    guard
+}
+
+/// Returns a node with the degrees of freedom `6 index..6 index + 6` along the world axes
+pub fn node(id: &str, index: usize, coordinate: Point3<f64>) -> Node {
+    Node {
+        id: id.to_string(),
+        degrees_of_freedom: Vector6::from_iterator(6 * index..6 * index + 6),
+        coordinate,
+        local_axes: None,
+    }
+}
+
+/// Returns a rigidly connected prismatic Euler-Bernoulli frame
+pub fn frame(
+    id: &str,
+    start_node_id: &str,
+    end_node_id: &str,
+    local_axes: Matrix3<f64>,
+    cross_section: CrossSection,
+    material: IsotropicMaterial,
+) -> FrameElement {
+    FrameElement {
+        id: id.to_string(),
+        start_node_id: start_node_id.to_string(),
+        end_node_id: end_node_id.to_string(),
+        start_releases: FrameEndReleases::fully_fixed(),
+        end_releases: FrameEndReleases::fully_fixed(),
+        geometry: FrameGeometry {
+            cross_section,
+            local_axes,
+            taper: None,
+        },
+        material,
+        end_offsets: FrameEndOffsets::none(),
+        beam_theory: BeamTheory::EulerBernoulli,
+        foundation: None,
+    }
+}
+
+/// Returns a section with equal bending stiffness about both axes and no shear deformation
+#[allow(non_snake_case)]
+pub fn section(A: f64, I: f64) -> CrossSection {
+    CrossSection {
+        A,
+        Avy: 0.,
+        Avz: 0.,
+        J: 2. * I,
+        Iy: I,
+        Iz: I,
+    }
+}
+
+/// Local axes of a frame along the world x axis, with its local y axis along world y
+pub fn along_x() -> Matrix3<f64> {
+    Matrix3::identity()
+}
+
+/// Local axes of a frame along the world z axis, with its local y axis along world x
+pub fn along_z() -> Matrix3<f64> {
+    Matrix3::from_columns(&[Vector3::z(), Vector3::x(), Vector3::y()])
+}
+
+/// Adds a column of ten frames along z from `n0` at its fixed base to `n10` at its free tip,
+/// with equal bending stiffness about both axes
+pub fn cantilever(length: f64, e: f64, i: f64, mass_per_length: f64) {
+    let count = 10;
+    for j in 0..=count {
+        let z = length * j as f64 / count as f64;
+        add_node(node(&format!("n{}", j), j, Point3::new(0., 0., z)));
+    }
+    for j in 0..count {
+        add_frame_element(frame(
+            &format!("f{}", j),
+            &format!("n{}", j),
+            &format!("n{}", j + 1),
+            along_z(),
+            section(1., i),
+            IsotropicMaterial::new(e, 0.3).with_density(mass_per_length),
+        ));
+    }
+    add_support(Support::fully_fixed("n0"));
+}
@@ -0,0 +1,226 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector3, Vector6};
+use quick_fea::analysis::linear_static::*;
+use quick_fea::models::constraint::*;
+use quick_fea::models::mass::NodalMass;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+
+fn assert_forces_eq(actual: &Vector6<f64>, expected: Vector6<f64>) {
+    for i in 0..6 {
+        assert_relative_eq!(actual[i], expected[i], epsilon = 1e-9);
+    }
+}
+
+fn assert_in_plane_eq(actual: &Vector3<f64>, expected: Vector3<f64>) {
+    for (a, b) in actual.iter().zip(expected.iter()) {
+        assert_relative_eq!(a, b, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn rigid_links_transfer_the_shear_and_moment_of_their_offset_beams() {
+    let _model = new_model();
+    let material = IsotropicMaterial::new(1000., 0.3);
+    add_node(node("n0", 0, Point3::new(0., 0., 0.)));
+    add_node(node("n1", 1, Point3::new(0., 0., 3.)));
+    add_frame_element(frame(
+        "column",
+        "n0",
+        "n1",
+        along_z(),
+        section(1., 0.1),
+        material.clone(),
+    ));
+    add_support(Support::fully_fixed("n0"));
+
+    // Cantilevers on either side of the column top, each offset from it by a rigid link
+    add_node(node("n2", 2, Point3::new(0.5, 0., 3.)));
+    add_node(node("n3", 3, Point3::new(3.5, 0., 3.)));
+    add_frame_element(frame(
+        "east",
+        "n2",
+        "n3",
+        along_x(),
+        section(1., 0.1),
+        material.clone(),
+    ));
+    add_node(node("n4", 4, Point3::new(-0.5, 0., 3.)));
+    add_node(node("n5", 5, Point3::new(-2.5, 0., 3.)));
+    add_frame_element(frame(
+        "west",
+        "n5",
+        "n4",
+        along_x(),
+        section(1., 0.1),
+        material,
+    ));
+    for (id, slave) in [("east link", "n2"), ("west link", "n4")].iter() {
+        add_constraint(
+            id,
+            Constraint::RigidLink(RigidLink {
+                master_node_id: "n1".to_string(),
+                slave_node_id: slave.to_string(),
+            }),
+        );
+    }
+    add_nodal_load("n3", Vector6::new(0., 0., -6., 0., 0., 0.)).unwrap();
+    add_nodal_load("n5", Vector6::new(0., 0., -4., 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+
+    // Each link holds up its beam with the beam's shear and root moment, and hands them to the
+    // column top along with the moment of the shear about the offset
+    let east = &results.constraint_forces["east link"];
+    assert_forces_eq(&east["n2"], Vector6::new(0., 0., 6., 0., -18., 0.));
+    assert_forces_eq(&east["n1"], Vector6::new(0., 0., -6., 0., 21., 0.));
+    let west = &results.constraint_forces["west link"];
+    assert_forces_eq(&west["n4"], Vector6::new(0., 0., 4., 0., 8., 0.));
+    assert_forces_eq(&west["n1"], Vector6::new(0., 0., -4., 0., -10., 0.));
+
+    // The beam end forces match the constraint forces on the slaves, and the base carries the
+    // combined shear and moment
+    let east_end = results.frame_end_forces["east"];
+    assert_relative_eq!(east_end[2], 6., epsilon = 1e-9);
+    assert_relative_eq!(east_end[4], -18., epsilon = 1e-9);
+    assert_forces_eq(
+        &results.reactions["n0"],
+        Vector6::new(0., 0., 10., 0., -11., 0.),
+    );
+    assert!(results.equilibrium.is_satisfied(1e-9));
+}
+
+#[test]
+fn diaphragm_shares_a_lateral_load_equally_between_identical_columns() {
+    let _model = new_model();
+    for (i, x) in [0., 4.].iter().enumerate() {
+        let (base, top) = (format!("b{}", i), format!("t{}", i));
+        add_node(node(&base, 2 * i, Point3::new(*x, 0., 0.)));
+        add_node(node(&top, 2 * i + 1, Point3::new(*x, 0., 3.)));
+        add_frame_element(frame(
+            &format!("c{}", i),
+            &base,
+            &top,
+            along_z(),
+            section(1., 0.1),
+            IsotropicMaterial::new(1000., 0.3),
+        ));
+        add_support(Support::fully_fixed(&base));
+    }
+    let master = add_diaphragm(
+        "floor",
+        DiaphragmNodes::List(vec!["t0".to_string(), "t1".to_string()]),
+        DiaphragmCenter::Geometry,
+    )
+    .unwrap();
+    add_nodal_load(&master.id, Vector6::new(10., 0., 0., 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+
+    // The slab delivers half the load to the top of each column, and the master carries the
+    // applied load into the slab
+    let floor = &results.diaphragm_forces["floor"];
+    assert_eq!(floor.len(), 3);
+    for id in ["t0", "t1"].iter() {
+        assert_in_plane_eq(&floor[*id], Vector3::new(5., 0., 0.));
+        assert_forces_eq(
+            &results.constraint_forces["floor"][*id],
+            Vector6::new(5., 0., 0., 0., 0., 0.),
+        );
+    }
+    assert_in_plane_eq(&floor[&master.id], Vector3::new(-10., 0., 0.));
+    for id in ["b0", "b1"].iter() {
+        assert_relative_eq!(results.reactions[*id][0], -5., epsilon = 1e-9);
+    }
+    assert!(results.equilibrium.is_satisfied(1e-9));
+}
+
+#[test]
+fn diaphragm_twists_under_a_load_at_an_eccentric_center_of_mass() {
+    let _model = new_model();
+    let (height, e, i) = (3., 1000., 0.1);
+    let material = IsotropicMaterial::new(e, 0.3);
+    // Identical columns at the corners of a square floor, with the heavier masses along its
+    // north edge moving the center of mass from (2, 2) to (2, 3)
+    let corners = [(0., 0., 1.), (4., 0., 1.), (0., 4., 3.), (4., 4., 3.)];
+    let mut tops = Vec::new();
+    for (j, (x, y, mass)) in corners.iter().enumerate() {
+        let (base, top) = (format!("b{}", j), format!("t{}", j));
+        add_node(node(&base, 2 * j, Point3::new(*x, *y, 0.)));
+        add_node(node(&top, 2 * j + 1, Point3::new(*x, *y, height)));
+        add_frame_element(frame(
+            &format!("c{}", j),
+            &base,
+            &top,
+            along_z(),
+            section(1., i),
+            material.clone(),
+        ));
+        add_support(Support::fully_fixed(&base));
+        add_nodal_mass(NodalMass::translational(&top, *mass));
+        tops.push(top);
+    }
+    let master = add_diaphragm(
+        "floor",
+        DiaphragmNodes::List(tops.clone()),
+        DiaphragmCenter::Mass,
+    )
+    .unwrap();
+    assert_relative_eq!(master.coordinate.x, 2., max_relative = 1e-12);
+    assert_relative_eq!(master.coordinate.y, 3., max_relative = 1e-12);
+    let load = 10.;
+    add_nodal_load(&master.id, Vector6::new(load, 0., 0., 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+
+    // The floor leaves the column tops free to rotate out of plane, so each column resists sway
+    // as a cantilever and twist by its torsional stiffness. About the center of stiffness at
+    // (2, 2) the load translates the floor along X and twists it by its eccentricity of 1.
+    let sway = 3. * e * i / height.powi(3);
+    let twist = material.G * 2. * i / height;
+    let torsional = 4. * (sway * 8. + twist);
+    let translation = load / (4. * sway);
+    let rotation = -load * 1. / torsional;
+    let moved = results.displacements[&master.id];
+    assert_relative_eq!(moved[0], translation - rotation, max_relative = 1e-9);
+    assert_relative_eq!(moved[1], 0., epsilon = 1e-12);
+    assert_relative_eq!(moved[5], rotation, max_relative = 1e-9);
+
+    let floor = &results.diaphragm_forces["floor"];
+    for (top, (x, y, _)) in tops.iter().zip(corners.iter()) {
+        let ux = translation - rotation * (y - 2.);
+        let uy = rotation * (x - 2.);
+        assert_in_plane_eq(
+            &floor[top],
+            Vector3::new(sway * ux, sway * uy, twist * rotation),
+        );
+    }
+    assert_in_plane_eq(&floor[&master.id], Vector3::new(-load, 0., 0.));
+    assert!(results.equilibrium.is_satisfied(1e-9));
+}
+
+#[test]
+fn nodes_sharing_the_degrees_of_freedom_of_a_diaphragm_master_are_rejected() {
+    let _model = new_model();
+    add_node(node("t0", 0, Point3::new(0., 0., 3.)));
+    add_node(node("t1", 1, Point3::new(4., 0., 3.)));
+    let master = add_diaphragm(
+        "floor",
+        DiaphragmNodes::List(vec!["t0".to_string(), "t1".to_string()]),
+        DiaphragmCenter::Geometry,
+    )
+    .unwrap();
+    assert_eq!(master.degrees_of_freedom[0], 12);
+
+    // A node numbered after the diaphragm as the third node reuses the master's numbers
+    add_node(node("t2", 2, Point3::new(8., 0., 3.)));
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("Nodes floor and t2 share degree of freedom 12"));
+}
@@ -0,0 +1,125 @@
+extern crate nalgebra as na;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector6};
+use quick_fea::analysis::diagnostics::*;
+use quick_fea::analysis::linear_static::run_linear_static_analysis;
+use quick_fea::analysis::*;
+use quick_fea::models::frame::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::node::Direction;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+use std::collections::HashMap;
+
+/// Returns a frame along x from `start` to `end`
+fn beam(id: &str, start: &str, end: &str) -> FrameElement {
+    frame(
+        id,
+        start,
+        end,
+        along_x(),
+        section(1., 0.1),
+        IsotropicMaterial::new(1000., 0.3),
+    )
+}
+
+fn location(node_id: &str, direction: Direction) -> DofLocation {
+    DofLocation {
+        node_id: node_id.to_string(),
+        direction,
+    }
+}
+
+fn world_stiffness() -> HashMap<(usize, usize), f64> {
+    // Running the analysis computes the frame stiffnesses the assembly draws on
+    let _ = run_linear_static_analysis();
+    assemble_world_stiffness_matrix().unwrap()
+}
+
+#[test]
+fn degree_of_freedom_without_stiffness_is_reported_by_node_and_direction() {
+    let _model = new_model();
+    add_node(node("n0", 0, Point3::new(0., 0., 0.)));
+    add_node(node("n1", 1, Point3::new(4., 0., 0.)));
+    let mut cantilever = beam("f0", "n0", "n1");
+    // Releasing torsion at the tip leaves nothing to resist its rotation about x
+    cantilever.end_releases.T = FrameEndRelease::Free;
+    add_frame_element(cantilever);
+    add_support(Support::fully_fixed("n0"));
+    add_nodal_load("n1", Vector6::new(0., 1., 0., 0., 0., 0.)).unwrap();
+
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("Node n1 has no stiffness in Rx"));
+
+    let diagnostics = diagnose_world_stiffness(&world_stiffness());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::ZeroStiffness);
+    assert_eq!(diagnostics[0].node_ids, vec!["n1".to_string()]);
+    assert_eq!(
+        diagnostics[0].locations,
+        vec![location("n1", Direction::Rx)]
+    );
+}
+
+#[test]
+fn substructure_without_support_is_reported_by_node_and_direction() {
+    let _model = new_model();
+    for (i, x) in [0., 4., 10., 14.].iter().enumerate() {
+        add_node(node(&format!("n{}", i), i, Point3::new(*x, 0., 0.)));
+    }
+    add_frame_element(beam("f0", "n0", "n1"));
+    add_frame_element(beam("f1", "n2", "n3"));
+    add_support(Support::fully_fixed("n0"));
+
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("Nodes [n2, n3] form a sub-structure"));
+
+    let diagnostics = diagnose_world_stiffness(&world_stiffness());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::DisconnectedSubstructure
+    );
+    assert_eq!(
+        diagnostics[0].node_ids,
+        vec!["n2".to_string(), "n3".to_string()]
+    );
+    assert_eq!(diagnostics[0].locations.len(), 12);
+    for id in ["n2", "n3"].iter() {
+        for direction in Direction::ALL.iter() {
+            assert!(diagnostics[0].locations.contains(&location(id, *direction)));
+        }
+    }
+}
+
+#[test]
+fn hinged_mechanism_is_reported_instead_of_reaching_the_solver() {
+    let _model = new_model();
+    for i in 0..3 {
+        add_node(node(
+            &format!("n{}", i),
+            i,
+            Point3::new(4. * i as f64, 0., 0.),
+        ));
+    }
+    // A hinge at n1 lets the outer span swing about it as a rigid body, which leaves an exactly
+    // zero pivot that the sparse solver cannot recover from
+    let mut inner = beam("f0", "n0", "n1");
+    inner.end_releases = FrameEndReleases::pinned();
+    add_frame_element(inner);
+    add_frame_element(beam("f1", "n1", "n2"));
+    add_support(Support::fully_fixed("n0"));
+    add_nodal_load("n2", Vector6::new(0., 1., 0., 0., 0., 0.)).unwrap();
+
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("forms part of a mechanism"));
+
+    let diagnostics = diagnose_world_stiffness(&world_stiffness());
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics
+        .iter()
+        .all(|x| x.kind == DiagnosticKind::Mechanism));
+}
@@ -0,0 +1,94 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector6};
+use quick_fea::analysis::frame_diagrams::*;
+use quick_fea::analysis::linear_static::*;
+use quick_fea::models::frame::ElasticFoundation;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::{Support, SupportCondition};
+use quick_fea::*;
+
+#[test]
+fn long_beam_on_elastic_foundation_matches_hetenyi_under_a_point_load() {
+    let _model = new_model();
+    let (e, i, k, load) = (1000., 0.1, 100., 10.);
+    let count = 24;
+    let length = 24.;
+    for j in 0..=count {
+        let x = length * j as f64 / count as f64 - length / 2.;
+        add_node(node(&format!("n{}", j), j, Point3::new(x, 0., 0.)));
+    }
+    let mut beams = Vec::new();
+    for j in 0..count {
+        let mut beam = frame(
+            &format!("f{}", j),
+            &format!("n{}", j),
+            &format!("n{}", j + 1),
+            along_x(),
+            section(1., i),
+            IsotropicMaterial::new(e, 0.3),
+        );
+        beam.foundation = Some(ElasticFoundation { ky: k, kz: k });
+        add_frame_element(beam.clone());
+        beams.push(beam);
+    }
+    // The foundation carries the beam transversely, so only its axial and twisting motion is held
+    let middle = format!("n{}", count / 2);
+    let mut hold = Support::springs(&middle, Vector6::zeros());
+    hold.Ux = SupportCondition::Fixed;
+    hold.Rx = SupportCondition::Fixed;
+    add_support(hold);
+    add_nodal_load(&middle, Vector6::new(0., 0., -load, 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+
+    // With β = (k / 4 E I)^¼, the beam deflects by P β e^(-βx) (cos βx + sin βx) / 2k at a
+    // distance x from the load, which it bends by P / 4β, and the foundation carries all of it
+    let beta = (k / (4. * e * i)).powf(0.25);
+    let deflection = |x: f64| {
+        -load * beta / (2. * k) * (-beta * x).exp() * ((beta * x).cos() + (beta * x).sin())
+    };
+    assert_relative_eq!(
+        results.displacements[&middle][2],
+        deflection(0.),
+        max_relative = 1e-2
+    );
+    let stations = 5;
+    let mut soil = 0.;
+    for (j, beam) in beams.iter().enumerate() {
+        let diagram = frame_diagram(beam, &results.displacements, stations).unwrap();
+        let start = j as f64 - length / 2.;
+        for (station, displacement) in diagram.stations.iter().zip(diagram.displacements.iter()) {
+            let x = (start + station).abs();
+            assert_relative_eq!(
+                displacement[2],
+                deflection(x),
+                epsilon = 1e-2 * deflection(0.).abs()
+            );
+        }
+        if j == count / 2 {
+            assert_relative_eq!(
+                diagram.forces[0][4].abs(),
+                load / (4. * beta),
+                max_relative = 2e-2
+            );
+        }
+        soil += integrate_soil_pressure(&diagram);
+    }
+    assert_relative_eq!(soil, load, max_relative = 1e-2);
+}
+
+/// Integrates the soil pressure along local z over the stations by the trapezoidal rule
+fn integrate_soil_pressure(diagram: &FrameDiagram) -> f64 {
+    diagram
+        .stations
+        .windows(2)
+        .zip(diagram.soil_pressures.windows(2))
+        .map(|(x, q)| (x[1] - x[0]) * (q[0][1] + q[1][1]) / 2.)
+        .sum()
+}
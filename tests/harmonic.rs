@@ -0,0 +1,70 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::Vector6;
+use quick_fea::analysis::harmonic::*;
+use quick_fea::analysis::modal::*;
+use quick_fea::analysis::time_history::RayleighDamping;
+use std::f64::consts::PI;
+
+#[test]
+fn modal_and_rayleigh_damping_agree_at_the_first_resonance_of_a_cantilever() {
+    let _model = new_model();
+    cantilever(10., 1000., 0.1, 1.);
+    let modal = run_modal_analysis(&ModalSettings::default()).unwrap();
+    assert!(modal.converged);
+    let first = &modal.modes[0];
+    let last = modal.modes.last().unwrap();
+
+    let zeta = 0.02;
+    let settings = |damping| HarmonicSettings {
+        frequencies: vec![first.frequency],
+        loads: vec![HarmonicLoad {
+            node_id: "n10".to_string(),
+            amplitude: Vector6::new(1., 0., 0., 0., 0., 0.),
+        }],
+        damping,
+        mass_formulation: MassFormulation::Consistent,
+        output_node_ids: vec!["n10".to_string()],
+    };
+    let modal_damping = run_harmonic_analysis(&settings(HarmonicDamping::Modal {
+        damping_ratio: zeta,
+        mode_count: 12,
+    }))
+    .unwrap();
+    let rayleigh_damping = run_harmonic_analysis(&settings(HarmonicDamping::Rayleigh(
+        RayleighDamping::from_damping_ratios(
+            first.angular_frequency,
+            zeta,
+            last.angular_frequency,
+            zeta,
+        ),
+    )))
+    .unwrap();
+
+    // At resonance the repeated first pair responds with its static response amplified by
+    // 1 / 2ζ, lagging the load by a quarter cycle, and both dampings give it the same ratio
+    let tip = |x: &FrequencyResponse| {
+        let response = &x.nodes["n10"];
+        (response.amplitude[0], response.phase[0])
+    };
+    let resonant: f64 = modal.modes[..2]
+        .iter()
+        .map(|mode| mode.shape["n10"][0].powi(2))
+        .sum::<f64>()
+        / (2. * zeta * first.angular_frequency.powi(2));
+    for response in [&modal_damping[0], &rayleigh_damping[0]].iter() {
+        let (amplitude, phase) = tip(response);
+        assert_relative_eq!(amplitude, resonant, max_relative = 1e-2);
+        assert_relative_eq!(phase, -PI / 2., epsilon = 2e-2);
+    }
+    assert_relative_eq!(
+        tip(&modal_damping[0]).0,
+        tip(&rayleigh_damping[0]).0,
+        max_relative = 1e-2
+    );
+}
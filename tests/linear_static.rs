@@ -0,0 +1,159 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Matrix3, Point3, Vector3, Vector6};
+use quick_fea::analysis::iterative::*;
+use quick_fea::analysis::linear_static::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+
+/// A cantilever of four frames along x, fixed at `n0` and loaded at its tip `n4`
+fn cantilever() {
+    for i in 0..5 {
+        add_node(node(&format!("n{}", i), i, Point3::new(i as f64, 0., 0.)));
+    }
+    for i in 0..4 {
+        add_frame_element(frame(
+            &format!("f{}", i),
+            &format!("n{}", i),
+            &format!("n{}", i + 1),
+            along_x(),
+            section(1., 0.1),
+            IsotropicMaterial::new(1000., 0.3),
+        ));
+    }
+    add_support(Support::fully_fixed("n0"));
+    add_nodal_load("n4", Vector6::new(1., 2., 3., 0., 0., 0.)).unwrap();
+}
+
+#[test]
+fn conjugate_gradient_solver_matches_direct_solver_and_reports_its_convergence() {
+    let _model = new_model();
+    cantilever();
+
+    let direct = run_linear_static_analysis().unwrap();
+    assert!(direct.conjugate_gradient.is_none());
+    // A tip load P deflects a cantilever by P L³ / 3 E I
+    assert_relative_eq!(
+        direct.displacements["n4"][1],
+        2. * 64. / (3. * 1000. * 0.1),
+        max_relative = 1e-9
+    );
+
+    let iterative = run_linear_static_analysis_with_solver(&LinearSolver::ConjugateGradient(
+        ConjugateGradientSettings {
+            preconditioner: Preconditioner::IncompleteCholesky,
+            tolerance: 1e-12,
+            max_iterations: 1000,
+        },
+    ))
+    .unwrap();
+    let convergence = iterative.conjugate_gradient.unwrap();
+    assert!(convergence.converged);
+    assert_eq!(
+        convergence.residual_history.len(),
+        convergence.iterations + 1
+    );
+    for (id, displacement) in direct.displacements.iter() {
+        for i in 0..6 {
+            assert_relative_eq!(
+                iterative.displacements[id][i],
+                displacement[i],
+                epsilon = 1e-9,
+                max_relative = 1e-6
+            );
+        }
+    }
+
+    let stopped_early = run_linear_static_analysis_with_solver(&LinearSolver::ConjugateGradient(
+        ConjugateGradientSettings {
+            preconditioner: Preconditioner::Jacobi,
+            tolerance: 1e-12,
+            max_iterations: 2,
+        },
+    ))
+    .unwrap();
+    let convergence = stopped_early.conjugate_gradient.unwrap();
+    assert!(!convergence.converged);
+    assert_eq!(convergence.iterations, 2);
+    assert!(convergence.residual_history[2] > 1e-12);
+}
+
+#[test]
+fn supported_portal_frame_reports_equilibrium() {
+    let _model = new_model();
+    let coordinates = [(0., 0.), (0., 3.), (4., 0.), (4., 3.)];
+    for (i, (x, z)) in coordinates.iter().enumerate() {
+        add_node(node(&format!("n{}", i), i, Point3::new(*x, 0., *z)));
+    }
+    let material = IsotropicMaterial::new(1000., 0.3);
+    add_frame_element(frame(
+        "c0",
+        "n0",
+        "n1",
+        along_z(),
+        section(1., 0.1),
+        material.clone(),
+    ));
+    add_frame_element(frame(
+        "c1",
+        "n2",
+        "n3",
+        along_z(),
+        section(1., 0.1),
+        material.clone(),
+    ));
+    add_frame_element(frame(
+        "b",
+        "n1",
+        "n3",
+        along_x(),
+        section(1., 0.1),
+        material,
+    ));
+    add_support(Support::fully_fixed("n0"));
+    add_support(Support::fully_fixed("n2"));
+    add_nodal_load("n1", Vector6::new(5., 0., 0., 0., 0., 0.)).unwrap();
+    add_nodal_load("n3", Vector6::new(0., 0., -10., 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+    let equilibrium = &results.equilibrium;
+    assert_relative_eq!(equilibrium.applied[0], 5., max_relative = 1e-12);
+    assert_relative_eq!(equilibrium.reactions[0], -5., max_relative = 1e-9);
+    assert_relative_eq!(equilibrium.reactions[2], 10., max_relative = 1e-9);
+    assert!(equilibrium.is_satisfied(1e-9));
+    for residual in equilibrium.nodal_residuals.values() {
+        assert!(residual.force < 1e-9 && residual.moment < 1e-9);
+    }
+}
+
+#[test]
+fn frames_with_local_axes_off_their_chord_or_mirrored_are_rejected() {
+    let _model = new_model();
+    cantilever();
+    let mut stray = frame(
+        "stray",
+        "n4",
+        "n3",
+        along_x(),
+        section(1., 0.1),
+        IsotropicMaterial::new(1000., 0.3),
+    );
+    add_frame_element(stray.clone());
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("Frame stray has a local x axis which does not run"));
+
+    stray.start_node_id = "n3".to_string();
+    stray.end_node_id = "n4".to_string();
+    stray.geometry.local_axes =
+        Matrix3::from_columns(&[Vector3::x(), Vector3::y(), -Vector3::z()]);
+    add_frame_element(stray);
+    let error = run_linear_static_analysis().err().unwrap();
+    assert!(error.contains("Frame stray has left-handed local axes"));
+    assert!(!error.contains("does not run"));
+}
@@ -0,0 +1,130 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use quick_fea::analysis::linear_static::*;
+use quick_fea::analysis::modal::*;
+use quick_fea::analysis::response_spectrum::*;
+use quick_fea::analysis::*;
+
+/// Returns the eigenvalues `ω²` of the free degrees of freedom in ascending order, by a dense
+/// solution of the whole eigenproblem
+fn dense_eigenvalues(formulation: MassFormulation) -> Vec<f64> {
+    run_linear_static_analysis().unwrap();
+    let partition = partition_degrees_of_freedom();
+    let stiffness =
+        reduce_to_free_dofs_dense(&assemble_world_stiffness_matrix().unwrap(), &partition);
+    let mass = reduce_to_free_dofs_dense(
+        &assemble_world_mass_matrix(formulation).unwrap(),
+        &partition,
+    );
+    let l = mass.cholesky().unwrap().l();
+    let half = l.solve_lower_triangular(&stiffness).unwrap();
+    let reduced = l.solve_lower_triangular(&half.transpose()).unwrap();
+    let mut eigenvalues: Vec<f64> = reduced
+        .symmetric_eigen()
+        .eigenvalues
+        .iter()
+        .cloned()
+        .collect();
+    eigenvalues.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    eigenvalues
+}
+
+#[test]
+fn cantilever_finds_repeated_bending_modes_and_their_participating_mass() {
+    let _model = new_model();
+    let (length, e, i, m) = (10., 1000., 0.1, 1.);
+    cantilever(length, e, i, m);
+
+    let modal = run_modal_analysis(&ModalSettings::default()).unwrap();
+    assert!(modal.converged);
+    assert_eq!(modal.modes.len(), 12);
+
+    // The first bending mode of a cantilever has ω = 1.875² √(E I / m) / L², and the equal
+    // stiffness about both axes repeats it
+    let first = 1.875104f64.powi(2) * (e * i / m).sqrt() / length.powi(2);
+    assert_relative_eq!(modal.modes[0].angular_frequency, first, max_relative = 1e-4);
+    assert_relative_eq!(modal.modes[1].angular_frequency, first, max_relative = 1e-4);
+
+    // No mode below the highest returned is left out
+    let dense = dense_eigenvalues(MassFormulation::Consistent);
+    for (mode, expected) in modal.modes.iter().zip(dense.iter()) {
+        assert_relative_eq!(mode.angular_frequency, expected.sqrt(), max_relative = 1e-6);
+    }
+
+    // The repeated pair engages 61% of the whole mass of the cantilever along each axis, while
+    // the ratios are taken of the mass at the free degrees of freedom only
+    for axis in 0..2 {
+        let pair = (modal.modes[0].effective_mass_ratios[axis]
+            + modal.modes[1].effective_mass_ratios[axis])
+            * modal.total_mass[axis];
+        assert_relative_eq!(pair, 0.613 * m * length, max_relative = 2e-2);
+    }
+    let cumulative = modal.cumulative_mass_ratios;
+    assert_relative_eq!(cumulative[0], cumulative[1], max_relative = 1e-6);
+    assert!(cumulative[0] > 0.9 && cumulative[0] <= 1. + 1e-9);
+}
+
+#[test]
+fn fewer_modes_than_a_repeated_pair_still_start_from_the_lowest() {
+    let _model = new_model();
+    cantilever(10., 1000., 0.1, 1.);
+
+    let modal = run_modal_analysis(&ModalSettings {
+        mode_count: 3,
+        ..ModalSettings::default()
+    })
+    .unwrap();
+    assert!(modal.converged);
+    let dense = dense_eigenvalues(MassFormulation::Consistent);
+    for (mode, expected) in modal.modes.iter().zip(dense.iter()) {
+        assert_relative_eq!(mode.angular_frequency, expected.sqrt(), max_relative = 1e-6);
+    }
+}
+
+#[test]
+fn response_spectrum_base_shear_combines_the_modal_base_shears() {
+    let _model = new_model();
+    cantilever(10., 1000., 0.1, 1.);
+
+    // A flat spectrum along X only, combined by CQC as the repeated pair is closely spaced
+    let scale = 2.;
+    let settings = ResponseSpectrumSettings {
+        spectrum: DesignSpectrum::UserDefined(vec![(0., 1.), (100., 1.)]),
+        scale_factors: na::Vector3::new(scale, 0., 0.),
+        modal_combination: ModalCombination::Cqc {
+            damping_ratio: 0.05,
+        },
+        directional_combination: DirectionalCombination::Srss,
+        modal: ModalSettings::default(),
+    };
+    let results = run_response_spectrum_analysis(&settings).unwrap();
+    let modal = &results.modal;
+    assert!(modal.converged);
+    assert!(modal.cumulative_mass_ratios[0] > 0.9);
+
+    // Each mode shears the base by Γ² Sa, however the repeated pair is oriented
+    let frequencies: Vec<f64> = modal.modes.iter().map(|x| x.angular_frequency).collect();
+    let base_shears: Vec<f64> = modal
+        .modes
+        .iter()
+        .map(|x| x.participation_factors[0].powi(2) * scale)
+        .collect();
+    let expected = combine_modal_responses(
+        &base_shears,
+        &modal_correlation(&frequencies, settings.modal_combination),
+    );
+    let x = results.directional[0].as_ref().unwrap();
+    assert_relative_eq!(x.reactions["n0"][0], expected, max_relative = 1e-9);
+    assert!(results.directional[1].is_none() && results.directional[2].is_none());
+
+    // Nothing moves across the excitation
+    let tip = results.combined.displacements["n10"];
+    assert!(tip[0] > 0.);
+    assert!(tip[1] < 1e-6 * tip[0]);
+    assert!(results.combined.reactions["n0"][1] < 1e-6 * expected);
+}
@@ -0,0 +1,71 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector6};
+use quick_fea::analysis::p_delta::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+
+#[test]
+fn cantilever_drift_is_amplified_by_its_axial_load() {
+    let _model = new_model();
+    let (length, e, i) = (5., 1000., 0.1);
+    let count = 8;
+    for j in 0..=count {
+        let z = length * j as f64 / count as f64;
+        add_node(node(&format!("n{}", j), j, Point3::new(0., 0., z)));
+    }
+    for j in 0..count {
+        add_frame_element(frame(
+            &format!("f{}", j),
+            &format!("n{}", j),
+            &format!("n{}", j + 1),
+            along_z(),
+            section(1., i),
+            IsotropicMaterial::new(e, 0.3),
+        ));
+    }
+    add_support(Support::fully_fixed("n0"));
+    // A cantilever column buckles at π² E I / 4 L²
+    let euler = std::f64::consts::PI.powi(2) * e * i / (4. * length * length);
+    let (axial, lateral) = (0.3 * euler, 0.01);
+    let tip = format!("n{}", count);
+    add_nodal_load(&tip, Vector6::new(lateral, 0., -axial, 0., 0., 0.)).unwrap();
+
+    let p_delta = run_p_delta_analysis(&PDeltaSettings {
+        effects: SecondOrderEffects::PDeltaAndPdelta,
+        tolerance: 1e-10,
+        max_iterations: 100,
+    })
+    .unwrap();
+    assert!(p_delta.converged);
+
+    let first_order = p_delta.first_order.displacements[&tip][0];
+    let second_order = p_delta.results.displacements[&tip][0];
+    assert_relative_eq!(
+        first_order,
+        lateral * length.powi(3) / (3. * e * i),
+        max_relative = 1e-9
+    );
+
+    // The exact drift is H (tan kL − kL) / P k with k² = P / E I, close to the first-order drift
+    // amplified by 1 / (1 − P / Pe)
+    let k = (axial / (e * i)).sqrt();
+    let exact = lateral * ((k * length).tan() - k * length) / (axial * k);
+    assert_relative_eq!(second_order, exact, max_relative = 1e-3);
+    assert_relative_eq!(
+        second_order / first_order,
+        1. / (1. - axial / euler),
+        max_relative = 1e-2
+    );
+    assert_relative_eq!(
+        p_delta.amplification,
+        second_order / first_order,
+        max_relative = 1e-9
+    );
+}
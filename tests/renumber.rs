@@ -0,0 +1,148 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector6};
+use quick_fea::analysis::linear_static::run_linear_static_analysis;
+use quick_fea::analysis::renumber::*;
+use quick_fea::analysis::*;
+use quick_fea::models::constraint::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+
+/// A cantilever of five frames along x whose nodes are numbered out of order along its length
+fn scrambled_cantilever() {
+    let numbering = [0, 3, 5, 1, 4, 2];
+    for (i, index) in numbering.iter().enumerate() {
+        add_node(node(
+            &format!("n{}", i),
+            *index,
+            Point3::new(i as f64, 0., 0.),
+        ));
+    }
+    for i in 0..5 {
+        add_frame_element(frame(
+            &format!("f{}", i),
+            &format!("n{}", i),
+            &format!("n{}", i + 1),
+            along_x(),
+            section(1., 0.1),
+            IsotropicMaterial::new(1000., 0.3),
+        ));
+    }
+    add_support(Support::fully_fixed("n0"));
+    add_nodal_load("n5", Vector6::new(1., 2., 3., 0., 0., 0.)).unwrap();
+}
+
+/// Returns the half-bandwidth of the stiffness matrix handed to the solver
+fn solved_bandwidth() -> usize {
+    let world = assemble_world_stiffness_matrix().unwrap();
+    let partition = partition_degrees_of_freedom();
+    reduce_to_free_dofs(&world, &partition)
+        .keys()
+        .map(|(i, j)| i.max(j) - i.min(j))
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn renumbering_narrows_the_solved_stiffness_without_changing_the_model() {
+    let _model = new_model();
+    scrambled_cantilever();
+
+    set_renumbering_method(None);
+    let original = run_linear_static_analysis().unwrap();
+    let original_bandwidth = solved_bandwidth();
+
+    for method in [
+        RenumberingMethod::ReverseCuthillMckee,
+        RenumberingMethod::MinimumDegree,
+    ]
+    .iter()
+    {
+        set_renumbering_method(Some(*method));
+        let report = degree_of_freedom_ordering(*method).unwrap();
+        assert!(report.bandwidth_after < report.bandwidth_before);
+
+        let renumbered = run_linear_static_analysis().unwrap();
+        let renumbered_bandwidth = solved_bandwidth();
+        assert!(renumbered_bandwidth < original_bandwidth);
+        if *method == RenumberingMethod::ReverseCuthillMckee {
+            // The nodes are solved in sequence along the cantilever, so each equation only
+            // reaches the six degrees of freedom of the next node
+            assert!(renumbered_bandwidth < 12);
+        }
+        for (id, displacement) in original.displacements.iter() {
+            for i in 0..6 {
+                assert_relative_eq!(
+                    renumbered.displacements[id][i],
+                    displacement[i],
+                    epsilon = 1e-9,
+                    max_relative = 1e-9
+                );
+            }
+        }
+    }
+
+    // The ordering only permutes the equations, so the nodes keep their own numbering
+    assert_eq!(
+        get_node_by_id("n2").unwrap().degrees_of_freedom,
+        Vector6::from_iterator(30..36)
+    );
+}
+
+#[test]
+fn rigid_link_joins_the_frames_it_ties_in_the_ordering() {
+    let _model = new_model();
+    // Two parallel cantilevers held together only by a rigid link between their tips, with the
+    // nodes numbered from the base of each
+    let count = 10;
+    for (row, y) in ["a", "b"].iter().zip([0., 1.].iter()) {
+        let offset = if *row == "a" { 0 } else { count + 1 };
+        for i in 0..=count {
+            add_node(node(
+                &format!("{}{}", row, i),
+                offset + i,
+                Point3::new(i as f64, *y, 0.),
+            ));
+        }
+        for i in 0..count {
+            add_frame_element(frame(
+                &format!("{}f{}", row, i),
+                &format!("{}{}", row, i),
+                &format!("{}{}", row, i + 1),
+                along_x(),
+                section(1., 0.1),
+                IsotropicMaterial::new(1000., 0.3),
+            ));
+        }
+        add_support(Support::fully_fixed(&format!("{}0", row)));
+    }
+    add_constraint(
+        "tip link",
+        Constraint::RigidLink(RigidLink {
+            master_node_id: format!("a{}", count),
+            slave_node_id: format!("b{}", count),
+        }),
+    );
+    add_nodal_load(
+        &format!("a{}", count),
+        Vector6::new(0., 0., -1., 0., 0., 0.),
+    )
+    .unwrap();
+
+    set_renumbering_method(None);
+    run_linear_static_analysis().unwrap();
+    let original_bandwidth = solved_bandwidth();
+
+    // Condensing the slave tip couples the master tip to the last free node of the other
+    // cantilever, which the ordering places beside it
+    set_renumbering_method(Some(RenumberingMethod::ReverseCuthillMckee));
+    run_linear_static_analysis().unwrap();
+    assert!(solved_bandwidth() < original_bandwidth);
+    assert!(solved_bandwidth() < 18);
+}
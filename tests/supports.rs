@@ -0,0 +1,166 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Matrix3, Point3, Vector3, Vector6};
+use quick_fea::analysis::iterative::*;
+use quick_fea::analysis::linear_static::*;
+use quick_fea::analysis::nonlinear_supports::*;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::node::Direction;
+use quick_fea::models::support::{Support, SupportCondition};
+use quick_fea::*;
+
+/// Adds a beam of `count` frames along x from `n0` to `n{count}`
+fn beam(length: f64, count: usize) {
+    for i in 0..=count {
+        let x = length * i as f64 / count as f64;
+        add_node(node(&format!("n{}", i), i, Point3::new(x, 0., 0.)));
+    }
+    for i in 0..count {
+        add_frame_element(frame(
+            &format!("f{}", i),
+            &format!("n{}", i),
+            &format!("n{}", i + 1),
+            along_x(),
+            section(1., 0.1),
+            IsotropicMaterial::new(1000., 0.3),
+        ));
+    }
+}
+
+#[test]
+fn roller_on_an_incline_reacts_normal_to_the_plane() {
+    let _model = new_model();
+    beam(4., 2);
+    // The roller at n2 slides on a plane rising at 30° along x, whose normal is its local z axis
+    let angle = std::f64::consts::PI / 6.;
+    let along = Vector3::new(angle.cos(), 0., angle.sin());
+    let normal = Vector3::new(-angle.sin(), 0., angle.cos());
+    let mut roller_node = node("n2", 2, Point3::new(4., 0., 0.));
+    roller_node.local_axes = Some(Matrix3::from_columns(&[along, Vector3::y(), normal]));
+    add_node(roller_node);
+
+    let mut pin = Support::pinned("n0");
+    pin.Rx = SupportCondition::Fixed;
+    add_support(pin);
+    let mut roller = Support::pinned("n2");
+    roller.Ux = SupportCondition::Free;
+    add_support(roller);
+    add_nodal_load("n1", Vector6::new(0., 0., -10., 0., 0., 0.)).unwrap();
+
+    let results = run_linear_static_analysis().unwrap();
+    // Moments about the pin share the vertical load equally, and the roller reaction follows
+    // the normal, with the pin taking its horizontal component
+    let reaction = results.reactions["n2"];
+    let expected = normal * 5. / angle.cos();
+    for i in 0..3 {
+        assert_relative_eq!(reaction[i], expected[i], epsilon = 1e-9);
+    }
+    let local = results.local_reactions["n2"];
+    assert_relative_eq!(local[2], 5. / angle.cos(), max_relative = 1e-9);
+    assert_relative_eq!(local[0], 0., epsilon = 1e-9);
+    assert_relative_eq!(
+        results.reactions["n0"][0],
+        5. * angle.tan(),
+        max_relative = 1e-9
+    );
+    // The roller moves along the plane only
+    let displacement = results.displacements["n2"];
+    assert_relative_eq!(
+        Vector3::new(displacement[0], displacement[1], displacement[2]).dot(&normal),
+        0.,
+        epsilon = 1e-12
+    );
+    assert!(results.equilibrium.is_satisfied(1e-9));
+}
+
+#[test]
+fn settlement_of_a_prop_bends_a_propped_cantilever() {
+    let _model = new_model();
+    let (length, settlement) = (4., -0.01);
+    beam(length, 4);
+    add_support(Support::fully_fixed("n0"));
+    let mut prop = Support::pinned("n4");
+    prop.Ux = SupportCondition::Free;
+    prop.Uz = SupportCondition::Prescribed(settlement);
+    add_support(prop);
+
+    // Settling the prop by δ takes a force of 3 E I δ / L³ and a fixed end moment of
+    // 3 E I δ / L²
+    let ei = 1000. * 0.1;
+    let check = |results: &LinearStaticResults| {
+        assert_relative_eq!(results.displacements["n4"][2], settlement, epsilon = 1e-12);
+        assert_relative_eq!(
+            results.reactions["n4"][2],
+            3. * ei * settlement / length.powi(3),
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            results.reactions["n0"][2],
+            -3. * ei * settlement / length.powi(3),
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            results.reactions["n0"][4].abs(),
+            3. * ei * settlement.abs() / length.powi(2),
+            max_relative = 1e-6
+        );
+        // No load is applied, so the reactions balance each other
+        assert!(results.equilibrium.imbalance.amax() < 1e-9);
+    };
+
+    check(&run_linear_static_analysis().unwrap());
+    check(
+        &run_linear_static_analysis_with_solver(&LinearSolver::ConjugateGradient(
+            ConjugateGradientSettings {
+                preconditioner: Preconditioner::Jacobi,
+                tolerance: 1e-12,
+                max_iterations: 1000,
+            },
+        ))
+        .unwrap(),
+    );
+}
+
+#[test]
+fn overturned_footing_lifts_off_one_compression_only_spring() {
+    let _model = new_model();
+    beam(2., 2);
+    // The footing bears on compression-only springs at its ends, and is held at its center
+    // against sliding and twisting, with a rotational spring resisting part of the overturning
+    let (k, rotational_k) = (1000., 100.);
+    for id in ["n0", "n2"].iter() {
+        let mut spring = Support::springs(id, Vector6::zeros());
+        spring.Uz = SupportCondition::CompressionOnly(k);
+        add_support(spring);
+    }
+    add_support(Support {
+        node_id: "n1".to_string(),
+        Ux: SupportCondition::Fixed,
+        Uy: SupportCondition::Fixed,
+        Uz: SupportCondition::Free,
+        Rx: SupportCondition::Fixed,
+        Ry: SupportCondition::Spring(rotational_k),
+        Rz: SupportCondition::Fixed,
+        local_axes: None,
+    });
+    let (load, overturning) = (10., 30.);
+    add_nodal_load("n1", Vector6::new(0., 0., -load, 0., overturning, 0.)).unwrap();
+
+    let nonlinear = run_nonlinear_support_analysis(&NonlinearSupportSettings::default()).unwrap();
+    assert!(nonlinear.converged);
+    assert_eq!(
+        nonlinear.lifted_off,
+        vec![("n0".to_string(), Direction::Uz)]
+    );
+
+    let results = &nonlinear.results;
+    assert!(results.displacements["n0"][2] > 0.);
+    assert_relative_eq!(results.spring_forces["n0"][2], 0.);
+    assert_relative_eq!(results.spring_forces["n2"][2], load, max_relative = 1e-9);
+    assert!(results.equilibrium.is_satisfied(1e-9));
+}
@@ -0,0 +1,178 @@
+extern crate nalgebra as na;
+#[macro_use]
+extern crate approx;
+
+mod common;
+
+use common::*;
+use na::{Point3, Vector3, Vector6};
+use quick_fea::analysis::modal::*;
+use quick_fea::analysis::time_history::*;
+use quick_fea::models::mass::NodalMass;
+use quick_fea::models::material::IsotropicMaterial;
+use quick_fea::models::support::Support;
+use quick_fea::*;
+use std::f64::consts::PI;
+
+/// Settings for an undamped average acceleration analysis recording the given nodes and frames
+fn settings(
+    time_step: f64,
+    step_count: usize,
+    loads: Vec<DynamicLoad>,
+    output_node_ids: &[&str],
+    output_frame_ids: &[&str],
+) -> TimeHistorySettings {
+    TimeHistorySettings {
+        time_step,
+        step_count,
+        scheme: IntegrationScheme::average_acceleration(),
+        damping: RayleighDamping::default(),
+        mass_formulation: MassFormulation::Consistent,
+        loads,
+        output_node_ids: output_node_ids.iter().map(|x| x.to_string()).collect(),
+        output_frame_ids: output_frame_ids.iter().map(|x| x.to_string()).collect(),
+    }
+}
+
+/// A series holding one from time zero to `duration`
+fn step(duration: f64) -> TimeSeries {
+    TimeSeries {
+        points: vec![(0., 1.), (duration, 1.)],
+    }
+}
+
+#[test]
+fn suddenly_applied_tip_load_doubles_the_static_deflection() {
+    let _model = new_model();
+    let (length, e, i, mass) = (10., 1000., 0.1, 2.);
+    cantilever(length, e, i, 0.);
+    add_nodal_mass(NodalMass::translational("n10", mass));
+
+    // The massless cantilever carries its tip mass on a spring of 3 E I / L³, which a
+    // suddenly applied load swings to twice its static deflection half a period later
+    let force = 1.;
+    let static_deflection = force * length.powi(3) / (3. * e * i);
+    let omega = (force / static_deflection / mass).sqrt();
+    let period = 2. * PI / omega;
+    let steps = 400;
+    let results = run_time_history_analysis(&settings(
+        period / steps as f64,
+        steps,
+        vec![DynamicLoad::NodalForce {
+            node_id: "n10".to_string(),
+            force: Vector6::new(force, 0., 0., 0., 0., 0.),
+            series: step(period),
+        }],
+        &["n10"],
+        &[],
+    ))
+    .unwrap();
+
+    let tip = &results.nodes["n10"].displacements;
+    assert_eq!(tip.len(), steps + 1);
+    for (time, displacement) in results.times.iter().zip(tip.iter()) {
+        assert_relative_eq!(
+            displacement[0],
+            static_deflection * (1. - (omega * time).cos()),
+            epsilon = 1e-3 * static_deflection
+        );
+    }
+    let peak = tip.iter().map(|x| x[0]).fold(0., f64::max);
+    assert_relative_eq!(peak, 2. * static_deflection, max_relative = 1e-4);
+    assert_relative_eq!(tip[steps / 2][0], peak);
+}
+
+#[test]
+fn ground_acceleration_sways_a_portal_frame_as_its_modes_predict() {
+    let _model = new_model();
+    let (height, span, mass) = (3., 4., 5.);
+    let material = IsotropicMaterial::new(1000., 0.3);
+    for (j, x) in [0., span].iter().enumerate() {
+        let (base, top) = (format!("b{}", j), format!("t{}", j));
+        add_node(node(&base, 2 * j, Point3::new(*x, 0., 0.)));
+        add_node(node(&top, 2 * j + 1, Point3::new(*x, 0., height)));
+        add_frame_element(frame(
+            &format!("c{}", j),
+            &base,
+            &top,
+            along_z(),
+            section(1., 0.1),
+            material.clone(),
+        ));
+        add_support(Support::fully_fixed(&base));
+        add_nodal_mass(NodalMass::translational(&top, mass));
+    }
+    add_frame_element(frame(
+        "beam",
+        "t0",
+        "t1",
+        along_x(),
+        section(1., 0.2),
+        material,
+    ));
+
+    // Under a ground acceleration stepping to ag, each undamped mode responds with
+    // -Γ φ ag (1 - cos ωt) / ω², and the masses carry only translation so their six modes are
+    // exact
+    let modal = run_modal_analysis(&ModalSettings {
+        mode_count: 6,
+        ..ModalSettings::default()
+    })
+    .unwrap();
+    assert!(modal.converged);
+    assert_eq!(modal.modes.len(), 6);
+    let ground = 0.5;
+    let closed_form = |id: &str, time: f64| -> f64 {
+        modal
+            .modes
+            .iter()
+            .map(|mode| {
+                -mode.participation_factors[0]
+                    * mode.shape[id][0]
+                    * ground
+                    * (1. - (mode.angular_frequency * time).cos())
+                    / mode.angular_frequency.powi(2)
+            })
+            .sum()
+    };
+
+    let sway = modal
+        .modes
+        .iter()
+        .max_by(|a, b| {
+            a.effective_mass_ratios[0]
+                .partial_cmp(&b.effective_mass_ratios[0])
+                .unwrap()
+        })
+        .unwrap();
+    let steps = 400;
+    let results = run_time_history_analysis(&settings(
+        sway.period / steps as f64,
+        steps,
+        vec![DynamicLoad::BaseAcceleration {
+            direction: Vector3::new(ground, 0., 0.),
+            series: step(sway.period),
+        }],
+        &["t0", "t1"],
+        &["c0", "c1"],
+    ))
+    .unwrap();
+
+    let peak = (2. * closed_form("t0", sway.period / 2.)).abs();
+    for (step, time) in results.times.iter().enumerate() {
+        let mut base_shear = 0.;
+        let mut inertia = 0.;
+        for (column, top) in [("c0", "t0"), ("c1", "t1")].iter() {
+            let history = &results.nodes[*top];
+            assert_relative_eq!(
+                history.displacements[step][0],
+                closed_form(top, *time),
+                epsilon = 1e-3 * peak
+            );
+            // The columns shear their bases by the absolute acceleration of the masses they carry
+            base_shear += results.frame_end_forces[*column][step][1];
+            inertia += mass * (history.accelerations[step][0] + ground);
+        }
+        assert_relative_eq!(base_shear, inertia, epsilon = 1e-6 * mass * ground);
+    }
+}